@@ -45,12 +45,66 @@ macro_rules! test_tokenizer {
     };
 }
 
+/// Read the text of a test file, given its path relative to `TESTS_PATH`.
+pub fn load_test_text(file: &str) -> String {
+    std::fs::read_to_string(env!("TESTS_PATH").to_owned() + file).unwrap()
+}
+
 pub fn load_test_data(file: &str) -> titokens::Tokens {
     let tokenizer = test_tokenizer!();
 
-    let (tokens, _boundaries) = tokenizer
-        .tokenize(&std::fs::read_to_string(env!("TESTS_PATH").to_owned() + file).unwrap())
-        .unwrap();
+    let (tokens, _boundaries) = tokenizer.tokenize(&load_test_text(file)).unwrap();
 
     tokens
 }
+
+/// Load every text program in the corpus (`programs/`), paired with its path relative to
+/// `TESTS_PATH`.
+pub fn load_test_programs() -> Vec<(String, titokens::Tokens)> {
+    let root = env!("TESTS_PATH");
+
+    walkdir::WalkDir::new(root.to_owned() + "/programs")
+        .sort_by_file_name()
+        .into_iter()
+        .map(|file| file.unwrap())
+        .filter(|file| {
+            let name = file.file_name().to_string_lossy();
+
+            name.ends_with(".txt") && !name.to_lowercase().contains("readme")
+        })
+        .map(|file| {
+            let path = file.path().to_string_lossy();
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_string();
+
+            let tokens = load_test_data(&relative);
+
+            (relative, tokens)
+        })
+        .collect()
+}
+
+/// Runs `pass` on a copy of `program`, then runs it again on the result and panics if the second
+/// run changed anything. A pass which keeps finding something to do on its own output is either
+/// oscillating or missing an opportunity on its first run. `context` is included in the panic
+/// message, to tell which pass and program failed.
+///
+/// Returns the program after the first run.
+#[track_caller]
+pub fn assert_idempotent<P: Clone + PartialEq>(
+    mut pass: impl FnMut(&mut P),
+    program: &P,
+    context: impl std::fmt::Display,
+) -> P {
+    let mut once = program.clone();
+    pass(&mut once);
+
+    let mut twice = once.clone();
+    pass(&mut twice);
+
+    assert!(
+        once == twice,
+        "{context}: running the pass a second time changed the program"
+    );
+
+    once
+}
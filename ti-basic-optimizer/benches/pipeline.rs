@@ -0,0 +1,104 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use test_files::{load_test_data, test_tokenizer, test_version};
+use titokens::Tokens;
+
+use tibo::data::intervals::IntervalTree;
+use tibo::parse::Program;
+use tibo::Config;
+
+/// A long program with plenty of nested blocks, labels, and jumps.
+fn synthetic_program() -> Tokens {
+    // no L, since `L1` is a list.
+    let letters: Vec<char> = "ABCDEFGHIJKMNOPQRSTUVWXYZ".chars().collect();
+
+    let mut text = String::new();
+    for idx in 0..letters.len() * 10 {
+        let label = format!("{}{}", letters[idx / 10], idx % 10);
+
+        text += &format!("Lbl {label}\n");
+        text += "For(I,1,9\nIf I=3\nThen\nDisp I\nElse\n";
+        text += &format!("{}->A\nOutput(2,3,\"HI\nEnd\nEnd\n", idx % 7);
+        text += "While A>0\nA-1->A\nIf A=2:Goto ";
+        text += &format!("{label}\nEnd\n");
+    }
+
+    let (tokens, _) = test_tokenizer!().tokenize(&text).unwrap();
+
+    tokens
+}
+
+fn inputs() -> Vec<(&'static str, Tokens)> {
+    vec![
+        (
+            "bouncy_ball",
+            load_test_data("/programs/bouncy_ball/raw.txt"),
+        ),
+        ("stick_hero", load_test_data("/programs/stick_hero/raw.txt")),
+        ("synthetic", synthetic_program()),
+    ]
+}
+
+fn parse(tokens: &Tokens) -> Program {
+    Program::try_from_tokens(&mut tokens.clone()).unwrap()
+}
+
+fn pipeline(c: &mut Criterion) {
+    let config = Config::from(test_version!());
+
+    for (name, tokens) in inputs() {
+        let mut group = c.benchmark_group(name);
+
+        group.bench_function("from_tokens", |b| {
+            b.iter_batched(
+                || tokens.clone(),
+                |mut tokens| Program::try_from_tokens(&mut tokens).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function("optimize", |b| {
+            b.iter_batched(
+                || parse(&tokens),
+                |mut program| {
+                    program.optimize(&config);
+                    program
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        let mut optimized = parse(&tokens);
+        optimized.optimize(&config);
+        group.bench_function("reconstruct", |b| b.iter(|| optimized.reconstruct(&config)));
+
+        group.bench_function("control_flow", |b| {
+            b.iter_batched(
+                || {
+                    let mut program = parse(&tokens);
+                    program.optimize_label_names();
+                    program
+                },
+                |program| {
+                    program.precompute_analyses().unwrap();
+                    program
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        let blocks = optimized.lexical_blocks();
+        group.bench_function("interval_tree", |b| {
+            b.iter(|| {
+                let tree = IntervalTree::new(blocks.clone());
+                (0..optimized.lines.len())
+                    .map(|line| tree.stab(line).len())
+                    .sum::<usize>()
+            })
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, pipeline);
+criterion_main!(benches);
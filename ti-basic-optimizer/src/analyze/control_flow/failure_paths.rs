@@ -1,6 +1,8 @@
 //! Determine where conditionals will jump if their condition is false.
 //!
-//! This module provides [`Program::block_failure_paths`], [`Program::simple_failure_paths`], and [`Program::failure_paths`].
+//! This module provides [`Program::block_failure_paths`], [`Program::simple_failure_paths`], and [`Program::failure_paths`],
+//! as well as [`Program::lexical_blocks`]. [`Program::failure_path_of`] and
+//! [`Program::success_path_of`] answer for a single conditional.
 
 use crate::{
     data::intervals::IntervalTree,
@@ -10,6 +12,7 @@ use crate::{
     },
 };
 use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
 
 /// Extremely simplified model of TI-BASIC control flow (only what is necessary for this code to work)
 #[doc(hidden)]
@@ -147,6 +150,22 @@ impl Program {
         (output, eof_abusers)
     }
 
+    /// The lexical blocks of the program, for folding and indentation. Each block runs from the
+    /// line which opens it to the line *after* the `Else` or `End` which closes it, and the blocks
+    /// are sorted by their first line. Blocks without an `End` run to the end of the program.
+    ///
+    /// An `If-Then` with an `Else` is two blocks which share the `Else` line: one for the `If-Then`
+    /// and one for the `Else`.
+    ///
+    /// See also: [`Program::block_failure_paths`]
+    pub fn lexical_blocks(&self) -> Vec<Range<usize>> {
+        self.block_failure_paths()
+            .0
+            .into_iter()
+            .map(|(start, end)| start..end)
+            .collect()
+    }
+
     /// Conditionals like `Is>(`, `Ds<(`, and `If` without a `Then` skip a single line.
     ///
     /// Returns a [`BTreeMap`] mapping the line of the source statement to the line after
@@ -194,6 +213,18 @@ impl Program {
         output
     }
 
+    /// True if line `idx` is the single line skipped by an `If` without a `Then`, an `IS>(`, or a
+    /// `DS<(`: the line [`Program::simple_failure_paths`] jumps over, ignoring removed and
+    /// fictional lines. Removing such a line would make the conditional guard the line after it
+    /// instead.
+    pub fn is_guarded_single_line(&self, idx: usize) -> bool {
+        self.lines[..idx]
+            .iter()
+            .rev()
+            .find(|line| !matches!(line, Statement::None | Statement::Fiction(_)))
+            .is_some_and(Statement::guards_single_line)
+    }
+
     /// Union of [`Program::simple_failure_paths`] and [`Program::block_failure_paths`].
     pub fn failure_paths(&self) -> BTreeMap<usize, usize> {
         let mut all = self.simple_failure_paths();
@@ -201,6 +232,27 @@ impl Program {
 
         all
     }
+
+    /// Where the conditional on line `line_idx` jumps if its condition is false, as in
+    /// [`Program::failure_paths`]. For an `Else`, this is where the `If-Then` before it jumps after
+    /// its true branch. Returns `None` if the line is not a conditional.
+    pub fn failure_path_of(&self, line_idx: usize) -> Option<usize> {
+        self.failure_paths().get(&line_idx).copied()
+    }
+
+    /// Where the conditional on line `line_idx` continues if its condition is true, which is
+    /// always the next line. Returns `None` if the line is not a conditional, and for an `Else`,
+    /// which has no condition of its own.
+    pub fn success_path_of(&self, line_idx: usize) -> Option<usize> {
+        if matches!(
+            self.lines.get(line_idx).and_then(Statement::root),
+            Some(Statement::ControlFlow(ControlFlow::Else))
+        ) {
+            return None;
+        }
+
+        self.failure_path_of(line_idx).map(|_| line_idx + 1)
+    }
 }
 
 #[cfg(test)]
@@ -210,6 +262,58 @@ mod tests {
 
     use test_files::{load_test_data, test_tokenizer};
 
+    #[test]
+    fn lexical_blocks() {
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer
+            .tokenize("If A\nThen\nDisp 1\nElse\nWhile B\nEnd\nEnd\nRepeat C")
+            .unwrap();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        assert_eq!(program.lexical_blocks(), vec![0..3, 2..6, 3..5, 6..7]);
+    }
+
+    #[test]
+    fn single_conditional_paths() {
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer
+            .tokenize("If A\nThen\nDisp 1\nElse\nWhile B\nEnd\nEnd\nIS>(C,2\nDisp 2\nDisp 3")
+            .unwrap();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        for (idx, failure, success) in [
+            (0, Some(3), Some(1)),
+            (1, None, None),
+            (2, Some(6), None),
+            (3, Some(5), Some(4)),
+            (6, Some(8), Some(7)),
+            (7, None, None),
+        ] {
+            assert_eq!(program.failure_path_of(idx), failure, "line {idx}");
+            assert_eq!(program.success_path_of(idx), success, "line {idx}");
+        }
+    }
+
+    #[test]
+    fn guarded_single_lines() {
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer
+            .tokenize("If A\nDelVar B\nDisp 1\nIS>(C,2\nDisp 2\nIf D\nThen\nDisp 3\nEnd")
+            .unwrap();
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        let guarded = |program: &Program| {
+            (0..program.lines.len())
+                .filter(|&idx| program.is_guarded_single_line(idx))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(guarded(&program), vec![1, 4]);
+
+        // removed lines don't count
+        program.lines[1] = Statement::None;
+        assert_eq!(guarded(&program), vec![1, 2, 4]);
+    }
+
     #[test]
     fn control_flow_puzzle() {
         let mut tokens =
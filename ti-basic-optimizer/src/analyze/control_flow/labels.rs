@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 use crate::data::intervals::PartitionMap;
 use crate::parse::statements::control_flow::START_LABEL;
 use crate::parse::{
-    statements::{control_flow::Menu, ControlFlow, LabelName, Statement},
+    statements::{control_flow::Menu, ControlFlow, DelVarChain, LabelName, Statement},
     Program,
 };
 
@@ -40,14 +40,23 @@ impl Program {
         PartitionMap::new(begins, names)
     }
 
-    /// Compute a mapping from label names to label usages (namely, `Goto `, `Menu(`)
+    /// Compute a mapping from label names to label usages (namely, `Goto `, `Menu(`), including
+    /// those at the end of a `DelVar` chain, like `DelVar XGoto A`.
     ///
     /// If a `Menu(` references the same label name more than once, the line will appear in the
     /// usages that many times.
     pub fn label_usages(&self) -> BTreeMap<LabelName, Vec<usize>> {
         let mut usages: BTreeMap<LabelName, Vec<usize>> = BTreeMap::new();
 
-        for (idx, line) in self.lines.iter().enumerate() {
+        for (idx, mut line) in self.lines.iter().enumerate() {
+            while let Statement::DelVarChain(DelVarChain {
+                valence: Some(valence),
+                ..
+            }) = line
+            {
+                line = &**valence;
+            }
+
             match line {
                 Statement::ControlFlow(ControlFlow::Goto(label)) => {
                     usages.entry(*label).or_default().push(idx);
@@ -65,24 +74,40 @@ impl Program {
 
         usages
     }
+
+    /// The number of bytes taken up by label names, in every `Lbl`, `Goto`, and `Menu(` option.
+    pub fn label_name_bytes(&self) -> usize {
+        let declarations = self
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                Statement::ControlFlow(ControlFlow::Lbl(name)) => Some(name.byte_len()),
+                _ => None,
+            })
+            .sum::<usize>();
+
+        let usages = self
+            .label_usages()
+            .iter()
+            .map(|(name, usages)| name.byte_len() * usages.len())
+            .sum::<usize>();
+
+        declarations + usages
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::label_name;
-    use test_files::{load_test_data, test_tokenizer};
-
-    fn program() -> Program {
-        let mut tokens = load_test_data("/snippets/analysis/labels.txt");
-        let tokenizer = test_tokenizer!();
-
-        Program::from_tokens(&mut tokens, &tokenizer)
-    }
+    use test_files::{load_test_text, test_version};
 
     #[test]
     fn label_usages() {
-        let test_program = program();
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/labels.txt"),
+            test_version!(),
+        );
 
         let mut expected = BTreeMap::new();
         expected.insert(label_name!('R' 'E'), vec![1]);
@@ -94,7 +119,10 @@ mod tests {
 
     #[test]
     fn label_declarations() {
-        let test_program = program();
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/labels.txt"),
+            test_version!(),
+        );
 
         let mut expected = BTreeMap::new();
         expected.insert(label_name!('R' 'E'), 0usize);
@@ -106,7 +134,10 @@ mod tests {
 
     #[test]
     fn label_maps() {
-        let test_program = program();
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/labels.txt"),
+            test_version!(),
+        );
 
         let map = test_program.line_to_label_map();
 
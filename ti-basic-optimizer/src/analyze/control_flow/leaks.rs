@@ -0,0 +1,85 @@
+//! # Memory Leaks
+//! Every block closed by an `End` (`If-Then`, `While`, `Repeat`, and `For(`) pushes an entry onto
+//! the calculator's control flow stack, which is only popped when the matching `End` runs. Jumping
+//! out of the block with `Goto` or `Menu(` leaves the entry behind; doing this repeatedly eventually
+//! causes an `ERR:MEMORY`.
+
+use std::ops::Range;
+
+use crate::data::intervals::IntervalTree;
+use crate::parse::{
+    statements::{ControlFlow, Statement},
+    Program,
+};
+
+impl Program {
+    /// Compute the lexical blocks which leave an entry on the control flow stack until their
+    /// `End`. An `If-Then` with an `Else` is a single block spanning both halves.
+    fn stack_blocks(&self) -> IntervalTree<usize> {
+        let (paths, _) = self.block_failure_paths();
+
+        let mut blocks: Vec<Range<usize>> = vec![];
+        for (&start, &end) in paths.iter() {
+            match self.lines[start].root() {
+                Some(Statement::ControlFlow(ControlFlow::Else)) => continue,
+                Some(Statement::ControlFlow(ControlFlow::IfThen(_)))
+                    if matches!(
+                        self.lines.get(end - 1),
+                        Some(Statement::ControlFlow(ControlFlow::Else))
+                    ) =>
+                {
+                    blocks.push(start..*paths.get(&(end - 1)).unwrap_or(&end));
+                }
+
+                _ => blocks.push(start..end),
+            }
+        }
+
+        IntervalTree::new(blocks)
+    }
+
+    /// Compute the lines with a `Goto` or `Menu(` that can jump out of an enclosing `If-Then`,
+    /// `While`, `Repeat`, or `For(` block, leaking memory. See the [module documentation](self).
+    ///
+    /// Jumps to labels which are not declared are not reported.
+    pub fn loop_escaping_gotos(&self) -> Vec<usize> {
+        let blocks = self.stack_blocks();
+        let declarations = self.label_declarations();
+
+        let mut escaping = vec![];
+        for (label, usages) in self.label_usages() {
+            let Some(&target) = declarations.get(&label) else {
+                continue;
+            };
+
+            for usage in usages {
+                if blocks
+                    .stab(usage)
+                    .iter()
+                    .any(|block| !block.contains(&target))
+                {
+                    escaping.push(usage);
+                }
+            }
+        }
+
+        escaping.sort();
+        escaping.dedup();
+
+        escaping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn loop_escaping_gotos() {
+        let mut tokens = load_test_data("/snippets/analysis/leaks.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        assert_eq!(program.loop_escaping_gotos(), vec![2, 9, 19]);
+    }
+}
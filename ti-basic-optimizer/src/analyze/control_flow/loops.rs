@@ -0,0 +1,115 @@
+//! # Loop Variables
+//! `For(` reads its iterator back at every `End` to decide whether to run again, so a body which
+//! changes the iterator changes how many times the loop runs. Rewriting a loop (unrolling it,
+//! changing its step, or turning it into a `While`) is only correct when the body leaves the
+//! iterator alone.
+
+use std::ops::Range;
+
+use crate::analyze::Variable;
+use crate::parse::{
+    components::Operand,
+    expression::Expression,
+    statements::{control_flow::ForLoop, ControlFlow, Statement},
+    Program,
+};
+
+impl Program {
+    /// True if line `for_line` is a `For(` loop over a real variable whose body never changes
+    /// that variable. False if the line is not a `For(` loop.
+    ///
+    /// This is conservative: a body which invokes another program, runs a command that can write
+    /// variables the statement doesn't name (like `GetCalc(` or a regression), evaluates
+    /// `solve(`, `expr(`, or `eval(`, or jumps out of or into the loop with `Goto` or `Menu(` is
+    /// never safe.
+    pub fn loop_variable_safe(&self, for_line: usize) -> bool {
+        let Some(Statement::ControlFlow(ControlFlow::For(ForLoop {
+            iterator: Expression::Operand(Operand::NumericVarName(name)),
+            ..
+        }))) = self.lines.get(for_line).and_then(Statement::root)
+        else {
+            return false;
+        };
+        let iterator = Variable::Numeric(*name);
+
+        let Some(&end) = self.block_failure_paths().0.get(&for_line) else {
+            return false;
+        };
+        let body = for_line + 1..end;
+
+        !self.may_change_variable(body, iterator)
+    }
+
+    /// True if any line in `lines` might change `variable`, or if `Goto` or `Menu(` jumps into
+    /// or out of them. The same conservative rules as [`Program::loop_variable_safe`] apply.
+    pub fn may_change_variable(&self, lines: Range<usize>, variable: Variable) -> bool {
+        let declarations = self.label_declarations();
+        for (label, usages) in self.label_usages() {
+            let Some(target) = declarations.get(&label) else {
+                continue;
+            };
+
+            if usages
+                .iter()
+                .any(|usage| lines.contains(usage) != lines.contains(target))
+            {
+                return true;
+            }
+        }
+
+        self.lines[lines]
+            .iter()
+            .any(|line| may_change(line, variable))
+    }
+}
+
+/// True if `line` might change `variable`. See [`Program::loop_variable_safe`].
+fn may_change(line: &Statement, variable: Variable) -> bool {
+    if line.defined_variables().contains(&variable) || line.may_access_any_variable() {
+        return true;
+    }
+
+    match line {
+        Statement::ProgramInvocation(_) => true,
+        Statement::Generic(generic) => generic.affects_ans(),
+        Statement::DelVarChain(chain) => chain
+            .valence
+            .as_deref()
+            .is_some_and(|valence| may_change(valence, variable)),
+
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    #[test]
+    fn loop_variable_safe() {
+        let safe = Program::from_text(
+            "For(I,1,10\nDisp I\nI+1->J\nFor(J,1,I\nDisp J\nEnd\nEnd",
+            test_version!(),
+        );
+        assert!(safe.loop_variable_safe(0));
+        assert!(safe.loop_variable_safe(3));
+        assert!(!safe.loop_variable_safe(1));
+
+        // `expr(` in a string is just text
+        let quoted = Program::from_text("For(I,1,10\nDisp \"expr(\nEnd", test_version!());
+        assert!(quoted.loop_variable_safe(0));
+
+        for text in [
+            "For(I,1,10\nI+1->I\nEnd",
+            "For(I,1,10\nFor(I,1,5\nEnd\nEnd",
+            "For(I,1,10\nIf A\nInput I\nEnd",
+            "For(I,1,10\nprgmA\nEnd",
+            "For(I,1,10\nGoto A\nEnd\nLbl A\n5->I",
+            "For(I,1,10\nexpr(\"5->I\nEnd",
+        ] {
+            let program = Program::from_text(text, test_version!());
+            assert!(!program.loop_variable_safe(0), "{text}");
+        }
+    }
+}
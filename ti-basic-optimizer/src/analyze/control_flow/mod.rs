@@ -2,6 +2,10 @@
 
 mod failure_paths;
 mod labels;
+mod leaks;
+mod loops;
 mod parser;
+mod subroutines;
 
-pub use parser::ControlFlowGraph;
+pub(crate) use parser::ControlFlowLookup;
+pub use parser::{ControlFlowGraph, Edge};
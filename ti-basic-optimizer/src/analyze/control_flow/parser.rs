@@ -10,11 +10,11 @@ use crate::{
     },
     error_reporting::LineReport,
     parse::{
-        components::{BinOp, FunctionCall, Operand, Operator},
+        components::{BinOp, FunctionCall, Operand, Operator, StoreTarget},
         expression::Expression,
         statements::{
             control_flow::{ForLoop, IsDs, Menu, START_LABEL},
-            ControlFlow, LabelName, Statement,
+            ControlFlow, DelVarChain, LabelName, Statement,
         },
         Program,
     },
@@ -38,6 +38,38 @@ impl From<Digraph<BasicBlock>> for ControlFlowGraph {
     }
 }
 
+/// Where control may go after a [`BasicBlock`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Edge {
+    /// To the block of the same graph which starts at this line.
+    Block(usize),
+    /// To the fragment which starts at this label, with a `Goto` or `Menu(`.
+    Label(LabelName),
+}
+
+impl ControlFlowGraph {
+    /// Every out-edge of every block in this graph, as pairs of the source block's starting line
+    /// and the destination. A `Menu(` block has one edge to each of its option labels.
+    pub fn edges(&self) -> BTreeSet<(usize, Edge)> {
+        let mut edges = BTreeSet::new();
+
+        for (from, to) in self.graph.arcs() {
+            edges.insert((
+                self.graph.node(from).starting_line,
+                Edge::Block(self.graph.node(to).starting_line),
+            ));
+        }
+
+        for (_, block) in self.graph.nodes() {
+            for label in block.flow.label_targets() {
+                edges.insert((block.starting_line, Edge::Label(*label)));
+            }
+        }
+
+        edges
+    }
+}
+
 /// Simply put, Flow answers the question of "how do we decide which out-edge to take?"
 #[derive(Clone, Debug, Default)]
 pub enum Flow {
@@ -60,6 +92,16 @@ impl Flow {
     pub fn is_unknown(&self) -> bool {
         matches!(self, Flow::Unknown)
     }
+
+    /// Labels this flow may jump to. A `Goto` has one target and a `Menu(` has one per option.
+    pub fn label_targets(&self) -> &[LabelName] {
+        match self {
+            Flow::Goto(label) => std::slice::from_ref(label),
+            Flow::Menu(menu) => menu.branch_targets(),
+
+            _ => &[],
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -124,11 +166,45 @@ impl LabelFragment {
             failure_connection: None,
         });
     }
+
+    /// Names of the fragments this fragment can jump to with a `Goto`, `Menu(`, or the failure
+    /// path of a literal conditional. Falling through to the next fragment is not included.
+    pub fn label_targets(&self) -> BTreeSet<LabelName> {
+        let mut targets = BTreeSet::new();
+
+        for segment in &self.data {
+            match segment {
+                Segment::Literal {
+                    statement,
+                    failure_connection,
+                } => {
+                    match statement {
+                        ControlFlow::Goto(label) => {
+                            targets.insert(*label);
+                        }
+                        ControlFlow::Menu(menu) => targets.extend(menu.branch_targets()),
+
+                        _ => {}
+                    }
+
+                    targets.extend(failure_connection);
+                }
+                Segment::Blocks(cfg) => {
+                    targets.extend(cfg.edges().into_iter().filter_map(|(_, edge)| match edge {
+                        Edge::Label(label) => Some(label),
+                        Edge::Block(_) => None,
+                    }));
+                }
+            }
+        }
+
+        targets
+    }
 }
 
 /// Struct to hold several different useful precomputations for constructing control flow
 #[derive(Debug)]
-pub(super) struct ControlFlowLookup {
+pub(crate) struct ControlFlowLookup {
     /// TIBO treats conditionals that define a lexical block and contain a Goto, Lbl, or Menu differently.
     /// These statements are not part of control flow graphs but are optimized and handled separately.
     /// Unlike in a fully structured program, specific changes to these statements (eg. converting a
@@ -158,8 +234,28 @@ pub(super) struct ControlFlowLookup {
 }
 
 impl Program {
+    /// Get the [`ControlFlowLookup`] for this program, computing it if it is not already cached.
+    ///
+    /// Assumes [`Program::optimize_label_names`] has been run to remove duplicate labels.
+    pub(crate) fn control_flow_lookup(&self) -> Result<&ControlFlowLookup, LineReport> {
+        if let Some(cfl) = self.control_flow_lookup.get() {
+            return Ok(cfl);
+        }
+
+        let cfl = self.compute_control_flow_lookup()?;
+
+        Ok(self.control_flow_lookup.get_or_init(|| cfl))
+    }
+
+    /// Compute and cache the control flow analyses which passes share, so that the first pass to
+    /// use them doesn't pay for them.
+    ///
     /// Assumes [`Program::optimize_label_names`] has been run to remove duplicate labels.
-    fn control_flow_lookup(&self) -> Result<Box<ControlFlowLookup>, LineReport> {
+    pub fn precompute_analyses(&self) -> Result<(), LineReport> {
+        self.control_flow_lookup().map(|_| ())
+    }
+
+    fn compute_control_flow_lookup(&self) -> Result<Box<ControlFlowLookup>, LineReport> {
         let (block_failure_paths, eof_abusers) = self.block_failure_paths();
 
         let labels = self.line_to_label_map();
@@ -215,6 +311,18 @@ impl Program {
             }
         }
 
+        // An `Else` ending a literal `If-Then` block starts a block of its own, and the `End` of
+        // that block must be a literal too or the Ends will not balance.
+        for (statement, failure_path) in block_failure_paths.iter() {
+            if matches!(
+                self.lines[*statement],
+                Statement::ControlFlow(ControlFlow::Else)
+            ) && literals.contains(statement)
+            {
+                literals.insert(failure_path - 1);
+            }
+        }
+
         Ok(Box::new(ControlFlowLookup {
             literals,
             eof_abusers,
@@ -233,7 +341,8 @@ impl Program {
         // Strictly reduces number of labels -> less work
         self.optimize_label_names();
 
-        let cfl = self.control_flow_lookup()?;
+        self.control_flow_lookup()?;
+        let cfl = self.control_flow_lookup.take().unwrap();
         let mut parser = ControlFlowParser::new(cfl);
 
         for (idx, stmt) in self.lines.into_iter().enumerate() {
@@ -299,11 +408,22 @@ impl ControlFlowParser {
         match statement {
             Statement::None => Ok(()),
             Statement::ControlFlow(cf) => self.handle_control_flow(line_index, cf),
-            Statement::DelVarChain(_) if statement.is_control_flow() => Err(LineReport::new(
-                line_index,
-                "Unsupported DelVar'ed Control Flow.",
-                Some("This may be supported in later versions of the optimizer."),
-            )),
+            Statement::DelVarChain(DelVarChain {
+                deletions,
+                valence: Some(valence),
+            }) if valence.is_control_flow() => {
+                let Statement::ControlFlow(cf) = *valence else {
+                    unreachable!()
+                };
+
+                self.cur_block
+                    .push_line(Statement::DelVarChain(DelVarChain {
+                        deletions,
+                        valence: None,
+                    }));
+
+                self.handle_delvar_control_flow(line_index, cf)
+            }
 
             stmt => {
                 self.cur_block.push_line(stmt);
@@ -427,6 +547,38 @@ impl ControlFlowParser {
         Ok(())
     }
 
+    /// Control flow attached to a `DelVar` chain runs after the deletions and behaves like it
+    /// normally would, except that a block it opens is never remembered: the `End` which
+    /// reaches it closes the block around it instead (see [`Program::block_failure_paths`]). A
+    /// block like this with no block around it is kept as a literal, since its `End` is a literal.
+    fn handle_delvar_control_flow(
+        &mut self,
+        line_index: usize,
+        cf: ControlFlow,
+    ) -> Result<(), LineReport> {
+        let opens_block = matches!(
+            cf,
+            ControlFlow::While(_)
+                | ControlFlow::For(_)
+                | ControlFlow::Repeat(_)
+                | ControlFlow::IfThen(_)
+                | ControlFlow::Else
+        );
+
+        if opens_block && self.cf_stack.is_empty() {
+            self.finish_subgraph(line_index + 1);
+            self.cur_fragment.push_literal(cf);
+
+            return Ok(());
+        }
+
+        let depth = self.cf_stack.len();
+        self.handle_control_flow(line_index, cf)?;
+        self.cf_stack.truncate(depth);
+
+        Ok(())
+    }
+
     fn handle_isds(&mut self, line_index: usize, isds: IsDs, increment: bool) {
         let mutator = if increment {
             Token::OneByte(0x70) // +
@@ -470,7 +622,22 @@ impl ControlFlowParser {
     }
 
     fn handle_for_loop(&mut self, line_index: usize, forloop: ForLoop) -> Result<(), LineReport> {
-        if let Expression::Operand(Operand::NumericVarName(nvn)) = forloop.iterator {
+        // The iterator is only written here; the loop condition is phrased in terms of the bounds.
+        let iterator = match &forloop.iterator {
+            Expression::Operand(Operand::NumericVarName(name)) => {
+                Some(StoreTarget::NumericVar(*name))
+            }
+            Expression::Operand(Operand::ListAccess(access)) => {
+                Some(StoreTarget::ListIndex(access.clone()))
+            }
+            Expression::Operand(Operand::MatrixAccess(access)) => {
+                Some(StoreTarget::MatrixIndex(access.clone()))
+            }
+
+            _ => None,
+        };
+
+        if let Some(iterator) = iterator {
             let failure_path: usize = *self.cfl.block_failure_paths.get(&line_index).unwrap();
 
             self.add_edge(self.cur_block.starting_line, line_index + 1);
@@ -478,7 +645,7 @@ impl ControlFlowParser {
             self.cur_block
                 .push_line(Statement::Fiction(Box::new(Statement::Store(
                     forloop.start.clone(),
-                    nvn.into(),
+                    iterator,
                 ))));
 
             let difference = Box::new(Expression::Operator(Operator::Binary(BinOp {
@@ -514,7 +681,7 @@ impl ControlFlowParser {
             Err(LineReport::new(
                 line_index,
                 "Unsupported loop variable.",
-                None,
+                Some("Only real variables and list or matrix elements are supported."),
             ))
         }
     }
@@ -566,13 +733,159 @@ impl ControlFlowParser {
         ));
     }
 
-    #[cfg(feature = "debug-tools")]
-    pub fn finish(mut self) {
+    /// Close the last fragment and return every fragment.
+    pub fn into_fragments(mut self) -> Vec<LabelFragment> {
         self.handle_control_flow(self.cfl.line_count, ControlFlow::Return)
             .unwrap();
 
         self.finish_fragment(START_LABEL, self.cfl.line_count);
+
+        self.fragments
+    }
+
+    #[cfg(feature = "debug-tools")]
+    pub fn finish(self) {
         use crate::data::graphviz::Graphviz;
-        self.fragments.dbg();
+        self.into_fragments().dbg();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn control_flow_lookup_cached() {
+        let mut tokens =
+            load_test_data("/snippets/parsing/control-flow-shenanigans/puzzle-solution.txt");
+        let mut program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        let first: *const ControlFlowLookup = program.control_flow_lookup().unwrap();
+        let second: *const ControlFlowLookup = program.control_flow_lookup().unwrap();
+        assert_eq!(first, second);
+
+        program.invalidate_analyses();
+        assert!(program.control_flow_lookup.get().is_none());
+        assert_eq!(
+            program.control_flow_lookup().unwrap().line_count,
+            program.lines.len()
+        );
+    }
+
+    #[test]
+    fn nested_menu_literals() {
+        let mut tokens = load_test_data("/snippets/analysis/nested-menu.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+        let cfl = program.control_flow_lookup().unwrap();
+
+        // every block around the Menu, and each of their Ends
+        assert_eq!(cfl.literals, BTreeSet::from([0, 1, 2, 4, 5, 6]));
+
+        let mut tokens = load_test_data("/snippets/analysis/nested-menu-else.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+        let cfl = program.control_flow_lookup().unwrap();
+
+        assert_eq!(cfl.literals, BTreeSet::from([0, 1, 3, 4, 6]));
+
+        assert!(parse_fragments("/snippets/analysis/nested-menu.txt").is_ok());
+        assert!(parse_fragments("/snippets/analysis/nested-menu-else.txt").is_ok());
+    }
+
+    fn parse_fragments(path: &str) -> Result<Vec<LabelFragment>, LineReport> {
+        let mut tokens = load_test_data(path);
+        let mut program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        program.control_flow_lookup()?;
+        let mut parser = ControlFlowParser::new(program.control_flow_lookup.take().unwrap());
+        for (idx, stmt) in program.lines.into_iter().enumerate() {
+            parser.next_line(idx, stmt)?;
+        }
+
+        Ok(parser.into_fragments())
+    }
+
+    #[test]
+    fn for_loop_iterators() {
+        assert!(parse_fragments("/snippets/analysis/for-loop-iterators.txt").is_ok());
+
+        let report = parse_fragments("/snippets/analysis/for-loop-bad-iterator.txt")
+            .err()
+            .unwrap();
+        assert!(report.to_string().contains("on line 1"));
+    }
+
+    #[test]
+    fn delvar_control_flow() {
+        // the first line is both the start of the program and a DelVar'd loop
+        let fragments = parse_fragments("/snippets/analysis/delvar-first-line.txt").unwrap();
+        let Segment::Blocks(start) = &fragments[0].data[0] else {
+            panic!("Expected the deletion to start a block.");
+        };
+        let (_, first) = start.graph.nodes().next().unwrap();
+        assert_eq!(first.starting_line, 0);
+        assert!(matches!(first.lines[..], [Statement::DelVarChain(_)]));
+        assert!(matches!(
+            fragments[0].data[1],
+            Segment::Literal {
+                statement: ControlFlow::While(_),
+                ..
+            }
+        ));
+
+        for path in [
+            "/snippets/analysis/delvar-nested-loop.txt",
+            "/snippets/parsing/control-flow-shenanigans/delvar-control-flow.txt",
+        ] {
+            assert!(parse_fragments(path).is_ok(), "{path}");
+        }
+    }
+
+    #[test]
+    fn menu_targets() {
+        let fragments = parse_fragments("/snippets/analysis/menu-targets.txt").unwrap();
+        let targets = |name: LabelName| {
+            fragments
+                .iter()
+                .find(|fragment| fragment.name == name)
+                .unwrap()
+                .label_targets()
+        };
+
+        let a = LabelName::new(b'A', None);
+        let b = LabelName::new(b'B', None);
+        assert_eq!(targets(START_LABEL), BTreeSet::from([a, b]));
+        assert_eq!(targets(a), BTreeSet::from([b]));
+        assert_eq!(targets(b), BTreeSet::new());
+
+        let edges = |name: LabelName| {
+            let fragment = fragments
+                .iter()
+                .find(|fragment| fragment.name == name)
+                .unwrap();
+
+            fragment
+                .data
+                .iter()
+                .flat_map(|segment| match segment {
+                    Segment::Blocks(cfg) => cfg.edges(),
+                    Segment::Literal { .. } => BTreeSet::new(),
+                })
+                .collect::<BTreeSet<_>>()
+        };
+
+        assert_eq!(
+            edges(START_LABEL),
+            BTreeSet::from([(0, Edge::Label(a)), (0, Edge::Label(b))])
+        );
+        assert_eq!(edges(a), BTreeSet::from([(2, Edge::Label(b))]));
+        assert_eq!(
+            edges(b),
+            BTreeSet::from([
+                (5, Edge::Block(6)),
+                (5, Edge::Block(7)),
+                (6, Edge::Block(7))
+            ])
+        );
     }
 }
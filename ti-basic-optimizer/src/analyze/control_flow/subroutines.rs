@@ -0,0 +1,93 @@
+//! # Accidental Subroutines
+//! Without a way to call a label and return from it, some programs simulate subroutines by
+//! recording where they came from, jumping to the shared code, and jumping back:
+//! ```text
+//! 1->R:Goto S
+//! Lbl A
+//! ...
+//! 2->R:Goto S
+//! Lbl B
+//! ...
+//! Lbl S
+//! ...
+//! If R=1:Goto A
+//! Goto B
+//! ```
+//! This is slow (every `Goto` searches the program for its label) and easy to break, so it is
+//! worth pointing out. Turning the shared code into a separate program or restructuring the loop
+//! is left to the user.
+
+use std::collections::BTreeMap;
+
+use crate::parse::{
+    statements::{ControlFlow, LabelName, Statement},
+    Program,
+};
+
+impl Program {
+    /// Finds labels used like subroutines. See the [module documentation](self).
+    ///
+    /// A label is reported when it is the target of `Goto`s on two or more lines, each of those
+    /// lines is immediately followed by a `Lbl` (the return point), and the code under the label
+    /// (up to the next `Lbl`) has a `Goto` to every one of those return points.
+    ///
+    /// Returns a map from each such label to the lines which jump to it.
+    pub fn subroutine_like_labels(&self) -> BTreeMap<LabelName, Vec<usize>> {
+        let usages = self.label_usages();
+        let fragments = self.line_to_label_map();
+
+        let mut subroutines = BTreeMap::new();
+        for (label, call_sites) in usages.iter() {
+            if call_sites.len() < 2
+                || call_sites.iter().any(|&line| {
+                    !matches!(
+                        self.lines[line],
+                        Statement::ControlFlow(ControlFlow::Goto(_))
+                    )
+                })
+            {
+                continue;
+            }
+
+            let returns_to_every_caller = call_sites.iter().all(|&call_site| {
+                let Some(Statement::ControlFlow(ControlFlow::Lbl(return_label))) = self.lines
+                    [call_site + 1..]
+                    .iter()
+                    .find(|line| !matches!(line, Statement::None))
+                else {
+                    return false;
+                };
+
+                usages.get(return_label).is_some_and(|returns| {
+                    returns
+                        .iter()
+                        .any(|&line| fragments.find(&line) == Some(label))
+                })
+            });
+
+            if returns_to_every_caller {
+                subroutines.insert(*label, call_sites.clone());
+            }
+        }
+
+        subroutines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label_name;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn subroutine_like_labels() {
+        let mut tokens = load_test_data("/snippets/analysis/subroutines.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        assert_eq!(
+            program.subroutine_like_labels(),
+            BTreeMap::from([(label_name!('S'), vec![2, 6])])
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! # getKey Patterns
+//! `getKey` never waits: it gives the key being pressed at the moment it runs, or 0. Waiting for a
+//! key takes a loop, and the loop has to keep the key it saw, which trips up a lot of programs.
+//! This analysis classifies each line which reads `getKey` so that suspicious shapes can be
+//! pointed out. It does not change the program.
+
+use titokens::Token;
+
+use crate::parse::{
+    components::{NumericVarName, Operand, PseudoVariable, StoreTarget},
+    expression::Expression,
+    statements::{ControlFlow, Statement},
+    Program,
+};
+
+/// How a line uses `getKey`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GetKeyPattern {
+    /// The last line of a `Repeat` loop whose condition is the key just read, like
+    /// `Repeat K:getKey->K:End` or `Repeat Ans:getKey:End`. This waits for a key and keeps it.
+    Wait,
+    /// `getKey` in the condition of a `Repeat` loop, like `Repeat getKey:End`. This waits for a
+    /// key, but the key is thrown away.
+    DiscardedWait,
+    /// `getKey` in the condition of a `While` loop. The condition is checked as soon as the loop
+    /// is reached, when a key is almost never held down, so the loop usually doesn't run at all.
+    While,
+    /// Any other read, which only sees a key if one is held down at that moment.
+    Poll,
+}
+
+fn is_getkey(expr: &Expression) -> bool {
+    matches!(
+        expr,
+        Expression::Operand(Operand::PseudoVariable(PseudoVariable {
+            kind: Token::OneByte(0xAD)
+        }))
+    )
+}
+
+fn reads_getkey(statement: &Statement) -> bool {
+    let mut statement = statement.clone();
+    let mut found = false;
+    for expr in statement.expressions_mut() {
+        expr.walk_mut(&mut |expr| found |= is_getkey(expr));
+    }
+
+    found
+}
+
+/// `Some(None)` for `getKey` (which stores to Ans), `Some(Some(K))` for `getKey->K`.
+fn stored_key(statement: &Statement) -> Option<Option<NumericVarName>> {
+    match statement {
+        Statement::Expression(expr) if is_getkey(expr) => Some(None),
+        Statement::Store(
+            expr,
+            StoreTarget::NumericVar(name) | StoreTarget::NumericVarOrListName(name),
+        ) if is_getkey(expr) => Some(Some(*name)),
+
+        _ => None,
+    }
+}
+
+impl Program {
+    /// Classifies every line which reads `getKey`, in order.
+    pub fn getkey_patterns(&self) -> Vec<(usize, GetKeyPattern)> {
+        let block_ends = self.block_failure_paths().0;
+
+        let mut patterns = vec![];
+        for (idx, statement) in self.lines.iter().enumerate() {
+            if !reads_getkey(statement) {
+                continue;
+            }
+
+            let pattern = match statement.root() {
+                Some(Statement::ControlFlow(ControlFlow::Repeat(_))) => {
+                    GetKeyPattern::DiscardedWait
+                }
+                Some(Statement::ControlFlow(ControlFlow::While(_))) => GetKeyPattern::While,
+
+                _ => GetKeyPattern::Poll,
+            };
+
+            patterns.push((idx, pattern));
+        }
+
+        for (&start, &after_end) in &block_ends {
+            let Statement::ControlFlow(ControlFlow::Repeat(Expression::Operand(condition))) =
+                &self.lines[start]
+            else {
+                continue;
+            };
+
+            let Some(last) = (start + 1..after_end.saturating_sub(1))
+                .rev()
+                .find(|&idx| !matches!(self.lines[idx], Statement::None))
+            else {
+                continue;
+            };
+
+            let keeps_key = match (stored_key(&self.lines[last]), condition) {
+                (Some(None), Operand::Ans) => true,
+                (Some(Some(stored)), Operand::NumericVarName(checked)) => stored == *checked,
+                _ => false,
+            };
+
+            if keeps_key {
+                if let Some((_, pattern)) = patterns.iter_mut().find(|(idx, _)| *idx == last) {
+                    *pattern = GetKeyPattern::Wait;
+                }
+            }
+        }
+
+        patterns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn getkey_patterns() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/analysis/getkey.txt");
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        assert_eq!(
+            program.getkey_patterns(),
+            vec![
+                (1, GetKeyPattern::Wait),
+                (4, GetKeyPattern::Wait),
+                (6, GetKeyPattern::DiscardedWait),
+                (8, GetKeyPattern::While),
+                (10, GetKeyPattern::Poll),
+                (12, GetKeyPattern::Poll),
+                (13, GetKeyPattern::Wait),
+                (15, GetKeyPattern::Poll),
+            ]
+        );
+    }
+}
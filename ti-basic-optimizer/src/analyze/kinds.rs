@@ -0,0 +1,303 @@
+//! # Value Kinds
+//! Most operators in TI-BASIC work on reals, lists, matrices, and (for `+`) strings alike, but
+//! they don't behave the same way on each: `+` concatenates strings, `*` multiplies matrices, and
+//! list arithmetic is element-wise. Rewrites which are only valid for reals must check what they
+//! are working with first.
+
+use titokens::Token;
+
+use crate::parse::{
+    components::{BinOp, FunctionCall, Operand, Operator},
+    expression::Expression,
+};
+
+/// What an expression evaluates to, as far as can be told without running the program.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ValueKind {
+    /// A real or complex number.
+    Scalar,
+    List,
+    Matrix,
+    String,
+    /// Could be any of the above; `Ans`, for instance, depends on the line before it.
+    Unknown,
+}
+
+impl ValueKind {
+    /// Whether a value of this kind could be a `kind`.
+    pub fn may_be(self, kind: ValueKind) -> bool {
+        self == kind || self == ValueKind::Unknown
+    }
+
+    /// The kind of `a ⊕ b` for an arithmetic operator ⊕. A scalar combined with a list or matrix
+    /// is applied to each element, so the collection wins.
+    fn combine(a: ValueKind, b: ValueKind) -> ValueKind {
+        match (a, b) {
+            (ValueKind::Unknown, ValueKind::Scalar) | (ValueKind::Scalar, ValueKind::Unknown) => {
+                ValueKind::Unknown
+            }
+            (ValueKind::Unknown, known) | (known, ValueKind::Unknown) => known,
+            (ValueKind::Scalar, other) | (other, ValueKind::Scalar) => other,
+            (a, _) => a,
+        }
+    }
+}
+
+impl Expression {
+    /// Infers what this expression evaluates to from the shapes of its literals and the kinds of
+    /// the variables it names.
+    ///
+    /// `Ans` is [`ValueKind::Unknown`], as are function calls not covered by
+    /// [`FunctionCall::return_kind`] and operators applied to either where the result could be a
+    /// collection.
+    pub fn infer_kind(&self) -> ValueKind {
+        match self {
+            Expression::Operand(operand) => operand.infer_kind(),
+
+            Expression::Operator(Operator::Binary(binop)) => {
+                let left = binop.left.infer_kind();
+                let right = binop.right.infer_kind();
+
+                match binop.kind {
+                    // relational and logical operators give 0 or 1 for each element
+                    Token::OneByte(0x3C | 0x3D | 0x40 | 0x6A..=0x6F) => {
+                        if left == ValueKind::List || right == ValueKind::List {
+                            ValueKind::List
+                        } else if left.may_be(ValueKind::List) || right.may_be(ValueKind::List) {
+                            ValueKind::Unknown
+                        } else {
+                            ValueKind::Scalar
+                        }
+                    }
+
+                    _ => ValueKind::combine(left, right),
+                }
+            }
+
+            Expression::Operator(Operator::Unary(unop)) => unop.child.infer_kind(),
+            Expression::Operator(Operator::FunctionCall(call)) => call.return_kind(),
+
+            Expression::Operator(_) => ValueKind::Unknown,
+        }
+    }
+}
+
+impl FunctionCall {
+    /// What this function call evaluates to, given the kinds of its arguments.
+    ///
+    /// `Fill(`, `SortA(`, and the like are commands which overwrite their argument rather than
+    /// functions, so they don't appear here.
+    pub fn return_kind(&self) -> ValueKind {
+        let arguments = || self.arguments.iter().map(Expression::infer_kind);
+        let elementwise = || {
+            arguments()
+                .reduce(ValueKind::combine)
+                .unwrap_or(ValueKind::Unknown)
+        };
+
+        match self.kind.into() {
+            // seq( cumSum( ΔList( timeCnv( randIntNoRep(
+            0x23 | 0xBB29 | 0xBB2C | 0xEF05 | 0xEF35 => ValueKind::List,
+
+            // rowSwap( row+( *row( *row+( randM( identity( ref( rref(
+            0x15..=0x18 | 0x20 | 0xB4 | 0xBB2D | 0xBB2E => ValueKind::Matrix,
+
+            // solve( fnInt( nDeriv( fMin( fMax( det( sum( prod( mean( median( stdDev( variance(
+            // inString( length( checkTmr( dayOfWk(
+            0x22 | 0x24 | 0x25 | 0x27 | 0x28 | 0xB3 | 0xB6 | 0xB7 | 0x21 | 0x1F | 0xBB0D
+            | 0xBB0E | 0xBB0F | 0xBB2B | 0xEF02 | 0xEF06 => ValueKind::Scalar,
+
+            // getDtStr( getTmStr( toString(
+            0xEF07 | 0xEF08 | 0xEF97 => ValueKind::String,
+
+            // sub( with one argument divides by 100
+            0xBB0C if self.arguments.len() == 3 => ValueKind::String,
+            0xBB0C => elementwise(),
+
+            // augment( joins two lists or two matrices
+            0x14 => match arguments().next() {
+                Some(kind @ (ValueKind::List | ValueKind::Matrix)) => kind,
+                _ => ValueKind::Unknown,
+            },
+
+            // dim( gives the length of a list, or {rows,columns} of a matrix
+            0xB5 => match arguments().next() {
+                Some(ValueKind::List) => ValueKind::Scalar,
+                Some(ValueKind::Matrix) => ValueKind::List,
+                _ => ValueKind::Unknown,
+            },
+
+            // min( and max( of a single list reduce it; of two arguments, work element-wise
+            0x19 | 0x1A if self.arguments.len() == 1 => ValueKind::Scalar,
+
+            // randInt( randNorm( randBin( make a list when given a count
+            0xBB0A | 0xBB1F | 0xBB0B if self.arguments.len() == 3 => ValueKind::List,
+
+            // binompdf( binomcdf( without x give the whole distribution, for x = 0 through n
+            0xBB15 | 0xBB16 if self.arguments.len() == 2 => ValueKind::List,
+
+            // these work element-wise on lists
+            0x12 // round(
+            | 0x19 // max(
+            | 0x1A // min(
+            | 0xB1 // int(
+            | 0xB2 // abs(
+            | 0xB8..=0xBA // not( iPart( fPart(
+            | 0xBC..=0xCD // sqrt( through tanh^-1(
+            | 0xBB08..=0xBB0B // lcm( gcd( randInt( randBin(
+            | 0xBB10..=0xBB1F // the distributions, randNorm(
+            | 0xBB25..=0xBB28 // conj( real( imag( angle(
+            | 0xEF13 // invT(
+            | 0xEF32 // remainder(
+            | 0xEF34 // logBASE(
+            | 0xEF95 => elementwise(), // invBinom(
+
+            _ => ValueKind::Unknown,
+        }
+    }
+}
+
+impl Operand {
+    fn infer_kind(&self) -> ValueKind {
+        match self {
+            Operand::NumericVarName(_)
+            | Operand::NumericLiteral(_)
+            | Operand::Fraction(_)
+            | Operand::ListAccess(_)
+            | Operand::MatrixAccess(_)
+            | Operand::I
+            | Operand::TblInput
+            | Operand::WindowVarName(_) => ValueKind::Scalar,
+
+            Operand::ListName(_) | Operand::ListLiteral(_) => ValueKind::List,
+            Operand::MatrixName(_) => ValueKind::Matrix,
+            Operand::StringName(_) | Operand::StringLiteral(_) => ValueKind::String,
+
+            Operand::Rand(rand) => {
+                if rand.count.is_some() {
+                    ValueKind::List
+                } else {
+                    ValueKind::Scalar
+                }
+            }
+
+            Operand::PseudoVariable(pseudo) => match pseudo.kind {
+                Token::TwoByte(0xEF, 0x09 | 0x0A) => ValueKind::List, // getDate, getTime
+                _ => ValueKind::Scalar,
+            },
+
+            Operand::Expression(expr) => expr.infer_kind(),
+
+            Operand::EquationName(_)
+            | Operand::EquationAccess(_)
+            | Operand::Ans
+            | Operand::Piecewise(_) => ValueKind::Unknown,
+        }
+    }
+}
+
+impl BinOp {
+    /// Whether [`BinOp::opposite`] is safe to use for these particular operands.
+    ///
+    /// `+` does not commute when concatenating strings, and `*` does not commute when multiplying
+    /// two matrices.
+    pub fn operands_commute(&self) -> bool {
+        let left = self.left.infer_kind();
+        let right = self.right.infer_kind();
+
+        // if either side is known not to be a string (or matrix), the program can only run if the
+        // other side isn't one either
+        let both_may_be = |kind| left.may_be(kind) && right.may_be(kind);
+
+        match self.kind {
+            Token::OneByte(0x70) => !both_may_be(ValueKind::String),
+            Token::OneByte(0x82) => !both_may_be(ValueKind::Matrix),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parse;
+    use test_files::test_tokenizer;
+
+    fn expression(text: &str) -> Expression {
+        let (mut tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+        Expression::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn binop(text: &str) -> BinOp {
+        match expression(text) {
+            Expression::Operator(Operator::Binary(binop)) => binop,
+            _ => panic!("{text} is not a binary operation"),
+        }
+    }
+
+    #[test]
+    fn infer_kind() {
+        for (text, kind) in [
+            ("2A+B", ValueKind::Scalar),
+            ("L1+1", ValueKind::List),
+            ("2{1,2}", ValueKind::List),
+            ("[A]*2", ValueKind::Matrix),
+            ("Str1+\"A\"", ValueKind::String),
+            ("Str1=\"A\"", ValueKind::Scalar),
+            ("L1=2", ValueKind::List),
+            ("Ans", ValueKind::Unknown),
+            ("Ans+1", ValueKind::Unknown),
+            ("Ans+L1", ValueKind::List),
+            ("Ans=1", ValueKind::Unknown),
+            ("rand(3)", ValueKind::List),
+            ("L1(2)+[A](1,1)", ValueKind::Scalar),
+            ("~(A+B)", ValueKind::Scalar),
+        ] {
+            assert_eq!(expression(text).infer_kind(), kind, "{text}");
+        }
+    }
+
+    #[test]
+    fn return_kind() {
+        for (text, kind) in [
+            ("seq(I,I,1,5)", ValueKind::List),
+            ("cumSum(L1)", ValueKind::List),
+            ("augment(L1,{1})", ValueKind::List),
+            ("augment([A],[B])", ValueKind::Matrix),
+            ("dim(L1)", ValueKind::Scalar),
+            ("dim([A])", ValueKind::List),
+            ("sum(L1)+1", ValueKind::Scalar),
+            ("max(L1)", ValueKind::Scalar),
+            ("max(L1,5)", ValueKind::List),
+            ("max(A,5)", ValueKind::Scalar),
+            ("abs(L1)", ValueKind::List),
+            ("randInt(1,6)", ValueKind::Scalar),
+            ("randInt(1,6,3)", ValueKind::List),
+            ("binompdf(5,.5)", ValueKind::List),
+            ("binompdf(5,.5,2)", ValueKind::Scalar),
+            ("binomcdf(5,.5)", ValueKind::List),
+            ("binomcdf(5,.5,2)", ValueKind::Scalar),
+            ("sub(Str1,1,1)", ValueKind::String),
+            ("sub(5)", ValueKind::Scalar),
+            ("identity(3)", ValueKind::Matrix),
+            ("expr(Str1)", ValueKind::Unknown),
+            ("abs(Ans)", ValueKind::Unknown),
+        ] {
+            assert_eq!(expression(text).infer_kind(), kind, "{text}");
+        }
+    }
+
+    #[test]
+    fn operands_commute() {
+        assert!(binop("A+B").operands_commute());
+        assert!(binop("abs(A)+1").operands_commute());
+        assert!(binop("2*[A]").operands_commute());
+
+        assert!(!binop("\"A\"+Str1").operands_commute());
+        assert!(!binop("Ans+sub(Str1,1,1)").operands_commute());
+        assert!(!binop("[A]*[B]").operands_commute());
+        assert!(!binop("[A]*Ans").operands_commute());
+    }
+}
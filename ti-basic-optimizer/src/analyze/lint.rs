@@ -0,0 +1,282 @@
+//! # Lints
+//! Problems which the calculator won't point out until the offending line runs, if at all. These
+//! are collected from the read-only analyses so that a program can be checked without optimizing
+//! it.
+
+use std::collections::BTreeSet;
+
+use crate::analyze::{GetKeyPattern, Variable};
+use crate::error_reporting::{Severity, TokenReport};
+use crate::parse::{
+    statements::{ControlFlow, Statement},
+    Program,
+};
+
+/// Blocks nested deeper than this are reported by [`Program::lint`]. There is no fixed limit on the calculator, but
+/// every open block takes memory until its `End` runs, and hand-written programs rarely need more.
+pub const DEEP_NESTING: usize = 12;
+
+impl Program {
+    /// Collects a report for each problem found by the read-only analyses, ordered by line.
+    /// Jumps to labels which are never declared fail with `ERR:LABEL` and are reported as
+    /// [errors](Severity::Error). The rest are [warnings](Severity::Warning):
+    ///
+    /// - labels declared more than once, since only the first declaration is ever jumped to,
+    /// - jumps out of blocks, which leak memory (see [`Program::loop_escaping_gotos`]),
+    /// - lines directly after an unconditional `Goto`, `Menu(`, `Return`, or `Stop` which no
+    ///   label or block makes reachable again (only the first line of each run is reported),
+    /// - reads of variables which the program never sets, which get whatever value was left in
+    ///   them (only the first read of each variable is reported),
+    /// - `getKey` in a `While` condition (see [`GetKeyPattern::While`]), and
+    /// - blocks nested more than [`DEEP_NESTING`] deep (see [`Program::max_nesting_depth`]); only
+    ///   the first line of each run of such lines is reported.
+    ///
+    /// Reports point at the first token of the line, as given by [`Program::original_positions`].
+    pub fn lint(&self) -> Vec<TokenReport> {
+        let mut found: Vec<(usize, Severity, &str, &str)> = vec![];
+
+        let declarations = self.label_declarations();
+        let mut undeclared = BTreeSet::new();
+        for (label, usages) in self.label_usages() {
+            if !declarations.contains_key(&label) {
+                undeclared.extend(usages);
+            }
+        }
+        found.extend(undeclared.into_iter().map(|idx| {
+            (
+                idx,
+                Severity::Error,
+                "Jump to a label which is never declared.",
+                "This causes ERR:LABEL when it runs.",
+            )
+        }));
+
+        for (idx, line) in self.statements() {
+            if let Statement::ControlFlow(ControlFlow::Lbl(name)) = line {
+                if declarations[name] != idx {
+                    found.push((
+                        idx,
+                        Severity::Warning,
+                        "Label declared more than once.",
+                        "Only the first declaration is ever jumped to.",
+                    ));
+                }
+            }
+        }
+
+        found.extend(self.loop_escaping_gotos().into_iter().map(|idx| {
+            (
+                idx,
+                Severity::Warning,
+                "Jump out of a block.",
+                "The block's End never runs, which leaks memory until ERR:MEMORY.",
+            )
+        }));
+
+        found.extend(self.unreachable_lines().into_iter().map(|idx| {
+            (
+                idx,
+                Severity::Warning,
+                "Unreachable line.",
+                "Nothing jumps or falls through to this line.",
+            )
+        }));
+
+        found.extend(self.uninitialized_reads().into_iter().map(|idx| {
+            (
+                idx,
+                Severity::Warning,
+                "Read of a variable which the program never sets.",
+                "Its value is whatever was left in it before the program ran.",
+            )
+        }));
+
+        found.extend(
+            self.getkey_patterns()
+                .into_iter()
+                .filter(|(_, pattern)| *pattern == GetKeyPattern::While)
+                .map(|(idx, _)| {
+                    (
+                        idx,
+                        Severity::Warning,
+                        "getKey in a While condition.",
+                        "The loop usually doesn't run at all; use Repeat to wait for a key.",
+                    )
+                }),
+        );
+
+        let mut too_deep = false;
+        for (idx, depth) in self.nesting_depths().into_iter().enumerate() {
+            if depth > DEEP_NESTING && !too_deep {
+                found.push((
+                    idx,
+                    Severity::Warning,
+                    "Deeply nested block.",
+                    "Every open block takes memory, and nesting this deep can cause ERR:MEMORY.",
+                ));
+            }
+
+            too_deep = depth > DEEP_NESTING;
+        }
+
+        found.sort_by_key(|&(idx, ..)| idx);
+        found
+            .into_iter()
+            .map(|(idx, severity, message, suggestion)| {
+                let position = self
+                    .original_positions
+                    .get(idx)
+                    .copied()
+                    .unwrap_or_default();
+
+                TokenReport::new(position, message, Some(suggestion)).with_severity(severity)
+            })
+            .collect()
+    }
+
+    /// The first line of each run of lines which follow an unconditional jump, `Return`, or
+    /// `Stop`, up to the next `Lbl`, `Else`, or `End`.
+    fn unreachable_lines(&self) -> Vec<usize> {
+        let mut unreachable = vec![];
+
+        let mut dead = false;
+        for (idx, line) in self.statements() {
+            let root = line.root();
+
+            match root {
+                Some(Statement::ControlFlow(
+                    ControlFlow::Lbl(_) | ControlFlow::Else | ControlFlow::End,
+                )) => dead = false,
+                None => {}
+                Some(_) if dead => {
+                    unreachable.push(idx);
+                    dead = false;
+                }
+                Some(_) => {}
+            }
+
+            if !self.is_guarded_single_line(idx)
+                && matches!(
+                    root,
+                    Some(Statement::ControlFlow(
+                        ControlFlow::Goto(_)
+                            | ControlFlow::Menu(_)
+                            | ControlFlow::Return
+                            | ControlFlow::Stop
+                    ))
+                )
+            {
+                dead = true;
+            }
+        }
+
+        unreachable
+    }
+
+    /// The first line reading each variable which no line of the program writes (see
+    /// [`Statement::defined_variables`]). Nothing is reported if the program may write variables
+    /// without naming them, by running another program, a library, or `expr(`, or by receiving
+    /// them with a command like `GetCalc(`.
+    fn uninitialized_reads(&self) -> Vec<usize> {
+        let hidden_writes = self.statements().any(|(_, line)| {
+            line.may_access_any_variable()
+                || match line.root() {
+                    Some(Statement::ProgramInvocation(_)) => true,
+                    Some(Statement::Generic(generic)) => generic.affects_ans(),
+                    _ => false,
+                }
+        });
+        if hidden_writes {
+            return vec![];
+        }
+
+        let written: BTreeSet<Variable> = self
+            .statements()
+            .flat_map(|(_, line)| line.defined_variables())
+            .collect();
+
+        let mut reported = BTreeSet::new();
+        let mut reads = vec![];
+        for (idx, line) in self.statements() {
+            let mut first_read = false;
+            for variable in line.used_variables() {
+                if !written.contains(&variable) && reported.insert(variable) {
+                    first_read = true;
+                }
+            }
+
+            if first_read {
+                reads.push(idx);
+            }
+        }
+
+        reads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn lint() {
+        let mut tokens = load_test_data("/snippets/analysis/lint.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        assert_eq!(program.unreachable_lines(), vec![4]);
+        assert_eq!(
+            program
+                .lint()
+                .iter()
+                .map(ToString::to_string)
+                .map(|report| report.lines().next().unwrap().to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "Warning: Read of a variable which the program never sets. (at token 3)",
+                "Error: Jump to a label which is never declared. (at token 6)",
+                "Warning: Unreachable line. (at token 12)",
+                "Warning: Label declared more than once. (at token 15)",
+                "Warning: getKey in a While condition. (at token 18)",
+                "Warning: Jump out of a block. (at token 21)",
+            ]
+        );
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let text = |depth| {
+            let mut lines = vec!["While 1"; depth];
+            lines.extend(vec!["End"; depth]);
+
+            lines.join("\n")
+        };
+
+        let program = Program::from_text(&text(DEEP_NESTING), test_files::test_version!());
+        assert!(program.lint().is_empty());
+
+        let program = Program::from_text(&text(DEEP_NESTING + 2), test_files::test_version!());
+        let reports = program.lint();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0]
+            .to_string()
+            .starts_with("Warning: Deeply nested block. (at token 36)"));
+    }
+
+    #[test]
+    fn uninitialized_reads() {
+        let program = |text| Program::from_text(text, test_files::test_version!());
+
+        assert_eq!(
+            program("Disp A\nDisp A+B\nB+1->B\nDisp Str1").uninitialized_reads(),
+            vec![0, 3]
+        );
+        assert!(program("Disp A\nprgmSET").uninitialized_reads().is_empty());
+        assert!(program("Disp A\nDisp expr(\"2\"")
+            .uninitialized_reads()
+            .is_empty());
+        assert!(program("Disp A\nGetCalc(A")
+            .uninitialized_reads()
+            .is_empty());
+    }
+}
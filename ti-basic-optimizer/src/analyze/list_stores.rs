@@ -0,0 +1,216 @@
+//! # List Element Stores
+//! Programs often fill a list one element at a time, like `DelVar L1:5->L1(1):6->L1(2):7->L1(3)`.
+//! When the list starts out empty, this is the same as storing the list literal `{5,6,7` to it,
+//! which is much smaller and faster.
+//!
+//! The list must be known to be empty: storing to `L1(1)` and `L1(2)` leaves any later elements
+//! in place, but storing a list literal replaces the whole list.
+
+use std::collections::BTreeSet;
+
+use tifloats::{tifloat, Float};
+use titokens::Token;
+
+use crate::analyze::Variable;
+use crate::parse::{
+    components::{DelVarTarget, ListIndex, ListIndexable, ListName, Operand, StoreTarget, TIList},
+    expression::Expression,
+    statements::{DelVarChain, Statement},
+    Program, Reconstruct,
+};
+use crate::Config;
+
+/// The list and element index (as written) that `line` stores to, and the stored value, if `line`
+/// stores to an element of a list.
+fn element_store(line: &Statement) -> Option<(ListName, &Expression, &Expression)> {
+    match line {
+        Statement::Store(
+            value,
+            StoreTarget::ListIndex(ListIndex {
+                subject: ListIndexable::List(name),
+                index,
+            }),
+        ) => Some((*name, index, value)),
+
+        _ => None,
+    }
+}
+
+/// True if `line` always leaves `list` empty: `DelVar`, `ClrList`, or storing 0 to its `dim(`.
+fn empties(line: &Statement, list: ListName) -> bool {
+    match line {
+        Statement::DelVarChain(DelVarChain {
+            deletions,
+            valence: None,
+        }) => deletions.contains(&DelVarTarget::List(list)),
+        Statement::Generic(generic) => {
+            generic.kind == Token::OneByte(0xFA) // ClrList
+                && generic
+                    .arguments
+                    .contains(&Expression::Operand(Operand::ListName(list)))
+        }
+        Statement::Store(
+            Expression::Operand(Operand::NumericLiteral(value)),
+            StoreTarget::ListResizing(name),
+        ) => *name == list && *value == tifloat!(0 * 10 ^ 0),
+
+        _ => false,
+    }
+}
+
+/// True if `value` gives the same result whether it is stored right away or evaluated as part of
+/// a list literal after the stores before it: it can't read `list` or `Ans`, which the earlier
+/// stores change, or write any variable.
+fn can_move(value: &Expression, list: ListName, config: &Config) -> bool {
+    // Ans
+    if value.reconstruct(config).contains(&Token::OneByte(0x72)) || value.may_access_any_variable()
+    {
+        return false;
+    }
+
+    let mut used = BTreeSet::new();
+    value.collect_variables(&mut used);
+
+    !used.contains(&Variable::List(list))
+}
+
+impl Program {
+    /// Compute the runs of lines which store to the elements of a list in order, starting with
+    /// the first, right after the list is emptied, like the last three lines of
+    /// `DelVar L1:5->L1(1):6->L1(2):7->L1(3)`. Each run is returned with its list and has at least
+    /// two lines.
+    ///
+    /// The indices must be written as numbers, and the stored values must not read the list,
+    /// `Ans`, or anything the earlier stores of the run change. The effect of the last store on
+    /// `Ans` must never be observed, since a list literal store leaves the whole list in `Ans`.
+    /// The line which empties the list must not be guarded by a one-line conditional.
+    pub fn list_element_store_runs(&self, config: &Config) -> Vec<(ListName, Vec<usize>)> {
+        let lines = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Statement::None))
+            .collect::<Vec<_>>();
+
+        let one = tifloat!(0x0010000000000000 * 10 ^ 0);
+
+        let mut runs = vec![];
+        let mut start = 1;
+        while start < lines.len() {
+            let (previous_idx, previous) = lines[start - 1];
+            let Some((list, _, _)) = element_store(lines[start].1) else {
+                start += 1;
+                continue;
+            };
+
+            if !empties(previous, list) || self.is_guarded_single_line(previous_idx) {
+                start += 1;
+                continue;
+            }
+
+            let mut run = vec![];
+            let mut expected = one;
+            for &(idx, line) in &lines[start..] {
+                let Some((name, index, value)) = element_store(line) else {
+                    break;
+                };
+
+                if name != list
+                    || *index != Expression::Operand(Operand::NumericLiteral(expected))
+                    || !can_move(value, list, config)
+                {
+                    break;
+                }
+
+                run.push(idx);
+                let Ok(next) = expected + one else {
+                    break;
+                };
+                expected = next;
+            }
+
+            start += run.len().max(1);
+
+            if run.len() >= 2 && !self.ans_observed_after(*run.last().unwrap(), config) {
+                runs.push((list, run));
+            }
+        }
+
+        runs
+    }
+
+    /// Compute the lines which store a list literal to one of `L1` through `L6` right after the
+    /// list is emptied, like the second line of `DelVar L1:{5,6,7->L1`. These could instead store
+    /// each entry to its element in turn, `5->L1(1):6->L1(2):7->L1(3)`.
+    ///
+    /// This has the same requirements as [`Program::list_element_store_runs`], the other way
+    /// around. Custom lists are never included, since storing to an element of a deleted custom
+    /// list is an error.
+    pub fn expandable_list_literal_stores(&self, config: &Config) -> Vec<usize> {
+        let mut expandable = vec![];
+
+        let mut previous: Option<usize> = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            if matches!(line, Statement::None) {
+                continue;
+            }
+
+            let previous_idx = previous.replace(idx);
+
+            let Statement::Store(
+                Expression::Operand(Operand::ListLiteral(TIList { entries })),
+                StoreTarget::List(list @ ListName::Default(_)),
+            ) = line
+            else {
+                continue;
+            };
+
+            let Some(previous_idx) = previous_idx else {
+                continue;
+            };
+
+            if !entries.is_empty()
+                && empties(&self.lines[previous_idx], *list)
+                && !self.is_guarded_single_line(previous_idx)
+                && entries.iter().all(|entry| can_move(entry, *list, config))
+                && !self.ans_observed_after(idx, config)
+            {
+                expandable.push(idx);
+            }
+        }
+
+        expandable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn list_element_store_runs() {
+        let mut tokens = load_test_data("/snippets/analysis/list-stores.txt");
+        let tokenizer = test_tokenizer!();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+        let config = Config::from(test_version!());
+
+        let runs = program.list_element_store_runs(&config);
+        assert_eq!(
+            runs.into_iter().map(|(_, run)| run).collect::<Vec<_>>(),
+            vec![vec![1, 2, 3], vec![6, 7], vec![18, 19]]
+        );
+    }
+
+    #[test]
+    fn expandable_list_literal_stores() {
+        let program = Program::from_text(
+            "DelVar L1\n{5,A+1->L1\nClrList L2\n{L2(1),2->L2\n0->dim(L3)\n{1,2->L3\nDisp Ans\n\
+            If A\nClrList L4\n{1,2->L4\nDelVar L5\n{1->L5\n0",
+            test_version!(),
+        );
+        let config = Config::from(test_version!());
+
+        assert_eq!(program.expandable_list_literal_stores(&config), vec![1, 11]);
+    }
+}
@@ -0,0 +1,171 @@
+//! # Program Metrics
+//! Summary statistics about a program, intended for review tooling.
+
+use std::collections::BTreeMap;
+
+use titokens::Token;
+
+use crate::parse::{
+    statements::{control_flow::Menu, ControlFlow, Statement},
+    Program,
+};
+use crate::Config;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Metrics {
+    /// Number of lines which are not blank.
+    pub statements: usize,
+    /// Deepest nesting of `If-Then`, `While`, `Repeat`, and `For(` blocks.
+    pub max_nesting_depth: usize,
+    /// McCabe's cyclomatic complexity.
+    pub cyclomatic_complexity: usize,
+    /// Number of distinct labels declared.
+    pub labels: usize,
+    /// Number of distinct named variables referenced. See [`Program::variables`].
+    pub variables: usize,
+}
+
+impl Program {
+    /// Compute [`Metrics`] for this program.
+    ///
+    /// Cyclomatic complexity is computed as one more than the number of decision points: one for
+    /// each conditional, loop, and `IS>(`/`DS<(`, plus one for each `Menu(` option after the first.
+    /// For a program with a single entry and exit this is equal to E - N + 2 over its control flow
+    /// graph.
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = Metrics {
+            cyclomatic_complexity: 1,
+            labels: self.label_declarations().len(),
+            variables: self.variables().len(),
+            max_nesting_depth: self.max_nesting_depth(),
+            ..Metrics::default()
+        };
+
+        for line in &self.lines {
+            let Some(root) = line.root() else {
+                continue;
+            };
+
+            metrics.statements += 1;
+
+            let Statement::ControlFlow(control_flow) = root else {
+                continue;
+            };
+
+            metrics.cyclomatic_complexity += match control_flow {
+                ControlFlow::If(_)
+                | ControlFlow::IfThen(_)
+                | ControlFlow::While(_)
+                | ControlFlow::Repeat(_)
+                | ControlFlow::For(_)
+                | ControlFlow::IsGt(_)
+                | ControlFlow::DsLt(_) => 1,
+                ControlFlow::Menu(Menu { option_labels, .. }) => {
+                    option_labels.len().saturating_sub(1)
+                }
+
+                _ => 0,
+            };
+        }
+
+        metrics
+    }
+
+    /// How many [lexical blocks](Program::lexical_blocks) each line is inside, counting the line
+    /// which opens a block as inside it and the `Else` or `End` which closes it as outside. The
+    /// two halves of an `If-Then` with an `Else` count once.
+    pub(crate) fn nesting_depths(&self) -> Vec<usize> {
+        let (blocks, eof_abusers) = self.block_failure_paths();
+
+        let mut depths = vec![0; self.lines.len()];
+        for (start, end) in blocks {
+            let end = if eof_abusers.contains(&start) {
+                end
+            } else {
+                end - 1
+            };
+
+            for depth in &mut depths[start..end] {
+                *depth += 1;
+            }
+        }
+
+        depths
+    }
+
+    /// Deepest nesting of `If-Then`, `While`, `Repeat`, and `For(` blocks. Every block which is
+    /// open takes memory until its `End` runs.
+    pub fn max_nesting_depth(&self) -> usize {
+        self.nesting_depths().into_iter().max().unwrap_or(0)
+    }
+
+    /// Count how many times each token appears in the [reconstructed](Program::reconstruct)
+    /// program, line separators included.
+    pub fn token_histogram(&self, config: &Config) -> BTreeMap<Token, usize> {
+        let mut histogram = BTreeMap::new();
+        for token in self.reconstruct(config) {
+            *histogram.entry(token).or_insert(0) += 1;
+        }
+
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn metrics() {
+        let mut tokens = load_test_data("/snippets/parsing/control-flow-shenanigans/counter.txt");
+        let tokenizer = test_tokenizer!();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        assert_eq!(
+            program.metrics(),
+            Metrics {
+                statements: 14,
+                max_nesting_depth: 2,
+                cyclomatic_complexity: 4,
+                labels: 2,
+                variables: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn max_nesting_depth() {
+        let version = test_files::test_version!();
+        for (text, depth) in [
+            ("Disp 1", 0),
+            ("While A\nEnd\nFor(I,1,2\nEnd", 1),
+            (
+                "If A\nThen\nWhile B\nEnd\nElse\nRepeat C\nIf D\nThen\nEnd\nEnd\nEnd",
+                3,
+            ),
+            ("For(I,1,2\nWhile A\nEnd", 2),
+        ] {
+            let program = Program::from_text(text, version.clone());
+            assert_eq!(program.max_nesting_depth(), depth, "{text}");
+        }
+    }
+
+    #[test]
+    fn token_histogram() {
+        let version = test_files::test_version!();
+        let program = Program::from_text("1->A\nDisp A,A", version.clone());
+
+        assert_eq!(
+            program.token_histogram(&Config::from(version)),
+            BTreeMap::from([
+                (Token::OneByte(0x04), 1), // ->
+                (Token::OneByte(0x2B), 1), // ,
+                (Token::OneByte(0x31), 1), // 1
+                (Token::OneByte(0x3F), 1), // newline
+                (Token::OneByte(0x41), 3), // A
+                (Token::OneByte(0xDE), 1), // Disp
+            ])
+        );
+    }
+}
@@ -1 +1,21 @@
 mod control_flow;
+mod getkey;
+mod kinds;
+mod lint;
+mod list_stores;
+mod metrics;
+mod modes;
+mod screen;
+mod stores;
+mod strings;
+mod variables;
+
+pub(crate) use control_flow::ControlFlowLookup;
+pub use getkey::GetKeyPattern;
+pub use kinds::ValueKind;
+pub use lint::DEEP_NESTING;
+pub use metrics::Metrics;
+pub use modes::{GraphMode, ModeGroup};
+pub use strings::StringAccumulation;
+pub(crate) use variables::is_inert_command;
+pub use variables::Variable;
@@ -0,0 +1,268 @@
+//! # Mode Tracking
+//! Many commands (`Radian`, `Func`, `AxesOff`, ...) set one of the calculator's modes. The mode at
+//! the start of a program depends on the state of the calculator, so nothing is known until the
+//! program sets it.
+
+use std::collections::BTreeMap;
+use titokens::Token;
+
+use crate::parse::{
+    statements::{Generic, Statement},
+    Program,
+};
+
+/// A set of mutually exclusive mode settings.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ModeGroup {
+    /// `Radian`, `Degree`
+    Angle,
+    /// `Normal`, `Sci`, `Eng`
+    Notation,
+    /// `Float`, `Fix `
+    Decimals,
+    /// `Full`, `Horiz`
+    Screen,
+    /// `Func`, `Param`, `Polar`, `Seq`
+    GraphType,
+    /// `IndpntAuto`, `IndpntAsk`
+    Independent,
+    /// `DependAuto`, `DependAsk`
+    Dependent,
+    /// `Sequential`, `Simul`
+    GraphOrder,
+    /// `RectGC`, `PolarGC`
+    CoordinateType,
+    /// `CoordOn`, `CoordOff`
+    Coordinates,
+    /// `Thick`, `Dot-Thick`, `Thin`, `Dot-Thin`
+    LineStyle,
+    /// `AxesOn`, `AxesOff`
+    Axes,
+    /// `GridDot `, `GridLine `, `GridOff`
+    Grid,
+    /// `LabelOn`, `LabelOff`
+    Labels,
+    /// `Web`, `Time`, `uvAxes`, `vwAxes`, `uwAxes`
+    SequenceAxes,
+    /// `PmtEnd`, `PmtBgn`
+    Payment,
+    /// `Real`, `re^thetai`, `a+bi`
+    Complex,
+    /// `ExprOn`, `ExprOff`
+    Expressions,
+    /// `MATHPRINT`, `CLASSIC`
+    Print,
+    /// `n/d`, `Un/d`
+    Fractions,
+    /// `AUTO Answer`, `DEC Answer`, `FRAC Answer`, `FRAC-APPROX Answer`
+    Answers,
+    /// `DetectAsymOn`, `DetectAsymOff`
+    DetectAsymptotes,
+    /// `BackgroundOn `, `BackgroundOff`
+    Background,
+    /// `ClockOn`, `ClockOff`
+    Clock,
+}
+
+impl ModeGroup {
+    /// The mode group set by the provided token, if any.
+    pub fn of(token: Token) -> Option<ModeGroup> {
+        use ModeGroup::*;
+
+        Some(match token.into() {
+            0x64 | 0x65 => Angle,                           // Radian, Degree
+            0x66..=0x68 => Notation,                        // Normal, Sci, Eng
+            0x69 | 0x73 => Decimals,                        // Float, Fix
+            0x74 | 0x75 => Screen,                          // Horiz, Full
+            0x76..=0x79 => GraphType,                       // Func, Param, Polar, Seq
+            0x7A | 0x7B => Independent,                     // IndpntAuto, IndpntAsk
+            0x7C | 0x7D => Dependent,                       // DependAuto, DependAsk
+            0x7E00 | 0x7E01 => GraphOrder,                  // Sequential, Simul
+            0x7E02 | 0x7E03 => CoordinateType,              // PolarGC, RectGC
+            0x7E04 | 0x7E05 => Coordinates,                 // CoordOn, CoordOff
+            0x7E06 | 0x7E07 | 0xEF74 | 0xEF75 => LineStyle, // Thick, DotThick, Thin, DotThin
+            0x7E08 | 0x7E09 => Axes,                        // AxesOn, AxesOff
+            0x7E0A | 0x7E0B | 0xEF5A => Grid,               // GridDot, GridOff, GridLine
+            0x7E0C | 0x7E0D => Labels,                      // LabelOn, LabelOff
+            0x7E0E..=0x7E12 => SequenceAxes,                // Web, Time, UvAxes, VwAxes, UwAxes
+            0xBB4B | 0xBB4C => Payment,                     // PmtEnd, PmtBgn
+            0xBB4D..=0xBB4F => Complex,                     // Real, REThetaI, APlusBI
+            0xBB50 | 0xBB51 => Expressions,                 // ExprOn, ExprOff
+            0xEF36 | 0xEF37 => Print,                       // MATHPRINT, CLASSIC
+            0xEF38 | 0xEF39 => Fractions,                   // Nd, Und
+            0xEF3A..=0xEF3D => Answers,                     // AUTO, DEC, FRAC, FRACAPPROX
+            0xEF6A | 0xEF6B => DetectAsymptotes,            // DetectAsymOn, DetectAsymOff
+            0xEF5B | 0xEF64 => Background,                  // BackgroundOn, BackgroundOff
+            0xEF0F | 0xEF10 => Clock,                       // ClockOff, ClockOn
+            _ => return None,
+        })
+    }
+}
+
+/// The graph screen settings known to be in effect at some point in a program, as the token of
+/// the command which set each one. `None` means the setting is unknown.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct GraphMode {
+    /// `Func`, `Param`, `Polar`, or `Seq`
+    pub graph_type: Option<Token>,
+    /// `Sequential` or `Simul`
+    pub order: Option<Token>,
+    /// `RectGC` or `PolarGC`
+    pub coordinate_type: Option<Token>,
+    /// `CoordOn` or `CoordOff`
+    pub coordinates: Option<Token>,
+    /// `Thick`, `Dot-Thick`, `Thin`, or `Dot-Thin`
+    pub line_style: Option<Token>,
+    /// `AxesOn` or `AxesOff`
+    pub axes: Option<Token>,
+    /// `GridDot `, `GridLine `, or `GridOff`
+    pub grid: Option<Token>,
+    /// `LabelOn` or `LabelOff`
+    pub labels: Option<Token>,
+    /// `Web`, `Time`, `uvAxes`, `vwAxes`, or `uwAxes`
+    pub sequence_axes: Option<Token>,
+    /// `DetectAsymOn` or `DetectAsymOff`
+    pub detect_asymptotes: Option<Token>,
+}
+
+impl GraphMode {
+    fn from_known(known: &BTreeMap<ModeGroup, Token>) -> Self {
+        let get = |group| known.get(&group).copied();
+
+        GraphMode {
+            graph_type: get(ModeGroup::GraphType),
+            order: get(ModeGroup::GraphOrder),
+            coordinate_type: get(ModeGroup::CoordinateType),
+            coordinates: get(ModeGroup::Coordinates),
+            line_style: get(ModeGroup::LineStyle),
+            axes: get(ModeGroup::Axes),
+            grid: get(ModeGroup::Grid),
+            labels: get(ModeGroup::Labels),
+            sequence_axes: get(ModeGroup::SequenceAxes),
+            detect_asymptotes: get(ModeGroup::DetectAsymptotes),
+        }
+    }
+}
+
+impl Generic {
+    /// The mode group this command sets, if any.
+    pub fn mode_group(&self) -> Option<ModeGroup> {
+        ModeGroup::of(self.kind)
+    }
+}
+
+impl Program {
+    /// Compute the lines which set a mode to the value it is already known to have.
+    ///
+    /// Knowledge is only carried through straight-line code; it is discarded at every control flow
+    /// statement and program invocation. The first setting of any mode is never redundant because
+    /// the mode at the start of the program is unknown.
+    pub fn redundant_mode_settings(&self) -> Vec<usize> {
+        let mut redundant = vec![];
+        self.walk_modes(|_| {}, |idx| redundant.push(idx));
+
+        redundant
+    }
+
+    /// Compute the [`GraphMode`] known to be in effect as each line starts. Knowledge is tracked
+    /// as in [`Program::redundant_mode_settings`].
+    pub fn graph_modes(&self) -> Vec<GraphMode> {
+        let mut modes = vec![];
+        self.walk_modes(|known| modes.push(GraphMode::from_known(known)), |_| {});
+
+        modes
+    }
+
+    /// Track modes through the program. `before_line` is called with what is known as each line
+    /// starts, and `on_redundant` with each line which sets a mode to the value it already has.
+    fn walk_modes(
+        &self,
+        mut before_line: impl FnMut(&BTreeMap<ModeGroup, Token>),
+        mut on_redundant: impl FnMut(usize),
+    ) {
+        let mut known: BTreeMap<ModeGroup, Token> = BTreeMap::new();
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            before_line(&known);
+
+            let root = line.root();
+            let was_conditional = self.is_guarded_single_line(idx);
+
+            match root {
+                Some(Statement::Generic(generic)) => {
+                    let Some(group) = generic.mode_group() else {
+                        if matches!(generic.kind, Token::TwoByte(0xEF, 0x11 | 0x12)) {
+                            // OpenLib(, ExecLib
+                            known.clear();
+                        }
+
+                        continue;
+                    };
+
+                    if was_conditional || !generic.arguments.is_empty() {
+                        // Fix 2, AxesOn RED, and conditional settings are not tracked.
+                        known.remove(&group);
+                    } else if known.insert(group, generic.kind) == Some(generic.kind)
+                        && matches!(line, Statement::Generic(_))
+                    {
+                        on_redundant(idx);
+                    }
+                }
+
+                Some(Statement::ControlFlow(_) | Statement::ProgramInvocation(_)) => {
+                    known.clear();
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_text, test_version};
+
+    #[test]
+    fn redundant() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/modes/redundant.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(test_program.redundant_mode_settings(), vec![2, 4]);
+    }
+
+    #[test]
+    fn not_redundant() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/modes/not-redundant.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(test_program.redundant_mode_settings(), vec![]);
+    }
+
+    #[test]
+    fn graph_modes() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/modes/graph.txt"),
+            test_version!(),
+        );
+        let modes = test_program.graph_modes();
+
+        assert_eq!(modes.len(), test_program.lines.len());
+        assert_eq!(modes[0], GraphMode::default());
+        assert_eq!(
+            modes[3],
+            GraphMode {
+                graph_type: Some(Token::OneByte(0x76)),            // Func
+                coordinate_type: Some(Token::TwoByte(0x7E, 0x03)), // RectGC
+                axes: Some(Token::TwoByte(0x7E, 0x09)),            // AxesOff
+                ..GraphMode::default()
+            }
+        );
+        assert_eq!(modes[5], GraphMode::default());
+    }
+}
@@ -0,0 +1,161 @@
+//! # Screen Command Analysis
+//! Finds screen commands whose effect is immediately repeated, like `ClrHome:ClrHome` or two
+//! identical `Output(` calls in a row. Removing the repeat doesn't change what ends up on the
+//! screen, but it does change how long the program takes to draw it, so acting on this is opt-in.
+
+use titokens::Token;
+
+use crate::parse::{
+    components::{ListIndexable, MatrixIndexable, Operand, Operator},
+    expression::Expression,
+    statements::Statement,
+    Program, Reconstruct,
+};
+use crate::Config;
+
+/// Functions whose result only depends on their arguments.
+fn is_deterministic_function(kind: Token) -> bool {
+    matches!(
+        kind.into(),
+        0x12 | // round(
+        0x19 | // max(
+        0x1A | // min(
+        0xB1 | // int(
+        0xB2 | // abs(
+        0xB9 | // iPart(
+        0xBA | // fPart(
+        0xBC | // sqrt(
+        0xBE | // ln(
+        0xBF | // e^(
+        0xC0 | // log(
+        0xC1 | // 10^(
+        0xC2
+            ..=0xC7 | // sin( sin^-1( cos( cos^-1( tan( tan^-1(
+        0xBB0C | // sub(
+        0xBB2B | // length(
+        0xEF97 // toString(
+    )
+}
+
+impl Expression {
+    /// True if evaluating this expression twice in a row, with nothing in between, is guaranteed
+    /// to give the same result both times.
+    fn is_deterministic(&self) -> bool {
+        match self {
+            Expression::Operator(Operator::Binary(binop)) => {
+                binop.left.is_deterministic() && binop.right.is_deterministic()
+            }
+            Expression::Operator(Operator::Unary(unop)) => unop.child.is_deterministic(),
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                is_deterministic_function(call.kind)
+                    && call.arguments.iter().all(Expression::is_deterministic)
+            }
+            Expression::Operator(_) => false,
+
+            Expression::Operand(operand) => match operand {
+                Operand::NumericVarName(_)
+                | Operand::ListName(_)
+                | Operand::MatrixName(_)
+                | Operand::StringName(_)
+                | Operand::Ans
+                | Operand::NumericLiteral(_)
+                | Operand::Fraction(_)
+                | Operand::StringLiteral(_) => true,
+                Operand::ListAccess(access) => {
+                    matches!(access.subject, ListIndexable::List(_))
+                        && access.index.is_deterministic()
+                }
+                Operand::MatrixAccess(access) => {
+                    matches!(access.subject, MatrixIndexable::Matrix(_))
+                        && access.row.is_deterministic()
+                        && access.col.is_deterministic()
+                }
+                Operand::Expression(expr) => expr.is_deterministic(),
+
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Program {
+    /// Compute the lines which repeat the screen command immediately before them. These are
+    /// `ClrHome` or `ClrDraw` directly after the same command, and an `Output(` directly after an
+    /// identical `Output(` whose arguments always evaluate to the same thing (so no `rand`,
+    /// `getKey`, ...).
+    ///
+    /// Neither line may be the body of a one-line conditional: if the first might not run, the
+    /// second is needed, and if the second is removed the conditional would guard the line after
+    /// it instead.
+    pub fn redundant_screen_commands(&self, config: &Config) -> Vec<usize> {
+        let mut redundant = vec![];
+
+        // the previous line, if it is an unguarded screen command that could be repeated.
+        let mut previous: Option<Vec<Token>> = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            if matches!(line, Statement::None) {
+                continue;
+            }
+
+            let was_conditional = self.is_guarded_single_line(idx);
+
+            let repeatable = match line {
+                Statement::Generic(generic) => match generic.kind {
+                    Token::OneByte(0x85 | 0xE1) => true, // ClrDraw, ClrHome
+                    Token::OneByte(0xE0) => {
+                        // Output(
+                        generic.arguments.iter().all(Expression::is_deterministic)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+
+            if !repeatable || was_conditional {
+                previous = None;
+                continue;
+            }
+
+            let tokens = line.reconstruct(config);
+            if previous.as_ref() == Some(&tokens) {
+                redundant.push(idx);
+            } else {
+                previous = Some(tokens);
+            }
+        }
+
+        redundant
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_text, test_version};
+
+    #[test]
+    fn redundant() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/screen/redundant.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_screen_commands(&Config::from(test_version!())),
+            vec![1, 4, 5]
+        );
+    }
+
+    #[test]
+    fn not_redundant() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/screen/not-redundant.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_screen_commands(&Config::from(test_version!())),
+            vec![]
+        );
+    }
+}
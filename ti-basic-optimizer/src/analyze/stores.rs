@@ -0,0 +1,185 @@
+//! # Redundant Stores
+//! Storing a value to a variable which is already known to hold that value, like the second line
+//! of `5->A:5->A`, doesn't change the variable. The only other effect of a store is that `Ans`
+//! becomes the stored value, so such a store can only be removed if nothing reads `Ans` before it
+//! is next overwritten.
+
+use std::collections::BTreeMap;
+
+use tifloats::Float;
+use titokens::Token;
+
+use crate::analyze::{is_inert_command, Variable};
+use crate::parse::{
+    components::{NumericVarName, Operand, StoreTarget},
+    expression::Expression,
+    statements::{ControlFlow, Statement},
+    Program, Reconstruct,
+};
+use crate::Config;
+
+impl Program {
+    /// Compute the lines which store a numeric literal to a real variable that is already known to
+    /// hold that value, and whose effect on `Ans` is never observed.
+    ///
+    /// Knowledge is only carried through straight-line code; it is discarded at every control flow
+    /// statement, program invocation, and command which might change a variable. `Input`,
+    /// `Prompt`, and stores of non-literals forget only the variables they write. Stores guarded
+    /// by a one-line conditional are never redundant.
+    pub fn redundant_stores(&self, config: &Config) -> Vec<usize> {
+        let mut known: BTreeMap<NumericVarName, Float> = BTreeMap::new();
+        let mut redundant = vec![];
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            if matches!(line, Statement::None) {
+                continue;
+            }
+
+            let was_conditional = self.is_guarded_single_line(idx);
+
+            if line.may_access_any_variable() {
+                known.clear();
+                continue;
+            }
+
+            match line {
+                Statement::Store(
+                    expr,
+                    StoreTarget::NumericVar(name) | StoreTarget::NumericVarOrListName(name),
+                ) => {
+                    let Expression::Operand(Operand::NumericLiteral(value)) = expr else {
+                        known.remove(name);
+                        continue;
+                    };
+
+                    if was_conditional {
+                        known.remove(name);
+                    } else if known.get(name) == Some(value)
+                        && !self.ans_observed_after(idx, config)
+                    {
+                        redundant.push(idx);
+                    } else {
+                        known.insert(*name, *value);
+                    }
+                }
+                Statement::Store(..)
+                | Statement::Expression(_)
+                | Statement::UserInput(_)
+                | Statement::ControlFlow(ControlFlow::If(_)) => {
+                    for variable in line.defined_variables() {
+                        if let Variable::Numeric(name) = variable {
+                            known.remove(&name);
+                        }
+                    }
+                }
+                Statement::Generic(generic) if is_inert_command(generic.kind) => {}
+
+                _ => known.clear(),
+            }
+        }
+
+        redundant
+    }
+
+    /// True if `Ans` might be read after line `idx` before it is overwritten. Reaching control
+    /// flow or the end of the program counts as a read, since `Ans` is still visible after the
+    /// program finishes, as does a command which might read or change `Ans` itself.
+    ///
+    /// See also: [`Generic::affects_ans`](crate::parse::statements::Generic::affects_ans)
+    pub(super) fn ans_observed_after(&self, idx: usize, config: &Config) -> bool {
+        for line in &self.lines[idx + 1..] {
+            if matches!(line, Statement::None) {
+                continue;
+            }
+
+            if line.reconstruct(config).contains(&Token::OneByte(0x72)) {
+                // Ans
+                return true;
+            }
+
+            match line {
+                Statement::Store(..) | Statement::Expression(_) => return false,
+                Statement::Generic(generic) if generic.affects_ans() => return true,
+                Statement::Generic(_) | Statement::StatPlot(_) => {}
+
+                _ => return true,
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_text, test_version};
+
+    #[test]
+    fn redundant() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/stores/redundant.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_stores(&Config::from(test_version!())),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn ans_observed() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/stores/ans-observed.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_stores(&Config::from(test_version!())),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn input() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/stores/input.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_stores(&Config::from(test_version!())),
+            vec![4]
+        );
+    }
+
+    #[test]
+    fn ans_command() {
+        let test_program = Program::from_text(
+            &load_test_text("/snippets/analysis/stores/ans-command.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(
+            test_program.redundant_stores(&Config::from(test_version!())),
+            vec![5]
+        );
+    }
+
+    #[test]
+    fn eval_reads_everything() {
+        for path in [
+            "/snippets/analysis/stores/eval.txt",
+            "/snippets/analysis/stores/expr.txt",
+        ] {
+            let test_program = Program::from_text(&load_test_text(path), test_version!());
+
+            assert_eq!(
+                test_program.redundant_stores(&Config::from(test_version!())),
+                vec![],
+                "{path}"
+            );
+        }
+    }
+}
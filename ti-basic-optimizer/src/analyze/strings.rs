@@ -0,0 +1,165 @@
+//! # String Accumulation
+//! Programs often build up a string one piece at a time, like `Str1+"!"->Str1`, before displaying
+//! it. Each step copies the whole string, so this is slow in loops. This analysis finds these
+//! steps, and the ones which directly follow a literal store to the same string, like
+//! `"AB"->Str1:Str1+"CD"->Str1`, whose result is known ahead of time.
+
+use titokens::Token;
+
+use crate::parse::{
+    components::{Operand, Operator, StoreTarget, StringName},
+    expression::Expression,
+    statements::{ControlFlow, Statement},
+    Program,
+};
+
+/// A line which stores a concatenation back into one of its operands, like `Str1+"!"->Str1` or
+/// `"!"+Str1->Str1`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StringAccumulation {
+    pub line: usize,
+    pub name: StringName,
+    /// True for `Str1+X->Str1`, false for `X+Str1->Str1`.
+    pub appends: bool,
+    /// True if the line is inside a `For(`, `While`, or `Repeat` loop.
+    pub in_loop: bool,
+}
+
+impl Statement {
+    /// If this line is `Str1+X->Str1` or `X+Str1->Str1`, returns `Str1`, whether it appends, and
+    /// `X`.
+    pub(crate) fn string_accumulation(&self) -> Option<(StringName, bool, &Expression)> {
+        let Statement::Store(
+            Expression::Operator(Operator::Binary(binop)),
+            StoreTarget::String(name),
+        ) = self
+        else {
+            return None;
+        };
+
+        if binop.kind != Token::OneByte(0x70) {
+            return None;
+        }
+
+        let is_name = |expr: &Expression| match expr {
+            Expression::Operand(Operand::StringName(operand)) => operand == name,
+            _ => false,
+        };
+
+        if is_name(&binop.left) {
+            Some((*name, true, &binop.right))
+        } else if is_name(&binop.right) {
+            Some((*name, false, &binop.left))
+        } else {
+            None
+        }
+    }
+}
+
+impl Program {
+    /// Every line which concatenates onto a string and stores the result back to it, in order.
+    pub fn string_accumulations(&self) -> Vec<StringAccumulation> {
+        let loops = self
+            .block_failure_paths()
+            .0
+            .into_iter()
+            .filter(|&(start, _)| {
+                matches!(
+                    self.lines[start].root(),
+                    Some(Statement::ControlFlow(
+                        ControlFlow::For(_) | ControlFlow::While(_) | ControlFlow::Repeat(_)
+                    ))
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(line, statement)| {
+                let (name, appends, _) = statement.string_accumulation()?;
+
+                Some(StringAccumulation {
+                    line,
+                    name,
+                    appends,
+                    in_loop: loops
+                        .iter()
+                        .any(|&(start, after_end)| start < line && line < after_end),
+                })
+            })
+            .collect()
+    }
+
+    /// Pairs of lines `(store, accumulation)` like `"AB"->Str1` followed by `Str1+"CD"->Str1`,
+    /// where the accumulation only adds a literal, so both lines together store a literal.
+    ///
+    /// The store must not be guarded by a one-line conditional, and must be the line right before
+    /// the accumulation (ignoring removed lines) so that nothing can jump between them. Both lines
+    /// leave the same `Ans`.
+    pub fn foldable_string_accumulations(&self) -> Vec<(usize, usize)> {
+        let mut foldable = vec![];
+
+        let mut previous: Option<usize> = None;
+        for (idx, statement) in self.statements() {
+            if let (Some(store), Some((name, _, Expression::Operand(Operand::StringLiteral(_))))) =
+                (previous, statement.string_accumulation())
+            {
+                if !self.is_guarded_single_line(store)
+                    && matches!(
+                        &self.lines[store],
+                        Statement::Store(
+                            Expression::Operand(Operand::StringLiteral(_)),
+                            StoreTarget::String(stored)
+                        ) if *stored == name
+                    )
+                {
+                    foldable.push((store, idx));
+                }
+            }
+
+            previous = Some(idx);
+        }
+
+        foldable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{load_test_text, test_version};
+
+    #[test]
+    fn string_accumulations() {
+        let program = Program::from_text(
+            &load_test_text("/snippets/analysis/strings.txt"),
+            test_version!(),
+        );
+        let accumulations = program.string_accumulations();
+
+        assert_eq!(
+            accumulations
+                .iter()
+                .map(|acc| (acc.line, acc.appends, acc.in_loop))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, true, false),
+                (4, true, true),
+                (5, false, true),
+                (9, true, false),
+                (11, true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn foldable_string_accumulations() {
+        let program = Program::from_text(
+            &load_test_text("/snippets/analysis/strings.txt"),
+            test_version!(),
+        );
+
+        assert_eq!(program.foldable_string_accumulations(), vec![(0, 1)]);
+    }
+}
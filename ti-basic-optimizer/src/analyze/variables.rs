@@ -0,0 +1,502 @@
+//! # Variable Collection
+//! Finds the named variables (reals, lists, matrices, and strings) a program refers to, whether it
+//! reads them, writes them, or deletes them.
+
+use std::collections::BTreeSet;
+
+use crate::parse::{
+    components::{
+        DelVarTarget, ListIndex, ListIndexable, ListName, MatrixIndex, MatrixIndexable, MatrixName,
+        NumericVarName, Operand, Operator, StoreTarget, StringName,
+    },
+    expression::Expression,
+    statements::{control_flow::ForLoop, ControlFlow, Statement, UserInput},
+    Program,
+};
+use titokens::Token;
+
+/// Commands which don't change any variable, `Ans`, or the mode settings, and can't jump
+/// anywhere.
+pub(crate) fn is_inert_command(kind: Token) -> bool {
+    matches!(
+        kind.into(),
+        0x85 | // ClrDraw
+        0x93 | // Text(
+        0x9C | // Line(
+        0x9E
+            ..=0xA3 | // Pt-On( Pt-Off( Pt-Change( Pxl-On( Pxl-Off( Pxl-Change(
+        0xA5 | // Circle(
+        0xD8 | // Pause
+        0xDE | // Disp
+        0xDF | // DispGraph
+        0xE0 | // Output(
+        0xE1 | // ClrHome
+        0xEF96 // Wait
+    )
+}
+
+/// A named variable.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Variable {
+    Numeric(NumericVarName),
+    List(ListName),
+    Matrix(MatrixName),
+    String(StringName),
+}
+
+impl ListIndex {
+    fn collect_variables(&self, variables: &mut BTreeSet<Variable>) {
+        if let ListIndexable::List(name) = &self.subject {
+            variables.insert(Variable::List(*name));
+        }
+        self.index.collect_variables(variables);
+    }
+}
+
+impl MatrixIndex {
+    fn collect_variables(&self, variables: &mut BTreeSet<Variable>) {
+        if let MatrixIndexable::Matrix(name) = &self.subject {
+            variables.insert(Variable::Matrix(*name));
+        }
+        self.row.collect_variables(variables);
+        self.col.collect_variables(variables);
+    }
+}
+
+impl Expression {
+    /// Adds every variable referenced by this expression to `variables`.
+    pub fn collect_variables(&self, variables: &mut BTreeSet<Variable>) {
+        match self {
+            Expression::Operator(Operator::Binary(binop)) => {
+                binop.left.collect_variables(variables);
+                binop.right.collect_variables(variables);
+            }
+            Expression::Operator(Operator::Unary(unop)) => unop.child.collect_variables(variables),
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                for argument in &call.arguments {
+                    argument.collect_variables(variables);
+                }
+            }
+            Expression::Operator(_) => {}
+
+            Expression::Operand(operand) => match operand {
+                Operand::NumericVarName(name) => {
+                    variables.insert(Variable::Numeric(*name));
+                }
+                Operand::ListName(name) => {
+                    variables.insert(Variable::List(*name));
+                }
+                Operand::MatrixName(name) => {
+                    variables.insert(Variable::Matrix(*name));
+                }
+                Operand::StringName(name) => {
+                    variables.insert(Variable::String(*name));
+                }
+                Operand::ListAccess(access) => access.collect_variables(variables),
+                Operand::MatrixAccess(access) => access.collect_variables(variables),
+                Operand::EquationAccess(access) => access.index.collect_variables(variables),
+                Operand::Rand(rand) => {
+                    if let Some(count) = &rand.count {
+                        count.collect_variables(variables);
+                    }
+                }
+                Operand::ListLiteral(list) => {
+                    for entry in &list.entries {
+                        entry.collect_variables(variables);
+                    }
+                }
+                Operand::Piecewise(piecewise) => {
+                    for argument in piecewise.arguments() {
+                        argument.collect_variables(variables);
+                    }
+                }
+                Operand::Expression(expr) => expr.collect_variables(variables),
+
+                _ => {}
+            },
+        }
+    }
+
+    /// True if evaluating this expression may read or write a variable which does not appear in
+    /// it: `expr(` and `eval(` evaluate a string which can name any variable (or `Ans`), and
+    /// `solve(` leaves its solution in the variable it solves for.
+    pub fn may_access_any_variable(&self) -> bool {
+        let mut found = false;
+        self.walk(&mut |expr| {
+            if let Expression::Operator(Operator::FunctionCall(call)) = expr {
+                found |= matches!(
+                    call.kind.into(),
+                    0x22 | // solve(
+                    0xBB2A | // expr(
+                    0xEF98 // eval(
+                );
+            }
+        });
+
+        found
+    }
+}
+
+impl StoreTarget {
+    /// Adds the variable written by this target, and any variables in its indices, to
+    /// `variables`.
+    fn collect_variables(&self, variables: &mut BTreeSet<Variable>) {
+        match self {
+            StoreTarget::NumericVarOrListName(name) | StoreTarget::NumericVar(name) => {
+                variables.insert(Variable::Numeric(*name));
+            }
+            StoreTarget::List(name) | StoreTarget::ListResizing(name) => {
+                variables.insert(Variable::List(*name));
+            }
+            StoreTarget::Matrix(name) | StoreTarget::MatrixResizing(name) => {
+                variables.insert(Variable::Matrix(*name));
+            }
+            StoreTarget::String(name) => {
+                variables.insert(Variable::String(*name));
+            }
+            StoreTarget::ListIndex(access) => access.collect_variables(variables),
+            StoreTarget::MatrixIndex(access) => access.collect_variables(variables),
+
+            _ => {}
+        }
+    }
+
+    /// The named variable this target writes, if any. Storing to an element of a list or matrix
+    /// writes the whole list or matrix.
+    pub fn written_variable(&self) -> Option<Variable> {
+        Some(match self {
+            StoreTarget::NumericVarOrListName(name) | StoreTarget::NumericVar(name) => {
+                Variable::Numeric(*name)
+            }
+            StoreTarget::List(name) | StoreTarget::ListResizing(name) => Variable::List(*name),
+            StoreTarget::Matrix(name) | StoreTarget::MatrixResizing(name) => {
+                Variable::Matrix(*name)
+            }
+            StoreTarget::String(name) => Variable::String(*name),
+            StoreTarget::ListIndex(ListIndex {
+                subject: ListIndexable::List(name),
+                ..
+            }) => Variable::List(*name),
+            StoreTarget::MatrixIndex(MatrixIndex {
+                subject: MatrixIndexable::Matrix(name),
+                ..
+            }) => Variable::Matrix(*name),
+
+            _ => return None,
+        })
+    }
+}
+
+impl Statement {
+    /// The named variables this statement may write. `Input` with no arguments writes X and Y,
+    /// `getKey->K` writes K like any other store, and `SetUpEditor` creates the lists it puts in
+    /// the editor (`L1` through `L6` with no arguments) if they don't exist.
+    ///
+    /// Writes which can't be seen in the statement itself, by program invocations, `Asm(`, or
+    /// `solve(`, for example, are not included.
+    pub fn defined_variables(&self) -> BTreeSet<Variable> {
+        let mut defined = BTreeSet::new();
+
+        match self {
+            Statement::Store(_, target) => defined.extend(target.written_variable()),
+            Statement::UserInput(UserInput::Input { target: None, .. }) => {
+                defined.extend(
+                    [0x58, 0x59] // X, Y
+                        .map(|var| Variable::Numeric(NumericVarName(Token::OneByte(var)))),
+                );
+            }
+            Statement::UserInput(input) => defined.extend(
+                input
+                    .targets()
+                    .iter()
+                    .filter_map(StoreTarget::written_variable),
+            ),
+            Statement::ControlFlow(ControlFlow::For(ForLoop {
+                iterator: Expression::Operand(Operand::NumericVarName(name)),
+                ..
+            })) => {
+                defined.insert(Variable::Numeric(*name));
+            }
+            Statement::ControlFlow(ControlFlow::IsGt(is_ds) | ControlFlow::DsLt(is_ds)) => {
+                defined.insert(Variable::Numeric(is_ds.variable));
+            }
+            Statement::DelVarChain(chain) => {
+                for target in &chain.deletions {
+                    defined.extend(match target {
+                        DelVarTarget::NumericVar(name) => Some(Variable::Numeric(*name)),
+                        DelVarTarget::List(name) => Some(Variable::List(*name)),
+                        DelVarTarget::Matrix(name) => Some(Variable::Matrix(*name)),
+                        DelVarTarget::String(name) => Some(Variable::String(*name)),
+
+                        _ => None,
+                    });
+                }
+
+                if let Some(valence) = &chain.valence {
+                    defined.extend(valence.defined_variables());
+                }
+            }
+            Statement::SetUpEditor(editor) => {
+                defined.extend(editor.edited_lists().iter().copied().map(Variable::List));
+            }
+            Statement::Fiction(statement) => defined.extend(statement.defined_variables()),
+
+            _ => {}
+        }
+
+        defined
+    }
+
+    /// The named variables whose values this statement may read. Storing to an element of a
+    /// list or matrix reads the rest of it.
+    pub fn used_variables(&self) -> BTreeSet<Variable> {
+        let mut used = BTreeSet::new();
+
+        match self {
+            Statement::Store(expr, target) => {
+                expr.collect_variables(&mut used);
+                if matches!(
+                    target,
+                    StoreTarget::ListIndex(_) | StoreTarget::MatrixIndex(_)
+                ) {
+                    target.collect_variables(&mut used);
+                }
+            }
+            Statement::UserInput(input) => {
+                if let UserInput::Input {
+                    prompt: Some(prompt),
+                    ..
+                } = input
+                {
+                    prompt.collect_variables(&mut used);
+                }
+
+                for target in input.targets() {
+                    if matches!(
+                        target,
+                        StoreTarget::ListIndex(_) | StoreTarget::MatrixIndex(_)
+                    ) {
+                        target.collect_variables(&mut used);
+                    }
+                }
+            }
+            Statement::DelVarChain(chain) => {
+                if let Some(valence) = &chain.valence {
+                    used.extend(valence.used_variables());
+                }
+            }
+            Statement::SetUpEditor(_) => {}
+            Statement::Fiction(statement) => used.extend(statement.used_variables()),
+
+            _ => self.collect_variables(&mut used),
+        }
+
+        used
+    }
+
+    /// Adds every variable referenced by this statement to `variables`.
+    pub fn collect_variables(&self, variables: &mut BTreeSet<Variable>) {
+        match self {
+            Statement::ControlFlow(control_flow) => match control_flow {
+                ControlFlow::If(expr)
+                | ControlFlow::IfThen(expr)
+                | ControlFlow::While(expr)
+                | ControlFlow::Repeat(expr) => expr.collect_variables(variables),
+                ControlFlow::For(for_loop) => {
+                    for expr in [&for_loop.iterator, &for_loop.start, &for_loop.end] {
+                        expr.collect_variables(variables);
+                    }
+                    if let Some(step) = &for_loop.step {
+                        step.collect_variables(variables);
+                    }
+                }
+                ControlFlow::IsGt(is_ds) | ControlFlow::DsLt(is_ds) => {
+                    variables.insert(Variable::Numeric(is_ds.variable));
+                    is_ds.condition.collect_variables(variables);
+                }
+                ControlFlow::Menu(menu) => {
+                    menu.title.collect_variables(variables);
+                    for title in &menu.option_titles {
+                        title.collect_variables(variables);
+                    }
+                }
+
+                _ => {}
+            },
+            Statement::Generic(generic) => {
+                for argument in &generic.arguments {
+                    argument.collect_variables(variables);
+                }
+            }
+            Statement::DelVarChain(chain) => {
+                for target in &chain.deletions {
+                    match target {
+                        DelVarTarget::NumericVar(name) => {
+                            variables.insert(Variable::Numeric(*name));
+                        }
+                        DelVarTarget::List(name) => {
+                            variables.insert(Variable::List(*name));
+                        }
+                        DelVarTarget::Matrix(name) => {
+                            variables.insert(Variable::Matrix(*name));
+                        }
+                        DelVarTarget::String(name) => {
+                            variables.insert(Variable::String(*name));
+                        }
+                        DelVarTarget::ListAccess(access) => access.collect_variables(variables),
+                        DelVarTarget::MatrixAccess(access) => access.collect_variables(variables),
+
+                        _ => {}
+                    }
+                }
+
+                if let Some(valence) = &chain.valence {
+                    valence.collect_variables(variables);
+                }
+            }
+            Statement::SetUpEditor(editor) => {
+                variables.extend(editor.edited_lists().iter().copied().map(Variable::List));
+            }
+            Statement::StatPlot(plot) => {
+                for argument in plot.expressions() {
+                    argument.collect_variables(variables);
+                }
+            }
+            Statement::Expression(expr) => expr.collect_variables(variables),
+            Statement::Store(expr, target) => {
+                expr.collect_variables(variables);
+                target.collect_variables(variables);
+            }
+            Statement::UserInput(input) => {
+                if let UserInput::Input {
+                    prompt: Some(prompt),
+                    ..
+                } = input
+                {
+                    prompt.collect_variables(variables);
+                }
+
+                for target in input.targets() {
+                    target.collect_variables(variables);
+                }
+            }
+            Statement::Fiction(statement) => statement.collect_variables(variables),
+
+            Statement::None | Statement::ProgramInvocation(_) => {}
+        }
+    }
+
+    /// True if any expression in this statement may read or write a variable which does not
+    /// appear in it.
+    ///
+    /// See also: [`Expression::may_access_any_variable`]
+    pub fn may_access_any_variable(&self) -> bool {
+        self.expressions()
+            .into_iter()
+            .any(Expression::may_access_any_variable)
+    }
+}
+
+impl Program {
+    /// Compute the set of named variables referenced anywhere in the program.
+    ///
+    /// Variables named inside a string passed to `expr(` or `eval(` can't be seen, so analyses
+    /// must treat those functions as reading every variable.
+    pub fn variables(&self) -> BTreeSet<Variable> {
+        let mut variables = BTreeSet::new();
+
+        for line in &self.lines {
+            line.collect_variables(&mut variables);
+        }
+
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::components::DEFAULT_LISTS;
+    use test_files::test_tokenizer;
+
+    #[test]
+    fn defined_and_used() {
+        let version = test_files::test_version!();
+        let statement = |text: &str| Statement::from_text(text, version.clone()).unwrap();
+        let numeric = |name: u8| Variable::Numeric(NumericVarName(Token::OneByte(name)));
+
+        let input = statement("Input \"A=\",A");
+        assert_eq!(input.defined_variables(), BTreeSet::from([numeric(b'A')]));
+        assert!(input.used_variables().is_empty());
+
+        let prompt = statement("Prompt B,C");
+        assert_eq!(
+            prompt.defined_variables(),
+            BTreeSet::from([numeric(b'B'), numeric(b'C')])
+        );
+
+        assert_eq!(
+            statement("Input ").defined_variables(),
+            BTreeSet::from([numeric(b'X'), numeric(b'Y')])
+        );
+
+        let get_key = statement("getKey->K");
+        assert_eq!(get_key.defined_variables(), BTreeSet::from([numeric(b'K')]));
+        assert!(get_key.used_variables().is_empty());
+
+        let store = statement("A+1->B");
+        assert_eq!(store.defined_variables(), BTreeSet::from([numeric(b'B')]));
+        assert_eq!(store.used_variables(), BTreeSet::from([numeric(b'A')]));
+
+        let delvar = statement("DelVar CInput D");
+        assert_eq!(
+            delvar.defined_variables(),
+            BTreeSet::from([numeric(b'C'), numeric(b'D')])
+        );
+        assert!(delvar.used_variables().is_empty());
+
+        let restore = statement("SetUpEditor ");
+        assert_eq!(
+            restore.defined_variables(),
+            BTreeSet::from(DEFAULT_LISTS.map(Variable::List))
+        );
+        assert!(restore.used_variables().is_empty());
+
+        let named = statement("SetUpEditor L3,|LABC");
+        assert_eq!(
+            named.defined_variables(),
+            BTreeSet::from([
+                Variable::List(DEFAULT_LISTS[2]),
+                Variable::List(ListName::Custom([0x41, 0x42, 0x43, 0, 0])),
+            ])
+        );
+    }
+
+    #[test]
+    fn may_access_any_variable() {
+        let version = test_files::test_version!();
+        let statement = |text: &str| Statement::from_text(text, version.clone()).unwrap();
+
+        assert!(statement("expr(\"A\")+1->B").may_access_any_variable());
+        assert!(statement("Disp 2eval(Str1").may_access_any_variable());
+        assert!(statement("solve(X^2-2,X,1->L1(2").may_access_any_variable());
+        assert!(statement("1->L1(expr(Str1").may_access_any_variable());
+        assert!(statement("If expr(Str1").may_access_any_variable());
+        assert!(!statement("A+1->B").may_access_any_variable());
+        assert!(!statement("Disp sum(L1),Str1").may_access_any_variable());
+    }
+
+    #[test]
+    fn variables() {
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer
+            .tokenize("A+B->C\nDisp L1(C),Str1\nDelVar [A]For(I,1,2\nEnd")
+            .unwrap();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        assert_eq!(program.variables().len(), 7);
+        assert!(program
+            .variables()
+            .contains(&Variable::Numeric(NumericVarName(Token::OneByte(0x49)))));
+    }
+}
@@ -1,5 +1,7 @@
-use crate::optimize::Priority;
-use titokens::Version;
+use crate::optimize::{CostModel, DefaultCostModel, Priority};
+use crate::parse::SeparatorKind;
+use std::sync::Arc;
+use titokens::{Tokenizer, Version};
 
 #[derive(Clone, Debug)]
 /// Optimizer Configuration.
@@ -9,13 +11,99 @@ pub struct Config {
     pub mrov: Version,
 
     pub priority: Priority,
+
+    /// Language code (eg. `"en"` or `"fr"`) for the token names used when reading text and
+    /// writing diagnostics. Tokens without a translation fall back to English.
+    pub lang: String,
+
+    /// Timing estimates used to compare the speed of alternatives. See [`CostModel`].
+    pub cost_model: Arc<dyn CostModel>,
+
+    /// Remove screen commands which immediately repeat the previous line, like `ClrHome:ClrHome`.
+    /// The screen ends up the same, but it is drawn faster, which may matter to programs which
+    /// rely on drawing for timing. Off by default. See [`Program::redundant_screen_commands`].
+    ///
+    /// [`Program::redundant_screen_commands`]: crate::parse::Program::redundant_screen_commands
+    pub deduplicate_screen_commands: bool,
+
+    /// Rewrite counting loops written with `Lbl`, `IS>(`/`DS<(`, and `Goto` as `For(` loops. Only
+    /// loops which are certain to behave identically are rewritten. Off by default.
+    pub rewrite_counting_loops: bool,
+
+    /// Remove closing parentheses, brackets, braces, and quotes at the end of each line and before
+    /// each store arrow. On by default; turn it off for fully-parenthesized output.
+    pub strip_trailing_parens: bool,
+
+    /// Leave out `*` wherever the multiplication reads the same without it, like `2X` for `2*X`.
+    /// On by default; turn it off to write every multiplication explicitly.
+    pub implicit_multiplication: bool,
+
+    /// Separate every line with this separator instead of the one used in the original program.
+    /// `None` by default.
+    pub separator: Option<SeparatorKind>,
+
+    /// Treat lines which are nothing but a string literal, like `"BY J. DOE`, as comments: they are
+    /// written out exactly as they were read and are never moved or removed. TI-BASIC has no
+    /// comments, so programs use these for attribution and notes. Off by default. See
+    /// [`Statement::is_comment_string`].
+    ///
+    /// [`Statement::is_comment_string`]: crate::parse::statements::Statement::is_comment_string
+    pub preserve_comment_strings: bool,
+
+    /// Have [`Program::optimize`] return a [`ChangeRecord`] for every change it makes. Off by
+    /// default.
+    ///
+    /// [`Program::optimize`]: crate::parse::Program::optimize
+    /// [`ChangeRecord`]: crate::ChangeRecord
+    pub explain: bool,
+}
+
+impl Default for Config {
+    /// [`Config::new`] targeting the latest version of every model.
+    fn default() -> Self {
+        Self::new(titokens::version::LATEST.clone())
+    }
 }
 
 impl From<Version> for Config {
+    /// Same as [`Config::new`].
     fn from(value: Version) -> Self {
         Self {
             mrov: value,
             priority: Priority::Neutral,
+            lang: "en".to_string(),
+            cost_model: Arc::new(DefaultCostModel),
+            deduplicate_screen_commands: false,
+            rewrite_counting_loops: false,
+            strip_trailing_parens: true,
+            implicit_multiplication: true,
+            separator: None,
+            preserve_comment_strings: false,
+            explain: false,
         }
     }
 }
+
+impl Config {
+    /// The default configuration for programs which should run on `mrov`: [`Priority::Neutral`],
+    /// English token names, and the default for every other option.
+    ///
+    /// ```
+    /// # use tibo::{Config, Priority};
+    /// let config = Config::new(titokens::version::LATEST_MONO.clone());
+    /// assert_eq!(config.priority, Priority::Neutral);
+    ///
+    /// let config = Config {
+    ///     priority: Priority::Size,
+    ///     ..Config::new(titokens::version::LATEST_MONO.clone())
+    /// };
+    /// ```
+    pub fn new(mrov: Version) -> Self {
+        Self::from(mrov)
+    }
+
+    /// A [`Tokenizer`] for [`Config::mrov`] in [`Config::lang`].
+    pub fn tokenizer(&self) -> Tokenizer {
+        Tokenizer::new(self.mrov.clone(), &self.lang)
+    }
+}
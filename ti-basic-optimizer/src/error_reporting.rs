@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::ops::Range;
 use titokens::tokenizer::TokenBoundaries;
 
@@ -99,12 +100,14 @@ pub(crate) use expect_tok;
 pub(crate) use next_or_err;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "pretty-errors"), allow(dead_code))]
 enum LabelKind {
     Single(usize),
     Span(Range<usize>),
 }
 
 impl LabelKind {
+    #[cfg(feature = "pretty-errors")]
     fn string_indices(&self, token_boundaries: &TokenBoundaries) -> Range<usize> {
         match self {
             LabelKind::Single(tok_idx) => token_boundaries.single(*tok_idx),
@@ -113,20 +116,72 @@ impl LabelKind {
     }
 }
 
-pub trait Report: Sized {
+pub trait Report: Sized + Display {
     /// Format and print this error to stderr, using the provided [`TokenBoundaries`] to translate
-    /// the tokens. This does
+    /// the tokens.
     ///
     /// The `ariadne` crate seems to choke on Unicode input; tokenize without Unicode.
+    #[cfg(feature = "pretty-errors")]
     fn report(self, boundaries: TokenBoundaries) {
         self.translate(&boundaries)
             .eprint(ariadne::Source::from(boundaries.to_string()))
             .unwrap();
     }
 
+    /// Print this error to stderr.
+    ///
+    /// Without the `pretty-errors` feature, the source text is not shown.
+    #[cfg(not(feature = "pretty-errors"))]
+    fn report(self, _boundaries: TokenBoundaries) {
+        eprintln!("{self}");
+    }
+
+    #[cfg(feature = "pretty-errors")]
     fn translate<'a>(self, boundaries: &TokenBoundaries) -> ariadne::Report<'a>;
 }
 
+/// How serious a report is. Errors are problems which stop the program from being optimized or
+/// will fail on the calculator; warnings are for code which runs but is probably not what was
+/// meant.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        })
+    }
+}
+
+/// Shared [`Display`] implementation for reports, of the form
+/// `Error[code]: message (at token 5)`, with the suggestion on the next line.
+fn write_report(
+    f: &mut Formatter<'_>,
+    severity: Severity,
+    code: Option<u16>,
+    message: &str,
+    location: String,
+    suggestion: &Option<String>,
+) -> std::fmt::Result {
+    write!(f, "{severity}")?;
+    if let Some(code) = code {
+        write!(f, "[{code}]")?;
+    }
+    write!(f, ": {message} ({location})")?;
+
+    if let Some(suggestion) = suggestion {
+        write!(f, "\nHelp: {suggestion}")?;
+    }
+
+    Ok(())
+}
+
 /// `TokenReport` is used to report errors at the token level.
 ///
 /// Token indices are usually obtained by calling [`Tokens::current_position`](titokens::Tokens::current_position).
@@ -137,7 +192,9 @@ pub struct TokenReport {
     message: String,
     suggestion: Option<String>,
     code: Option<u16>,
+    severity: Severity,
 
+    #[cfg_attr(not(feature = "pretty-errors"), allow(dead_code))]
     labels: Vec<(LabelKind, String)>,
 }
 
@@ -151,6 +208,7 @@ impl TokenReport {
             message: message.to_string(),
             suggestion: suggestion.map(|x| x.to_string()),
             code: None,
+            severity: Severity::Error,
 
             labels: vec![],
         }
@@ -182,16 +240,45 @@ impl TokenReport {
 
         self
     }
+
+    /// Report this as a [`Severity::Warning`] or [`Severity::Error`]. Reports are errors unless
+    /// this is called.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+
+        self
+    }
+
+    /// How serious this report is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl Display for TokenReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_report(
+            f,
+            self.severity,
+            self.code,
+            &self.message,
+            format!("at token {}", self.location),
+            &self.suggestion,
+        )
+    }
 }
 
+impl std::error::Error for TokenReport {}
+
 impl Report for TokenReport {
+    #[cfg(feature = "pretty-errors")]
     fn translate<'a>(self, boundaries: &TokenBoundaries) -> ariadne::Report<'a> {
-        let mut builder = ariadne::Report::build(
-            ariadne::ReportKind::Error,
-            (),
-            boundaries.single(self.location).start,
-        )
-        .with_message(self.message);
+        let kind = match self.severity {
+            Severity::Warning => ariadne::ReportKind::Warning,
+            Severity::Error => ariadne::ReportKind::Error,
+        };
+        let mut builder = ariadne::Report::build(kind, (), boundaries.single(self.location).start)
+            .with_message(self.message);
 
         if self.labels.is_empty() {
             builder = builder.with_label(
@@ -246,7 +333,23 @@ impl LineReport {
     }
 }
 
+impl Display for LineReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_report(
+            f,
+            Severity::Error,
+            self.code,
+            &self.message,
+            format!("on line {}", self.location),
+            &self.suggestion,
+        )
+    }
+}
+
+impl std::error::Error for LineReport {}
+
 impl Report for LineReport {
+    #[cfg(feature = "pretty-errors")]
     fn translate<'a>(self, boundaries: &TokenBoundaries) -> ariadne::Report<'a> {
         let mut line_start_idx = None;
         let mut line_end_idx = None;
@@ -292,3 +395,104 @@ impl Report for LineReport {
         builder.finish()
     }
 }
+
+/// Any error tibo reports: parse errors are [`TokenReport`]s, and errors found while analyzing
+/// the parsed program are [`LineReport`]s. Both convert into this with `?`.
+#[derive(Clone, Debug)]
+#[must_use]
+pub enum Error {
+    Token(TokenReport),
+    Line(LineReport),
+}
+
+impl From<TokenReport> for Error {
+    fn from(value: TokenReport) -> Self {
+        Error::Token(value)
+    }
+}
+
+impl From<LineReport> for Error {
+    fn from(value: LineReport) -> Self {
+        Error::Line(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Token(report) => report.fmt(f),
+            Error::Line(report) => report.fmt(f),
+        }
+    }
+}
+
+/// `Display` already shows the wrapped report, so it is not also given as the `source()`;
+/// otherwise it would be printed twice by anything which walks the error chain.
+impl std::error::Error for Error {}
+
+impl Report for Error {
+    #[cfg(feature = "pretty-errors")]
+    fn translate<'a>(self, boundaries: &TokenBoundaries) -> ariadne::Report<'a> {
+        match self {
+            Error::Token(report) => report.translate(boundaries),
+            Error::Line(report) => report.translate(boundaries),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let report =
+            TokenReport::new(3, "Chained store.", Some("Store Ans on the next line.")).with_code(7);
+        assert_eq!(
+            report.to_string(),
+            "Error[7]: Chained store. (at token 3)\nHelp: Store Ans on the next line."
+        );
+
+        let report =
+            TokenReport::new(5, "Unreachable line.", None).with_severity(Severity::Warning);
+        assert_eq!(report.severity(), Severity::Warning);
+        assert_eq!(
+            report.to_string(),
+            "Warning: Unreachable line. (at token 5)"
+        );
+
+        let report = LineReport::new(2, "Unsupported loop variable.", None);
+        assert_eq!(
+            report.to_string(),
+            "Error: Unsupported loop variable. (on line 2)"
+        );
+    }
+
+    #[test]
+    fn unified() {
+        fn parse() -> Result<(), TokenReport> {
+            Err(TokenReport::new(3, "Chained store.", None))
+        }
+
+        fn analyze() -> Result<(), LineReport> {
+            Err(LineReport::new(2, "Unsupported loop variable.", None))
+        }
+
+        fn both(fail_parse: bool) -> Result<(), Error> {
+            if fail_parse {
+                parse()?;
+            }
+            analyze()?;
+
+            Ok(())
+        }
+
+        assert!(matches!(both(true), Err(Error::Token(_))));
+        assert!(matches!(both(false), Err(Error::Line(_))));
+        assert_eq!(
+            both(false).unwrap_err().to_string(),
+            "Error: Unsupported loop variable. (on line 2)"
+        );
+        assert!(std::error::Error::source(&both(false).unwrap_err()).is_none());
+    }
+}
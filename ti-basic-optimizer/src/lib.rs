@@ -7,4 +7,8 @@ mod optimize;
 pub mod parse;
 
 pub use config::Config;
-pub use optimize::Priority;
+pub use error_reporting::{Error, LineReport, Report, Severity, TokenReport};
+pub use optimize::{
+    ChangeRecord, CostModel, DefaultCostModel, FloatExt, MathConstantKind, OptimizationOpportunity,
+    Priority, StatementCandidate,
+};
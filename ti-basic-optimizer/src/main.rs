@@ -1,8 +1,11 @@
 use clap::Parser;
-use deku::prelude::*;
 use std::{fs, io, path::PathBuf};
 
-use titokens::{ti_connect_file::TIProgram, Tokenizer, Tokens, Version};
+use titokens::{
+    ti_connect_file::{ReadError, TIProgram},
+    tokenizer::{NameStyle, Newline, StringifyOptions},
+    Model, Token, Tokens, Version,
+};
 
 use tibo::parse::Program;
 use tibo::*;
@@ -10,7 +13,7 @@ use tibo::*;
 #[derive(Debug)]
 enum LoadError {
     IoError(io::Error),
-    DekuError(deku::DekuError),
+    ReadError(ReadError),
 }
 
 #[derive(Parser, Debug)]
@@ -30,6 +33,11 @@ pub struct Args {
         help = "Provide a tokenized 8xp to optimize. Mutually exclusive with --txt."
     )]
     path_to_8xp_file: Option<PathBuf>,
+    #[arg(
+        long = "out",
+        help = "Also save the optimized program as an 8xp, named after the file (eg. HELLO.8xp)."
+    )]
+    path_to_output_8xp: Option<PathBuf>,
 
     #[arg(long = "size", group = "priority", help = "Prioritize file size.")]
     size: bool,
@@ -39,40 +47,108 @@ pub struct Args {
         help = "Prioritize execution speed."
     )]
     speed: bool,
+
+    #[arg(
+        long = "model",
+        default_value = "latest",
+        help = "Calculator model to optimize for, as named on the token sheets (eg. TI-84+CE)."
+    )]
+    model: Model,
+    #[arg(
+        long = "os-version",
+        default_value = "9.99.99",
+        help = "OS version to optimize for (eg. 5.3.0). Defaults to the newest."
+    )]
+    os_version: String,
+
+    #[arg(
+        long = "dedup-screen",
+        help = "Remove screen commands which repeat the previous line. This can change timing."
+    )]
+    dedup_screen: bool,
+
+    #[arg(
+        long = "for-loops",
+        help = "Rewrite counting loops made with Lbl, IS>( or DS<(, and Goto as For( loops."
+    )]
+    for_loops: bool,
+
+    #[arg(
+        long = "keep-parens",
+        help = "Keep closing parentheses, brackets, braces, and quotes at the ends of lines."
+    )]
+    keep_parens: bool,
+
+    #[arg(
+        long = "explicit-mul",
+        help = "Write every multiplication with *, even where implicit multiplication would work."
+    )]
+    explicit_mul: bool,
+
+    #[arg(
+        long = "newlines",
+        help = "Separate every line with a newline, even where the input used a colon."
+    )]
+    newlines: bool,
+
+    #[arg(
+        long = "keep-comments",
+        help = "Keep lines which are only a string literal exactly as written, in place."
+    )]
+    keep_comments: bool,
+
+    #[arg(
+        long = "check",
+        help = "Report problems and print the size of the program without optimizing it. Exits with status 1 if any problem is an error."
+    )]
+    check: bool,
+
+    #[arg(
+        long = "explain",
+        help = "Print a note for every change the optimizer makes."
+    )]
+    explain: bool,
+
+    #[arg(
+        long = "histogram",
+        help = "Print how many times each token appears in the optimized program."
+    )]
+    histogram: bool,
+
+    #[arg(
+        long = "lang",
+        default_value = "en",
+        help = "Language code for token names in input, output, and errors."
+    )]
+    lang: String,
+
+    #[arg(
+        long = "display-names",
+        help = "Output Unicode display names (eg. →) instead of accessible names (eg. ->)."
+    )]
+    display_names: bool,
+    #[arg(long = "crlf", help = "Output CRLF newlines.")]
+    crlf: bool,
 }
 
-fn parse_8xp(path_buf: PathBuf) -> Result<parse::Program, LoadError> {
+fn parse_8xp(path_buf: PathBuf, config: &Config) -> Result<parse::Program, LoadError> {
     let bytes = fs::read(path_buf).map_err(LoadError::IoError)?;
-    let ti_program = TIProgram::from_bytes((&bytes, 0))
-        .map_err(LoadError::DekuError)?
-        .1;
-
-    let mut tokens = ti_program.read_tokens();
-    Ok(Program::from_tokens(
-        &mut tokens,
-        &Tokenizer::new(titokens::version::LATEST.clone(), "en"),
-    ))
+    let ti_program = TIProgram::read(&bytes).map_err(LoadError::ReadError)?;
+
+    let mut tokens = ti_program.read_tokens_for(config.mrov.clone());
+    let tokenizer = config.tokenizer();
+    let boundaries = tokens.stringify_with_boundaries(&tokenizer);
+    Ok(Program::from_tokens(&mut tokens, &tokenizer).with_source(boundaries))
 }
 
-fn parse_txt(path_buf: PathBuf) -> Result<parse::Program, LoadError> {
+fn parse_txt(path_buf: PathBuf, config: &Config) -> Result<parse::Program, LoadError> {
     let string = fs::read_to_string(path_buf).map_err(LoadError::IoError)?;
-    Ok(Program::from_text(
-        &string,
-        titokens::version::LATEST.clone(),
-    ))
+    Ok(Program::from_text_with_config(&string, config))
 }
 
 fn main() {
     let settings = Args::parse();
 
-    let loaded = if let Some(path_buf) = settings.path_to_8xp_file {
-        parse_8xp(path_buf)
-    } else {
-        let path_buf = settings.path_to_txt_file.unwrap();
-
-        parse_txt(path_buf)
-    };
-
     let priority = if settings.speed {
         Priority::Speed
     } else if settings.size {
@@ -81,14 +157,75 @@ fn main() {
         Priority::Neutral
     };
 
-    let version = titokens::version::LATEST.clone();
+    let version = Version::new(settings.model, &settings.os_version).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(2);
+    });
     let config = Config {
-        mrov: version.clone(),
         priority,
+        lang: settings.lang,
+        deduplicate_screen_commands: settings.dedup_screen,
+        rewrite_counting_loops: settings.for_loops,
+        strip_trailing_parens: !settings.keep_parens,
+        implicit_multiplication: !settings.explicit_mul,
+        separator: settings.newlines.then_some(parse::SeparatorKind::Newline),
+        preserve_comment_strings: settings.keep_comments,
+        explain: settings.explain,
+        ..Config::new(version.clone())
+    };
+
+    let loaded = if let Some(path_buf) = settings.path_to_8xp_file {
+        parse_8xp(path_buf, &config)
+    } else {
+        let path_buf = settings.path_to_txt_file.unwrap();
+
+        parse_txt(path_buf, &config)
+    };
+
+    let tokenizer = config.tokenizer();
+    let output_options = StringifyOptions {
+        names: if settings.display_names {
+            NameStyle::Display
+        } else {
+            NameStyle::default()
+        },
+        newline: if settings.crlf {
+            Newline::CrLf
+        } else {
+            Newline::Lf
+        },
     };
-    let tokenizer = Tokenizer::new(version.clone(), "en");
 
     if let Ok(mut program) = loaded {
+        if settings.check {
+            let metrics = program.metrics();
+            let size: usize = program
+                .reconstruct(&config)
+                .iter()
+                .map(Token::byte_len)
+                .sum();
+            println!(
+                "{} lines, {size} bytes, {} labels, {} variables, cyclomatic complexity {}",
+                metrics.statements,
+                metrics.labels,
+                metrics.variables,
+                metrics.cyclomatic_complexity
+            );
+
+            let reports = program.lint();
+            let failed = reports
+                .iter()
+                .any(|report| report.severity() == Severity::Error);
+            for report in reports {
+                match program.source() {
+                    Some(boundaries) => report.report(boundaries.clone()),
+                    None => eprintln!("{report}"),
+                }
+            }
+
+            std::process::exit(failed.into());
+        }
+
         if cfg!(feature = "round-trip") {
             let a = program.reconstruct(&config);
             let a_program = Program::from_tokens(
@@ -105,13 +242,69 @@ fn main() {
                 panic!("test failed");
             }
 
-            println!("{}", tokenizer.stringify(&b));
+            println!("{}", tokenizer.stringify_with(&b, &output_options));
         } else {
             println!("Loaded program successfully!");
-            program.optimize(&config);
+            let before = settings.explain.then(|| program.reconstruct(&config));
+            let changes = program.optimize(&config);
 
-            let tokens = program.reconstruct(&config);
-            println!("{}", tokenizer.stringify(&tokens));
+            if let Some(before) = before {
+                println!("== Before ==");
+                println!("{}", tokenizer.stringify_with(&before, &output_options));
+                println!("== Changes ==");
+                for change in &changes {
+                    println!("{change}");
+                }
+                println!("== After ==");
+            }
+
+            let tokens = match program.reconstruct_checked(&config, &tokenizer) {
+                Ok(tokens) => tokens,
+                Err(unavailable) => {
+                    let names = unavailable
+                        .into_iter()
+                        .map(|token| tokenizer.stringify(&[token]).to_string())
+                        .collect::<Vec<_>>();
+                    eprintln!(
+                        "The optimized program uses tokens which don't exist on the target: {}",
+                        names.join(", ")
+                    );
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", tokenizer.stringify_with(&tokens, &output_options));
+
+            if let Some(path_buf) = settings.path_to_output_8xp {
+                let name = path_buf
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_uppercase())
+                    .unwrap_or_default();
+
+                let written = program
+                    .to_8xp_bytes(&name, &config)
+                    .map_err(|err| err.to_string())
+                    .and_then(|bytes| fs::write(&path_buf, bytes).map_err(|err| err.to_string()));
+                if let Err(err) = written {
+                    eprintln!("Failed to save {}: {err}", path_buf.display());
+                    std::process::exit(1);
+                }
+            }
+
+            if settings.histogram {
+                let mut histogram = program
+                    .token_histogram(&config)
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                histogram.sort_by(|a, b| b.1.cmp(&a.1));
+
+                println!("== Token Histogram ==");
+                for (token, count) in histogram {
+                    let name = tokenizer
+                        .stringify_with(&[token], &output_options)
+                        .to_string();
+                    println!("{count:>6} {}", name.escape_debug());
+                }
+            }
         }
     } else {
         loaded.unwrap();
@@ -0,0 +1,104 @@
+//! # Adjacent Label Merging
+//! `Lbl A:Lbl B` declares two labels which land in the same place, so every `Goto B` can be
+//! written as `Goto A` and `Lbl B` removed. Fewer labels means more of the remaining ones get
+//! one-token names in [`Program::optimize_label_names`].
+
+use std::collections::BTreeMap;
+
+use super::label_name::relabeled;
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    statements::{ControlFlow, LabelName, Statement},
+    Program,
+};
+
+impl Program {
+    /// Merges labels declared on consecutive lines (ignoring removed lines) into the first label
+    /// of each run, redirecting every `Goto` and `Menu(` option to it.
+    ///
+    /// A repeated declaration of a label is not where that label lands, so it neither absorbs the
+    /// labels after it nor is absorbed itself.
+    pub fn optimize_adjacent_labels(&mut self) {
+        self.apply_rewrites(self.adjacent_label_rewrites());
+    }
+
+    pub(crate) fn adjacent_label_rewrites(&self) -> Vec<Rewrite> {
+        let declarations = self.label_declarations();
+        let is_landing = |name: &LabelName, idx: usize| declarations[name] == idx;
+
+        let mut redirects: BTreeMap<LabelName, LabelName> = BTreeMap::new();
+        let mut merged: BTreeMap<usize, (LabelName, LabelName)> = BTreeMap::new();
+        let mut landing: Option<LabelName> = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            match line {
+                Statement::None => {}
+                Statement::ControlFlow(ControlFlow::Lbl(name)) if is_landing(name, idx) => {
+                    if let Some(target) = landing {
+                        redirects.insert(*name, target);
+                        merged.insert(idx, (*name, target));
+                    } else {
+                        landing = Some(*name);
+                    }
+                }
+
+                _ => landing = None,
+            }
+        }
+
+        let mut rewrites = vec![];
+        for (idx, line) in self.lines.iter().enumerate() {
+            if let Some((name, target)) = merged.get(&idx) {
+                rewrites.push(Rewrite::remove(
+                    idx,
+                    format!("label {name} (merged into {target})"),
+                ));
+                continue;
+            }
+
+            if let Some(redirected) =
+                relabeled(line, |label| *redirects.get(&label).unwrap_or(&label))
+            {
+                rewrites.push(Rewrite::replace(idx, redirected, None));
+            }
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label_name;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::Config;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn adjacent_labels() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/adjacent-labels.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_adjacent_labels();
+
+        let (expected, _) = tokenizer
+            .tokenize("Goto A\nLbl A\nDisp 1\nMenu(\"M\",\"X\",A,\"Y\",A,\"Z\",D\nLbl D\nLbl A\nLbl E\nGoto E")
+            .unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(test_version!())),
+            expected.collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            program.label_declarations().keys().collect::<Vec<_>>(),
+            vec![&label_name!('A'), &label_name!('D'), &label_name!('E')]
+        );
+    }
+
+    #[test]
+    fn adjacent_labels_keep_comment_strings() {
+        assert_comment_strings_kept("\"TOP\nLbl A\nLbl B\n\"LOOP\nGoto A\nGoto B", |p, _| {
+            p.optimize_adjacent_labels()
+        });
+    }
+}
@@ -0,0 +1,249 @@
+//! # Counting Loops
+//! Before `For(` was fast, and still in plenty of programs today, counting loops were written by
+//! hand with a label, `IS>(`, and `Goto`:
+//! ```text
+//! 1->I
+//! Lbl A
+//! Disp I
+//! IS>(I,10
+//! Goto A
+//! ```
+//! `IS>(I,10` increments `I` and skips the `Goto` once `I` is greater than 10, so this is exactly
+//! `For(I,1,10):Disp I:End`, provided the body runs at least once and doesn't touch `I`. The
+//! `For(` loop is smaller, and it doesn't have to search the program for the label on every
+//! iteration. `DS<(` loops become `For(` loops with a step of -1.
+//!
+//! Only enabled with [`Config::rewrite_counting_loops`](crate::Config::rewrite_counting_loops).
+
+use tifloats::{tifloat, Float};
+
+use crate::analyze::{is_inert_command, Variable};
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    components::{NumericVarName, Operand, StoreTarget},
+    expression::Expression,
+    statements::{control_flow::ForLoop, ControlFlow, Statement},
+    Program,
+};
+
+/// A counting loop found by [`Program::counting_loops`].
+struct CountingLoop {
+    /// The line of the `Lbl`. The line before it initializes the iterator.
+    label: usize,
+    /// The line of the `IS>(` or `DS<(`. The `Goto` is on the line after it.
+    increment: usize,
+
+    iterator: NumericVarName,
+    start: Float,
+    end: Float,
+    decrementing: bool,
+}
+
+impl Program {
+    /// Rewrites `Lbl`/`IS>(`/`Goto` counting loops as `For(` loops. See the
+    /// [module documentation](self).
+    pub fn optimize_counting_loops(&mut self) {
+        // a loop around another one can only be rewritten once the inner one has been
+        loop {
+            let rewrites = self.counting_loop_rewrites();
+            if rewrites.is_empty() {
+                break;
+            }
+
+            self.apply_rewrites(rewrites);
+        }
+    }
+
+    /// The rewrites for each counting loop which can be rewritten as the program is now.
+    pub(crate) fn counting_loop_rewrites(&self) -> Vec<Rewrite> {
+        let mut counting_loops = self.counting_loops();
+        counting_loops.sort_by_key(|counting_loop| counting_loop.label);
+
+        let mut rewrites = vec![];
+        for counting_loop in counting_loops {
+            let step = counting_loop
+                .decrementing
+                .then(|| Expression::Operand(tifloat!(-0x0010000000000000 * 10 ^ 0).into()));
+
+            rewrites.push(Rewrite::replace(
+                counting_loop.label,
+                Statement::ControlFlow(ControlFlow::For(ForLoop {
+                    iterator: Expression::Operand(counting_loop.iterator.into()),
+                    start: Expression::Operand(counting_loop.start.into()),
+                    end: Expression::Operand(counting_loop.end.into()),
+                    step,
+                    has_ending_paren: false,
+                })),
+                Some("rewrote Lbl/Goto counting loop as For(".to_string()),
+            ));
+            rewrites.push(Rewrite::replace(
+                counting_loop.increment,
+                Statement::ControlFlow(ControlFlow::End),
+                None,
+            ));
+            rewrites.push(Rewrite::remove(
+                counting_loop.increment + 1,
+                "Goto of counting loop",
+            ));
+        }
+
+        rewrites
+    }
+
+    /// Every counting loop in the program. Their bodies can't jump anywhere, so they can't
+    /// overlap.
+    fn counting_loops(&self) -> Vec<CountingLoop> {
+        let declarations = self.label_declarations();
+        let usages = self.label_usages();
+
+        let mut counting_loops = vec![];
+        for (label, &label_idx) in declarations.iter() {
+            // the label must only be reached from the line before it and the `Goto` after the
+            // `IS>(`/`DS<(`.
+            let [goto_idx] = usages.get(label).map(Vec::as_slice).unwrap_or_default() else {
+                continue;
+            };
+            let goto_idx = *goto_idx;
+            if goto_idx < label_idx + 2 || label_idx < 1 {
+                continue;
+            }
+
+            let (is_ds, decrementing) = match &self.lines[goto_idx - 1] {
+                Statement::ControlFlow(ControlFlow::IsGt(is_ds)) => (is_ds, false),
+                Statement::ControlFlow(ControlFlow::DsLt(is_ds)) => (is_ds, true),
+                _ => continue,
+            };
+
+            let Statement::Store(
+                Expression::Operand(Operand::NumericLiteral(start)),
+                StoreTarget::NumericVar(iterator) | StoreTarget::NumericVarOrListName(iterator),
+            ) = &self.lines[label_idx - 1]
+            else {
+                continue;
+            };
+
+            let Expression::Operand(Operand::NumericLiteral(end)) = &is_ds.condition else {
+                continue;
+            };
+
+            // the hand-written loop always runs once, but the `For(` loop would not.
+            let runs_once = if decrementing {
+                start >= end
+            } else {
+                start <= end
+            };
+
+            if *iterator != is_ds.variable
+                || !runs_once
+                || self.is_guarded_single_line(label_idx - 1)
+                || !self.is_counting_loop_body(label_idx + 1..goto_idx - 1, *iterator)
+            {
+                continue;
+            }
+
+            counting_loops.push(CountingLoop {
+                label: label_idx,
+                increment: goto_idx - 1,
+                iterator: *iterator,
+                start: *start,
+                end: *end,
+                decrementing,
+            });
+        }
+
+        counting_loops
+    }
+
+    /// True if the lines in `body` can't jump anywhere or change `iterator` (see
+    /// [`Program::may_change_variable`]), and all of the blocks they open are closed within
+    /// `body`.
+    fn is_counting_loop_body(
+        &self,
+        body: std::ops::Range<usize>,
+        iterator: NumericVarName,
+    ) -> bool {
+        if self.may_change_variable(body.clone(), Variable::Numeric(iterator)) {
+            return false;
+        }
+
+        let mut depth = 0usize;
+
+        for idx in body.clone() {
+            let was_conditional = self.is_guarded_single_line(idx);
+
+            match &self.lines[idx] {
+                Statement::None
+                | Statement::Expression(_)
+                | Statement::SetUpEditor(_)
+                | Statement::Store(..) => {}
+                Statement::Generic(generic) if is_inert_command(generic.kind) => {}
+
+                Statement::ControlFlow(control_flow) => match control_flow {
+                    ControlFlow::If(_) => {}
+                    ControlFlow::IfThen(_)
+                    | ControlFlow::While(_)
+                    | ControlFlow::Repeat(_)
+                    | ControlFlow::For(_)
+                        if !was_conditional =>
+                    {
+                        depth += 1
+                    }
+                    ControlFlow::Else if !was_conditional && depth > 0 => {}
+                    ControlFlow::End if !was_conditional && depth > 0 => depth -= 1,
+
+                    _ => return false,
+                },
+
+                _ => return false,
+            }
+        }
+
+        // the last line of the body would otherwise guard the `IS>(`.
+        depth == 0 && !self.is_guarded_single_line(body.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::Config;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn counting_loops_rewritten() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/counting-loop.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_counting_loops();
+
+        let (expected, _) = tokenizer
+            .tokenize("1->I\nFor(I,1,9\nDisp I\nEnd\n5->J\nFor(J,5,1,~1\nIf J=2\nThen\nOutput(J,1,\"X\nEnd\nEnd\nDisp \"DONE")
+            .unwrap();
+
+        assert_eq!(
+            program.reconstruct(&Config::from(test_version!())),
+            expected.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn other_loops_kept() {
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/counting-loop-kept.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        let before = program.reconstruct(&config);
+        program.optimize_counting_loops();
+
+        assert_eq!(program.reconstruct(&config), before);
+    }
+
+    #[test]
+    fn counting_loops_keep_comment_strings() {
+        assert_comment_strings_kept(
+            "\"COUNT\n1->I\nLbl A\n\"BODY\nDisp I\nIS>(I,9\nGoto A\n\"DONE",
+            |p, _| p.optimize_counting_loops(),
+        );
+    }
+}
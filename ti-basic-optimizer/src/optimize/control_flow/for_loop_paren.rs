@@ -0,0 +1,79 @@
+//! # `For(` Parentheses
+//! When the first line of a `For(` loop is an `If` without a `Then`, leaving off the `For(`'s
+//! closing parenthesis makes every iteration of the loop much slower:
+//! ```text
+//! For(I,1,100
+//! If I=50
+//! Disp I
+//! End
+//! ```
+//! Closing the parenthesis costs one byte, so this is skipped under
+//! [`Priority::Size`](crate::Priority::Size).
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    statements::{ControlFlow, Statement},
+    Program,
+};
+
+impl Program {
+    /// Closes the parentheses of `For(` loops whose first line is an `If` without a `Then`.
+    pub fn optimize_for_loop_parentheses(&mut self) {
+        self.apply_rewrites(self.for_loop_paren_rewrites());
+    }
+
+    pub(crate) fn for_loop_paren_rewrites(&self) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            if let Statement::ControlFlow(ControlFlow::For(for_loop)) = line {
+                if for_loop.has_ending_paren {
+                    continue;
+                }
+
+                let next_is_if = matches!(
+                    self.lines[idx + 1..]
+                        .iter()
+                        .find(|line| !matches!(line, Statement::None)),
+                    Some(Statement::ControlFlow(ControlFlow::If(_)))
+                );
+
+                if next_is_if {
+                    let mut closed = for_loop.clone();
+                    closed.has_ending_paren = true;
+
+                    rewrites.push(Rewrite::replace(
+                        idx,
+                        Statement::ControlFlow(ControlFlow::For(closed)),
+                        Some("kept closing ) of For( before If".to_string()),
+                    ));
+                }
+            }
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn for_loop_parentheses() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/for-loop-paren.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_for_loop_parentheses();
+
+        let (expected, _) = tokenizer
+            .tokenize("For(I,1,9)\nIf I=3\nDisp I\nEnd\nFor(J,1,9\nIf J=3\nThen\nDisp J\nEnd\nEnd")
+            .unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(test_version!())),
+            expected.collect::<Vec<_>>()
+        );
+    }
+}
@@ -5,9 +5,12 @@
 //! As a micro-optimization, we choose letters before numbers because letters seem to be faster by a
 //! handful of CC's (perhaps they're checked first?).
 
+use std::collections::BTreeMap;
+
+use crate::optimize::rewrite::Rewrite;
 use crate::parse::statements::control_flow::Menu;
 use crate::parse::{
-    statements::{ControlFlow, LabelName, Statement},
+    statements::{ControlFlow, DelVarChain, LabelName, Statement},
     Program,
 };
 use itertools::Itertools;
@@ -66,74 +69,99 @@ fn label_name(mut rank: usize) -> LabelName {
     )
 }
 
+/// `line` with each label it jumps to passed through `rename`, if it is a `Goto` or `Menu(` (or
+/// a `DelVar` chain ending in one) and any of its labels change.
+pub(super) fn relabeled(
+    line: &Statement,
+    rename: impl Fn(LabelName) -> LabelName,
+) -> Option<Statement> {
+    let mut relabeled = line.clone();
+    let mut target = &mut relabeled;
+    while let Statement::DelVarChain(DelVarChain {
+        valence: Some(valence),
+        ..
+    }) = target
+    {
+        target = &mut **valence;
+    }
+
+    match target {
+        Statement::ControlFlow(ControlFlow::Goto(label)) => *label = rename(*label),
+        Statement::ControlFlow(ControlFlow::Menu(Menu { option_labels, .. })) => {
+            for label in option_labels {
+                *label = rename(*label);
+            }
+        }
+
+        // nothing else can use labels
+        _ => return None,
+    }
+
+    (relabeled != *line).then_some(relabeled)
+}
+
 #[allow(rustdoc::private_intra_doc_links)]
 impl Program {
     /// This optimization has two steps:
     /// - Clear unused label declarations
     /// - Optimize the length of label names so that more commonly used labels have shorter names.
     ///
+    /// An unused label which is the line guarded by an `If`, `IS>(`, or `DS<(` is replaced with
+    /// [`Statement::safe_noop`] rather than removed, so the conditional still guards it.
+    ///
     /// See also: [`label_name`]
     pub fn optimize_label_names(&mut self) {
+        self.apply_rewrites(self.label_name_rewrites());
+    }
+
+    pub(crate) fn label_name_rewrites(&self) -> Vec<Rewrite> {
         let label_declarations = self.label_declarations();
         let label_usages = self.label_usages();
 
-        for (line_idx, line) in self.lines.iter_mut().enumerate() {
-            if let Statement::ControlFlow(ControlFlow::Lbl(decl_label)) = line {
-                if !label_usages.contains_key(decl_label)
-                    || label_declarations[decl_label] != line_idx
-                {
-                    *line = Statement::None;
-                }
-            }
-        }
-
-        // At this moment, every label has exactly 1 + len(usages) occurrences in the program. Now,
-        // we sort and rank them.
+        // At this moment, every label has exactly 1 + len(usages) occurrences in the program, so
+        // its name costs `name.byte_len() * (1 + len(usages))` bytes. Names never get shorter as
+        // the rank goes up, so by the rearrangement inequality, ranking the labels from the most
+        // occurrences to the fewest minimizes the total.
 
         let mut usage_sorted = label_usages.iter().collect::<Vec<_>>();
-        usage_sorted.sort_by(|&a, &b| b.1.len().cmp(&a.1.len()));
+        usage_sorted.sort_by_key(|&(_, usages)| std::cmp::Reverse(1 + usages.len()));
 
-        for (rank, &(label, usages)) in usage_sorted.iter().enumerate() {
-            let new_name = label_name(rank);
+        // every label is renamed at once, so a label can take a name another one is giving up
+        let renames: BTreeMap<LabelName, (usize, LabelName)> = usage_sorted
+            .iter()
+            .enumerate()
+            .map(|(rank, &(&label, _))| (label, (rank, label_name(rank))))
+            .collect();
 
-            if let Some(&declaration_line) = label_declarations.get(label) {
-                if let Some(Statement::ControlFlow(ControlFlow::Lbl(decl_label))) =
-                    &mut self.lines.get_mut(declaration_line)
+        let mut rewrites = vec![];
+        for (line_idx, line) in self.lines.iter().enumerate() {
+            if let Statement::ControlFlow(ControlFlow::Lbl(decl_label)) = *line {
+                if !label_usages.contains_key(&decl_label)
+                    || label_declarations[&decl_label] != line_idx
                 {
-                    *decl_label = new_name;
-                } else {
-                    panic!("Incorrect output from label_declarations, please report this.");
-                }
-            } else {
-                panic!("Label used without accompanying Lbl statement.");
-            }
-
-            for i in 0..usages.len() {
-                let usage = usages[i];
-                if i != 0 && usages[i - 1] == usages[i] {
+                    rewrites.push(Rewrite::remove(
+                        line_idx,
+                        format!("unused label {decl_label}"),
+                    ));
                     continue;
                 }
 
-                match &mut self.lines.get_mut(usage) {
-                    Some(Statement::ControlFlow(ControlFlow::Goto(usage_label))) => {
-                        *usage_label = new_name;
-                    }
-
-                    Some(Statement::ControlFlow(ControlFlow::Menu(Menu {
-                        option_labels, ..
-                    }))) => {
-                        for used_label in option_labels {
-                            if label == used_label {
-                                *used_label = new_name;
-                            }
-                        }
-                    }
-
-                    // nothing else can use labels
-                    _ => {}
+                let (rank, new_name) = renames[&decl_label];
+                if decl_label != new_name {
+                    rewrites.push(Rewrite::replace(
+                        line_idx,
+                        Statement::ControlFlow(ControlFlow::Lbl(new_name)),
+                        Some(format!(
+                            "renamed label {decl_label}→{new_name} (usage rank {rank})"
+                        )),
+                    ));
                 }
+            } else if let Some(renamed) = relabeled(line, |label| renames[&label].1) {
+                rewrites.push(Rewrite::replace(line_idx, renamed, None));
             }
         }
+
+        rewrites
     }
 }
 
@@ -141,8 +169,10 @@ impl Program {
 mod tests {
     use super::*;
     use crate::label_name;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::Config;
     use std::collections::{BTreeMap, BTreeSet};
-    use test_files::{load_test_data, test_tokenizer};
+    use test_files::{load_test_data, test_tokenizer, test_version};
 
     // This is a correctness guarantee, by the pigeonhole principle and the fact that constructing a
     // LabelName performs a check on the validity of the bytes being passed.
@@ -157,6 +187,39 @@ mod tests {
         }
     }
 
+    // `optimize_label_names` relies on this to give the fewest total bytes.
+    #[test]
+    fn label_names_shortest_first() {
+        let lengths = (0..=(DICTIONARY.len() * DICTIONARY.len()))
+            .map(|rank| label_name(rank).byte_len())
+            .collect::<Vec<_>>();
+
+        assert!(lengths.is_sorted());
+        assert_eq!(lengths[DICTIONARY.len() - 1], 1);
+        assert_eq!(lengths[DICTIONARY.len()], 2);
+    }
+
+    #[test]
+    fn fewest_label_bytes() {
+        // 37 labels used once each, which sort before one label used three times; only 37 names
+        // are one byte long, so one of the labels has to get a two byte name
+        let mut lines = DICTIONARY[..37]
+            .iter()
+            .map(|&byte| format!("Lbl A{0}\nGoto A{0}", byte as char))
+            .collect::<Vec<_>>();
+        lines.push("Lbl Z9\nGoto Z9\nGoto Z9\nGoto Z9".to_string());
+        let text = lines.join("\n").replace('[', "theta");
+
+        let mut program = Program::from_text(&text, test_version!());
+        assert_eq!(program.label_name_bytes(), 2 * (37 * 2 + 4));
+
+        program.optimize_label_names();
+
+        // the label used three times gets a one byte name, and one label used once doesn't
+        assert_eq!(program.label_usages()[&label_name!('A')].len(), 3);
+        assert_eq!(program.label_name_bytes(), 4 + 36 * 2 + 2 * 2);
+    }
+
     #[test]
     fn unused_labels_eliminated() {
         let mut tokens = load_test_data("/snippets/optimize/control-flow/unused-label.txt");
@@ -183,4 +246,55 @@ mod tests {
 
         assert_eq!(program.label_usages(), expected);
     }
+
+    #[test]
+    fn delvar_chain_usages_renamed() {
+        let config = Config::from(test_version!());
+        let mut program = Program::from_text("Lbl Q\nDelVar XGoto Q", test_version!());
+        program.optimize_label_names();
+
+        let tokenizer = test_tokenizer!();
+        assert_eq!(
+            tokenizer
+                .stringify(&program.reconstruct(&config))
+                .to_string(),
+            "Lbl A\nDelVar XGoto A"
+        );
+    }
+
+    #[test]
+    fn guarded_unused_labels_replaced() {
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/guarded-label.txt");
+        let tokenizer = test_tokenizer!();
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.change_log = Some(vec![]);
+        program.optimize_label_names();
+
+        let (expected, _) = tokenizer
+            .tokenize("If A\nAns\nDisp 1\nIS>(B,2\nAns\nDisp 2\nLbl A\nGoto A")
+            .unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(test_version!())),
+            expected.collect::<Vec<_>>()
+        );
+        assert_eq!(
+            program.change_log.unwrap()[..3]
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "line 1: removed unused label T",
+                "line 3: replaced unused label Q with a no-op",
+                "line 6: replaced unused label A with a no-op",
+            ]
+        );
+    }
+
+    #[test]
+    fn label_names_keep_comment_strings() {
+        assert_comment_strings_kept("\"MAIN\nLbl QQ\n\"LOOP\nGoto QQ", |p, _| {
+            p.optimize_label_names()
+        });
+    }
 }
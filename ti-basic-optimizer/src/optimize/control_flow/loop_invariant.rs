@@ -0,0 +1,342 @@
+//! # Loop-Invariant Code Motion
+//! A store inside a loop whose value does not depend on anything the loop changes computes the
+//! same value on every iteration, so it can be computed once before the loop instead.
+//!
+//! This is deliberately narrow. Only `For(` loops with numeric literal bounds (so that the body is
+//! known to run at least once) and straight-line bodies are considered. The body may only contain
+//! stores, expressions, and a handful of output commands which cannot change variables.
+//!
+//! Those restrictions are what make it safe to work on line ranges instead of the control flow
+//! graph and its dominator tree. A body without control flow or labels is a single basic block
+//! which can only be entered from the `For(` line, so the line before the `For(` is a preheader
+//! which dominates the loop. Since the body always runs at least once, every line in it also
+//! dominates the loop exit, so a hoisted line runs on exactly the paths it ran on before, just
+//! once instead of on every iteration. The checks on each candidate store then stand in for
+//! liveness: its inputs aren't written anywhere in the body, its target is written only by it and
+//! isn't read before it in the body, and the `Ans` it leaves is always overwritten before the
+//! iteration ends.
+
+use std::collections::BTreeSet;
+use titokens::Token;
+
+use crate::analyze::is_inert_command;
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    components::{Operand, Operator, StoreTarget},
+    expression::Expression,
+    statements::{control_flow::ForLoop, ControlFlow, Statement},
+    Program, Reconstruct,
+};
+use crate::Config;
+
+fn hoist_note(line_idx: usize) -> String {
+    format!("hoisted loop-invariant line {} out of For(", line_idx + 1)
+}
+
+/// Functions which may appear in a hoisted expression.
+fn is_pure_function(kind: Token) -> bool {
+    matches!(
+        kind.into(),
+        0x12 | // round(
+        0x19 | // max(
+        0x1A | // min(
+        0xB1 | // int(
+        0xB2 | // abs(
+        0xB9 | // iPart(
+        0xBA | // fPart(
+        0xBC | // sqrt(
+        0xBE | // ln(
+        0xBF | // e^(
+        0xC0 | // log(
+        0xC1 | // 10^(
+        0xC2..=0xC7 // sin( sin^-1( cos( cos^-1( tan( tan^-1(
+    )
+}
+
+/// Functions which temporarily rebind a variable while they are evaluated.
+fn binds_variable(token: &Token) -> bool {
+    matches!(
+        (*token).into(),
+        0x22..=0x25 | 0x27 | 0x28 // solve( seq( fnInt( nDeriv( fMin( fMax(
+    )
+}
+
+impl Expression {
+    /// Collects the variables read by this expression into `inputs`. Returns false if the
+    /// expression reads anything other than real variables and literals or calls a function which
+    /// is not known to be pure.
+    fn pure_inputs(&self, inputs: &mut BTreeSet<Token>) -> bool {
+        match self {
+            Expression::Operand(Operand::NumericVarName(name)) => {
+                inputs.insert(name.0);
+
+                true
+            }
+            Expression::Operand(Operand::NumericLiteral(_)) => true,
+            Expression::Operator(Operator::Binary(binop)) => {
+                binop.left.pure_inputs(inputs) && binop.right.pure_inputs(inputs)
+            }
+            Expression::Operator(Operator::Unary(unop)) => unop.child.pure_inputs(inputs),
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                is_pure_function(call.kind)
+                    && call.arguments.iter().all(|arg| arg.pure_inputs(inputs))
+            }
+
+            _ => false,
+        }
+    }
+}
+
+impl ForLoop {
+    /// True if the loop has numeric literal bounds and is known to run at least once.
+    fn always_runs(&self) -> bool {
+        let literal = |expr: &Expression| match expr {
+            Expression::Operand(Operand::NumericLiteral(value)) => Some(*value),
+            _ => None,
+        };
+
+        let (Some(start), Some(end), Some(step)) = (
+            literal(&self.start),
+            literal(&self.end),
+            literal(&self.step()),
+        ) else {
+            return false;
+        };
+
+        if step.is_negative() {
+            start >= end
+        } else {
+            step.digits().iter().any(|&digit| digit != 0) && start <= end
+        }
+    }
+}
+
+impl Program {
+    /// Under [`Priority::Speed`](crate::optimize::Priority::Speed), moves stores of loop-invariant
+    /// values out of `For(` loops so that they are only computed once.
+    pub fn optimize_loop_invariants(&mut self, config: &Config) {
+        while let Some(&(for_idx, line_idx)) = self.loop_invariants(config).first() {
+            self.lines[for_idx..=line_idx].rotate_right(1);
+            self.explain(for_idx, || hoist_note(line_idx));
+            if let Some(separators) = self.separators.get_mut(for_idx..=line_idx) {
+                separators.rotate_right(1);
+            }
+            if let Some(original_lines) = self.original_lines.get_mut(for_idx..=line_idx) {
+                original_lines.rotate_right(1);
+            }
+            if let Some(positions) = self.original_positions.get_mut(for_idx..=line_idx) {
+                positions.rotate_right(1);
+            }
+        }
+
+        self.invalidate_analyses();
+    }
+
+    /// What [`Program::optimize_loop_invariants`] would hoist first out of each loop, as a line
+    /// moved in front of the `For(` and left empty in the body.
+    pub(crate) fn loop_invariant_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        self.loop_invariants(config)
+            .into_iter()
+            .flat_map(|(for_idx, line_idx)| {
+                let hoisted = vec![self.lines[line_idx].clone(), self.lines[for_idx].clone()];
+
+                [
+                    Rewrite::splice(for_idx, hoisted, Some(hoist_note(line_idx))),
+                    Rewrite::splice(line_idx, vec![], None),
+                ]
+            })
+            .collect()
+    }
+
+    /// Finds a line which can be hoisted out of each loop, in order of line. Returns the line of
+    /// the `For(` and the line to hoist.
+    fn loop_invariants(&self, config: &Config) -> Vec<(usize, usize)> {
+        let mut invariants = vec![];
+        let (paths, eof_abusers) = self.block_failure_paths();
+
+        for (&for_idx, &after_end) in paths.iter() {
+            let Statement::ControlFlow(ControlFlow::For(for_loop)) = &self.lines[for_idx] else {
+                continue;
+            };
+
+            if eof_abusers.contains(&for_idx)
+                || !matches!(
+                    self.lines[after_end - 1],
+                    Statement::ControlFlow(ControlFlow::End)
+                )
+            {
+                continue;
+            }
+
+            // a preheader can't be inserted between a one-line conditional and the loop it runs.
+            if self.is_guarded_single_line(for_idx) {
+                continue;
+            }
+
+            let Expression::Operand(Operand::NumericVarName(iterator)) = &for_loop.iterator else {
+                continue;
+            };
+
+            if !for_loop.always_runs() || !self.loop_variable_safe(for_idx) {
+                continue;
+            }
+
+            let body = for_idx + 1..after_end - 1;
+            if let Some(line_idx) = self.find_invariant_in(body, iterator.0, config) {
+                invariants.push((for_idx, line_idx));
+            }
+        }
+
+        invariants
+    }
+
+    fn find_invariant_in(
+        &self,
+        body: std::ops::Range<usize>,
+        iterator: Token,
+        config: &Config,
+    ) -> Option<usize> {
+        let mut written = BTreeSet::from([iterator]);
+        let mut body_tokens = vec![];
+
+        for line in &self.lines[body.clone()] {
+            match line {
+                // hoisting a line past a comment would move the comment
+                _ if config.preserve_comment_strings && line.is_comment_string() => return None,
+                Statement::None | Statement::Expression(_) => {}
+                Statement::Store(_, target) => {
+                    if let StoreTarget::NumericVar(name) | StoreTarget::NumericVarOrListName(name) =
+                        target
+                    {
+                        written.insert(name.0);
+                    }
+                }
+                Statement::Generic(generic) if is_inert_command(generic.kind) => {}
+
+                _ => return None,
+            }
+
+            let tokens = line.reconstruct(config);
+            if line.may_access_any_variable()
+                || tokens.iter().any(binds_variable)
+                || tokens.contains(&Token::OneByte(0x72))
+            {
+                // Ans
+                return None;
+            }
+
+            body_tokens.push(tokens);
+        }
+
+        let sets_ans =
+            |line: &Statement| matches!(line, Statement::Expression(_) | Statement::Store(..));
+
+        for (offset, line) in self.lines[body.clone()].iter().enumerate() {
+            // the stored value is always a number, so `->C` stores to C even if it might name a list.
+            let Statement::Store(
+                expr,
+                StoreTarget::NumericVar(target) | StoreTarget::NumericVarOrListName(target),
+            ) = line
+            else {
+                continue;
+            };
+
+            let mut inputs = BTreeSet::new();
+            if !expr.pure_inputs(&mut inputs) || !inputs.is_disjoint(&written) {
+                continue;
+            }
+
+            let stores_to_target = self.lines[body.clone()]
+                .iter()
+                .filter(|line| {
+                    matches!(
+                        line,
+                        Statement::Store(
+                            _,
+                            StoreTarget::NumericVar(name) | StoreTarget::NumericVarOrListName(name)
+                        ) if name.0 == target.0
+                    )
+                })
+                .count();
+
+            // The first iteration must not observe the value from before the loop, and Ans at the
+            // end of every iteration must not come from this line.
+            if stores_to_target != 1
+                || body_tokens[..offset]
+                    .iter()
+                    .any(|tokens| tokens.contains(&target.0))
+                || !self.lines[body.start + offset + 1..body.end]
+                    .iter()
+                    .any(sets_ans)
+            {
+                continue;
+            }
+
+            return Some(body.start + offset);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::Priority;
+    use test_files::{load_test_text, test_version};
+
+    fn config() -> Config {
+        Config {
+            priority: Priority::Speed,
+            ..Config::from(test_version!())
+        }
+    }
+
+    #[test]
+    fn invariant_store_hoisted() {
+        let mut program = Program::from_text(
+            &load_test_text("/snippets/optimize/control-flow/loop-invariant.txt"),
+            test_version!(),
+        );
+        program.optimize_loop_invariants(&config());
+
+        assert!(matches!(program.lines[0], Statement::Store(..)));
+        assert!(matches!(
+            program.lines[1],
+            Statement::ControlFlow(ControlFlow::For(_))
+        ));
+        assert!(matches!(program.lines[2], Statement::Generic(_)));
+    }
+
+    #[test]
+    fn variant_stores_kept() {
+        let mut program = Program::from_text(
+            &load_test_text("/snippets/optimize/control-flow/loop-variant.txt"),
+            test_version!(),
+        );
+        let before = program.reconstruct(&config());
+        program.optimize_loop_invariants(&config());
+
+        assert_eq!(program.reconstruct(&config()), before);
+    }
+
+    #[test]
+    fn comment_strings_kept_in_place() {
+        let text = "For(I,1,10\n\"NOTE\nA+B->C\nDisp C+I\nI->D\nEnd";
+
+        let mut program = Program::from_text(text, test_version!());
+        program.optimize_loop_invariants(&config());
+        assert!(matches!(program.lines[0], Statement::Store(..)));
+        assert!(program.lines[2].is_comment_string());
+
+        let config = Config {
+            preserve_comment_strings: true,
+            ..config()
+        };
+        let mut program = Program::from_text(text, test_version!());
+        let before = program.reconstruct(&config);
+        program.optimize_loop_invariants(&config);
+
+        assert_eq!(program.reconstruct(&config), before);
+    }
+}
@@ -1 +1,5 @@
+mod adjacent_labels;
+mod counting_loop;
+mod for_loop_paren;
 mod label_name;
+mod loop_invariant;
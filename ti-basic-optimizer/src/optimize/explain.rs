@@ -0,0 +1,44 @@
+//! # Explanations
+//! With [`Config::explain`](crate::Config::explain) set, every pass leaves a short note for each
+//! change it makes, so that the output can be checked (or learned from) one transformation at a
+//! time.
+
+use std::fmt::{Display, Formatter};
+
+use crate::parse::Program;
+
+/// One change made by an optimization pass.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeRecord {
+    /// The index of the changed line in [`Program::lines`] at the time of the change. Later passes
+    /// may move lines around, so this is only meaningful together with the records before it.
+    pub line: usize,
+    /// A short, human-readable description of the change, like `removed redundant store`.
+    pub note: String,
+}
+
+impl Display for ChangeRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.note)
+    }
+}
+
+impl Program {
+    /// Note a change to line `line` if explanations were requested. The note is only formatted
+    /// when it will be kept.
+    pub(crate) fn explain(&mut self, line: usize, note: impl FnOnce() -> String) {
+        if let Some(log) = &mut self.change_log {
+            log.push(ChangeRecord { line, note: note() });
+        }
+    }
+}
+
+/// The note for a line which was [removed](Program::remove_line), like `removed redundant store`,
+/// or `replaced redundant store with a no-op` if a one-line conditional guarded it.
+pub(crate) fn removal_note(what: &str, replaced: bool) -> String {
+    if replaced {
+        format!("replaced {what} with a no-op")
+    } else {
+        format!("removed {what}")
+    }
+}
@@ -0,0 +1,199 @@
+//! # Double Negation
+//! Negating a number twice gives the same number back, so `~~X` is `X`.
+//!
+//! `not(` is different: it turns every nonzero value into 0 and zero into 1, so `not(not(X))` is
+//! 1 for every nonzero `X`, not `X` itself. It can only be replaced with `X` when `X` is already
+//! 0 or 1, like the result of a comparison or of `and`; otherwise it becomes `X≠0`, which is one
+//! function call cheaper.
+
+use tifloats::{tifloat, Float};
+use titokens::Token;
+
+use crate::optimize::rewrite::Rewrite;
+use crate::optimize::Priority;
+use crate::parse::{
+    components::{BinOp, FunctionCall, Operator, UnOp},
+    expression::Expression,
+    Program, Reconstruct,
+};
+use crate::Config;
+
+/// True if `expr` is always 0 or 1 (elementwise, for lists).
+fn is_boolean(expr: &Expression) -> bool {
+    match expr {
+        Expression::Operator(Operator::Binary(binop)) => matches!(
+            binop.kind,
+            Token::OneByte(0x6A..=0x6F) // = < > ≤ ≥ ≠
+            | Token::OneByte(0x40) // and
+            | Token::OneByte(0x3C..=0x3D) // or xor
+        ),
+        Expression::Operator(Operator::FunctionCall(call)) => call.kind == Token::OneByte(0xB8),
+
+        _ => false,
+    }
+}
+
+/// The number of bytes `expr` takes, with its parentheses.
+fn size(expr: &Expression, config: &Config) -> usize {
+    expr.reconstruct(config).iter().map(Token::byte_len).sum()
+}
+
+/// The argument of `not(`, if `expr` is a call to it.
+fn not_argument(expr: &Expression) -> Option<&Expression> {
+    match expr {
+        Expression::Operator(Operator::FunctionCall(FunctionCall { kind, arguments }))
+            if *kind == Token::OneByte(0xB8) && arguments.len() == 1 =>
+        {
+            arguments.first()
+        }
+
+        _ => None,
+    }
+}
+
+impl Expression {
+    /// Simplifies every `~~X` in this expression to `X`, and every `not(not(X))` to `X` if `X` is
+    /// 0 or 1 and to `X≠0` otherwise. Returns the number of simplifications made.
+    ///
+    /// `X≠0` may need parentheses which `not(not(X` doesn't, so with [`Priority::Size`] it is
+    /// only used if it is no larger even with them.
+    pub fn simplify_double_negation(&mut self, config: &Config) -> u16 {
+        let mut simplified = 0;
+
+        self.walk_mut(&mut |expr| {
+            if let Expression::Operator(Operator::Unary(UnOp {
+                kind: Token::OneByte(0xB0),
+                child,
+            })) = expr
+            {
+                if let Expression::Operator(Operator::Unary(UnOp {
+                    kind: Token::OneByte(0xB0),
+                    child: inner,
+                })) = child.as_ref()
+                {
+                    *expr = inner.as_ref().clone();
+                    simplified += 1;
+                }
+
+                return;
+            }
+
+            let Some(inner) = not_argument(expr).and_then(not_argument) else {
+                return;
+            };
+
+            if is_boolean(inner) {
+                *expr = inner.clone();
+                simplified += 1;
+            }
+        });
+
+        // only after every `not(not(` around a boolean is gone, so that `not(not(not(X` is
+        // `not(X` rather than `not(X≠0`
+        self.walk_mut(&mut |expr| {
+            let Some(inner) = not_argument(expr).and_then(not_argument) else {
+                return;
+            };
+
+            let nonzero = Expression::Operator(Operator::Binary(BinOp {
+                kind: Token::OneByte(0x6F), // ≠
+                left: Box::new(inner.clone()),
+                right: Box::new(Expression::Operand(tifloat!(0 * 10 ^ 0).into())),
+            }));
+
+            // the two bytes are for the parentheses `≠` may need where `not(` doesn't
+            if config.priority == Priority::Size && size(&nonzero, config) + 2 > size(expr, config)
+            {
+                return;
+            }
+
+            *expr = nonzero;
+            simplified += 1;
+        });
+
+        simplified
+    }
+}
+
+impl Program {
+    /// Simplifies double negations everywhere in the program.
+    ///
+    /// See also: [`Expression::simplify_double_negation`]
+    pub fn optimize_double_negations(&mut self, config: &Config) {
+        self.apply_rewrites(self.double_negation_rewrites(config));
+    }
+
+    pub(crate) fn double_negation_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for (idx, line) in self.statements() {
+            let mut line = line.clone();
+            let simplified: u16 = line
+                .expressions_mut()
+                .into_iter()
+                .map(|expr| expr.simplify_double_negation(config))
+                .sum();
+
+            if simplified > 0 {
+                rewrites.push(Rewrite::replace(
+                    idx,
+                    line,
+                    Some(format!("simplified {simplified} double negation(s)")),
+                ));
+            }
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parse;
+    use test_files::{test_tokenizer, test_version};
+
+    fn simplify(text: &str, priority: Priority) -> String {
+        let tokenizer = test_tokenizer!();
+        let config = Config {
+            priority,
+            ..Config::from(test_version!())
+        };
+
+        let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+        let mut expr = Expression::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+        expr.simplify_double_negation(&config);
+
+        tokenizer.stringify(&expr.reconstruct(&config)).to_string()
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(simplify("~~A", Priority::Neutral), "A");
+        assert_eq!(simplify("~~~A", Priority::Neutral), "~A");
+        assert_eq!(simplify("~~~~A", Priority::Neutral), "A");
+        assert_eq!(simplify("2*~~(A+B)", Priority::Size), "2(A+B)");
+        assert_eq!(simplify("~~L1", Priority::Size), "L1");
+    }
+
+    #[test]
+    fn not() {
+        // not(not(5)) is 1, not 5
+        assert_eq!(simplify("not(not(A))", Priority::Neutral), "A!=0");
+        assert_eq!(simplify("not(not(A))", Priority::Speed), "A!=0");
+        assert_eq!(simplify("not(not(A))", Priority::Size), "A!=0");
+        assert_eq!(simplify("not(not(A+B))", Priority::Size), "A+B!=0");
+        assert_eq!(simplify("2not(not(A))", Priority::Size), "2(A!=0)");
+
+        assert_eq!(simplify("not(not(A=B))", Priority::Size), "A=B");
+        assert_eq!(simplify("not(not(A and B))", Priority::Size), "A and B");
+        assert_eq!(simplify("not(not(not(A)))", Priority::Size), "not(A)");
+        assert_eq!(simplify("not(not(not(A)))", Priority::Speed), "not(A)");
+        assert_eq!(simplify("not(not(not(not(A))))", Priority::Size), "A!=0");
+
+        assert_eq!(simplify("not(A)", Priority::Neutral), "not(A)");
+        assert_eq!(simplify("~not(A)", Priority::Neutral), "~not(A)");
+    }
+}
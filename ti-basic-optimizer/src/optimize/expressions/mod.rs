@@ -1 +1,3 @@
+mod double_negation;
 mod parenthesis_optimization;
+mod string_concatenation;
@@ -6,7 +6,9 @@ use std::mem;
 use titokens::Token;
 
 use crate::parse::components::{EquationIndex, ListIndex, MatrixIndex, Rand, StoreTarget};
-use crate::parse::statements::{ControlFlow, DelVarChain, Generic, Statement};
+use crate::parse::statements::{
+    ControlFlow, DelVarChain, Generic, PlotArgument, StatPlot, Statement,
+};
 use crate::parse::{
     components::{Operand, Operator},
     expression::Expression,
@@ -49,7 +51,7 @@ impl Expression {
                     }
                 }
 
-                if let Some(new_kind) = binop.opposite() {
+                if let Some(new_kind) = binop.opposite().filter(|_| binop.operands_commute()) {
                     let mut left = binop.left.optimize_parentheses();
 
                     if let Expression::Operator(Operator::Binary(left_binop)) = binop.left.as_ref()
@@ -103,6 +105,14 @@ impl Expression {
                 | Operand::Rand(Rand { count: Some(index) }),
             ) => 1 + index.optimize_parentheses(),
 
+            Expression::Operand(Operand::Piecewise(piecewise)) => {
+                if let Some(last) = piecewise.last_argument_mut() {
+                    1 + last.optimize_parentheses()
+                } else {
+                    1
+                }
+            }
+
             Expression::Operand(Operand::StringLiteral(_) | Operand::ListLiteral(_)) => 1,
 
             _ => 0,
@@ -162,11 +172,18 @@ impl Statement {
             }
 
             Statement::Generic(Generic { arguments, .. }) => {
+                // every other argument is followed by a comma, so only the last can lose parentheses
                 if let Some(last) = arguments.last_mut() {
                     last.optimize_parentheses();
                 }
             }
 
+            Statement::StatPlot(StatPlot { arguments, .. }) => {
+                if let Some(PlotArgument::Expression(last)) = arguments.last_mut() {
+                    last.optimize_parentheses();
+                }
+            }
+
             Statement::ControlFlow(control_flow) => {
                 match control_flow {
                     ControlFlow::If(expr)
@@ -216,7 +233,9 @@ impl Statement {
 mod tests {
     use super::*;
     use crate::parse::{Parse, Reconstruct};
-    use test_files::{load_test_data, test_version};
+    use crate::Config;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+    use titokens::Tokens;
 
     #[test]
     fn parenthesis_optimization() {
@@ -243,6 +262,94 @@ mod tests {
         }
     }
 
+    #[test]
+    fn nested_function_calls() {
+        let tokenizer = test_tokenizer!();
+        let config = test_version!().into();
+
+        for (text, expected_savings, expected) in [
+            ("round(min(A,B),2)", 1, "round(min(A,B),2"),
+            ("round(min(A,B),2", 1, "round(min(A,B),2"),
+            ("min(A,round(B,2))", 2, "min(A,round(B,2"),
+            ("max(1,A+sin(B))", 2, "max(1,A+sin(B"),
+            ("sum(cumSum(randInt(1,6,N)))", 3, "sum(cumSum(randInt(1,6,N"),
+            ("int(2max(A,B))", 2, "int(2max(A,B"),
+            ("abs(min(A,B))+1", 2, "1+abs(min(A,B"),
+            ("abs(min(A,B))^2", 0, "abs(min(A,B))^2"),
+        ] {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let mut expr = Expression::parse(tokens.next().unwrap(), &mut tokens)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(expr.optimize_parentheses(), expected_savings, "{text}");
+
+            let reconstructed = expr.reconstruct(&config);
+            let mut optimized = reconstructed.clone();
+            Expression::strip_closing_parenthesis(&mut optimized);
+            assert_eq!(
+                (reconstructed.len() - optimized.len()) as u16,
+                expected_savings,
+                "{text}"
+            );
+            assert_eq!(tokenizer.stringify(&optimized).to_string(), expected);
+
+            // the calculator reads the stripped expression the same way
+            let mut tokens = Tokens::from_vec(optimized.clone(), None);
+            let reparsed = Expression::parse(tokens.next().unwrap(), &mut tokens)
+                .unwrap()
+                .unwrap();
+            assert_eq!(reparsed.reconstruct(&config), reconstructed, "{text}");
+        }
+    }
+
+    #[test]
+    fn generic_commands() {
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+        let kept = Config {
+            strip_trailing_parens: false,
+            ..config.clone()
+        };
+
+        for (text, count, stripped, unstripped) in [
+            ("Line(A,B,C,D", 4, "Line(A,B,C,D", "Line(A,B,C,D)"),
+            ("Line(A,B,C,D)", 4, "Line(A,B,C,D", "Line(A,B,C,D)"),
+            (
+                "Pxl-On(A,min(B,C))",
+                2,
+                "Pxl-On(A,min(B,C",
+                "Pxl-On(A,min(B,C))",
+            ),
+            (
+                "Pxl-On(A,min(B,C",
+                2,
+                "Pxl-On(A,min(B,C",
+                "Pxl-On(A,min(B,C))",
+            ),
+        ] {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let mut statement = Statement::parse(tokens.next().unwrap(), &mut tokens)
+                .unwrap()
+                .unwrap();
+            assert_eq!(tokens.peek(), None, "{text}");
+
+            let Statement::Generic(Generic { arguments, .. }) = &statement else {
+                panic!("{text} is not a command");
+            };
+            assert_eq!(arguments.len(), count, "{text}");
+
+            statement.optimize_parentheses();
+            let reconstruct = |config| {
+                tokenizer
+                    .stringify(&statement.reconstruct(config))
+                    .to_string()
+            };
+            assert_eq!(reconstruct(&config), stripped, "{text}");
+            assert_eq!(reconstruct(&kept), unstripped, "{text}");
+        }
+    }
+
     #[test]
     fn strip_closing_parentheses() {
         for case in ["1.txt", "2.txt", "3.txt", "4.txt", "5.txt", "6.txt"] {
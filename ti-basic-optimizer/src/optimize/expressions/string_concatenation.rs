@@ -0,0 +1,252 @@
+//! # String Concatenation Folding
+//! `"AB"+"CD"` can be written as `"ABCD"`, which saves three tokens and an addition.
+//!
+//! Where the target has them, the strings `"0"`, `"1"`, and `"2"` are also written as `LEFT`,
+//! `CENTER`, and `RIGHT`, which are one token each.
+
+use titokens::{Token, Tokenizer};
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    components::{Operand, Operator},
+    expression::Expression,
+    Program,
+};
+use crate::Config;
+
+impl Expression {
+    /// Folds every concatenation of two string literals in this expression into one literal.
+    /// Returns the number of concatenations folded.
+    pub fn fold_string_concatenation(&mut self) -> u16 {
+        let mut folded = 0;
+
+        self.walk_mut(&mut |expr| {
+            let Expression::Operator(Operator::Binary(binop)) = expr else {
+                return;
+            };
+
+            if binop.kind != Token::OneByte(0x70) {
+                return;
+            }
+
+            if let (
+                Expression::Operand(Operand::StringLiteral(left)),
+                Expression::Operand(Operand::StringLiteral(right)),
+            ) = (binop.left.as_ref(), binop.right.as_ref())
+            {
+                *expr = Expression::Operand(Operand::StringLiteral(left.concat(right)));
+                folded += 1;
+            }
+        });
+
+        folded
+    }
+
+    /// Writes every string literal in this expression which equals `LEFT`, `CENTER`, or `RIGHT`
+    /// as that token, if `tokenizer` has it. Arguments of `sub(` and `inString(` are left alone,
+    /// since there the string is text to search rather than a screen alignment. Returns the number
+    /// of strings rewritten.
+    pub fn use_alignment_constants(&mut self, tokenizer: &Tokenizer) -> u16 {
+        let mut rewritten = 0;
+        let mut rewrite = |expr: &mut Expression| {
+            if let Expression::Operand(Operand::StringLiteral(string)) = expr {
+                if string
+                    .alignment_constant()
+                    .is_some_and(|constant| tokenizer.is_available(constant))
+                    && string.write_as_alignment_constant()
+                {
+                    rewritten += 1;
+                }
+            }
+        };
+
+        // children are visited before their parents, so each string is rewritten from its parent
+        self.walk_mut(&mut |expr| match expr {
+            Expression::Operator(Operator::FunctionCall(call))
+                if matches!(
+                    call.kind,
+                    Token::TwoByte(0xBB, 0x0C) | Token::TwoByte(0xBB, 0x0F) // sub(, inString(
+                ) => {}
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                call.arguments.iter_mut().for_each(&mut rewrite);
+            }
+            Expression::Operator(Operator::Binary(binop)) => {
+                rewrite(&mut binop.left);
+                rewrite(&mut binop.right);
+            }
+            Expression::Operator(Operator::Unary(unop)) => rewrite(&mut unop.child),
+
+            _ => {}
+        });
+        rewrite(self);
+
+        rewritten
+    }
+}
+
+impl Program {
+    /// Folds concatenations of string literals everywhere in the program, then writes the strings
+    /// `"0"`, `"1"`, and `"2"` as `LEFT`, `CENTER`, and `RIGHT` if [`Config::mrov`] has them.
+    /// Comment strings are left as they are when [`Config::preserve_comment_strings`] is set.
+    ///
+    /// See also: [`Expression::fold_string_concatenation`], [`Expression::use_alignment_constants`]
+    pub fn optimize_string_concatenation(&mut self, config: &Config) {
+        self.apply_rewrites(self.string_concatenation_rewrites(config));
+    }
+
+    pub(crate) fn string_concatenation_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        let tokenizer = config.tokenizer();
+        let mut rewrites = vec![];
+
+        for (idx, line) in self.statements() {
+            let mut line = line.clone();
+            let folded: u16 = line
+                .expressions_mut()
+                .into_iter()
+                .map(Expression::fold_string_concatenation)
+                .sum();
+
+            let constants: u16 = if config.preserve_comment_strings && line.is_comment_string() {
+                0
+            } else {
+                line.expressions_mut()
+                    .into_iter()
+                    .map(|expr| expr.use_alignment_constants(&tokenizer))
+                    .sum()
+            };
+
+            let mut notes = vec![];
+            if folded > 0 {
+                notes.push(format!("folded {folded} string concatenation(s)"));
+            }
+            if constants > 0 {
+                notes.push(format!(
+                    "wrote {constants} string(s) as LEFT, CENTER, or RIGHT"
+                ));
+            }
+
+            if !notes.is_empty() {
+                rewrites.push(Rewrite::replace(idx, line, Some(notes.join("; "))));
+            }
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{components::TIString, Parse, Reconstruct};
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    fn fold(text: &str) -> Vec<Token> {
+        let (mut tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+        let mut expr = Expression::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+        expr.fold_string_concatenation();
+
+        expr.reconstruct(&titokens::version::LATEST_MONO.clone().into())
+    }
+
+    fn tokenize(text: &str) -> Vec<Token> {
+        test_tokenizer!().tokenize(text).unwrap().0.collect()
+    }
+
+    #[test]
+    fn literals_folded() {
+        assert_eq!(fold("\"AB\"+\"CD\""), tokenize("\"ABCD\""));
+        assert_eq!(fold("\"A\"+\"B\"+\"C\""), tokenize("\"ABC\""));
+        assert_eq!(fold("\"A\"+(\"B\"+\"C\")"), tokenize("\"ABC\""));
+        assert_eq!(fold("\"A\"+\"B\"+Str1"), tokenize("\"AB\"+Str1"));
+        assert_eq!(
+            fold("length(\"sin(\"+\"2\")"),
+            tokenize("length(\"sin(2\")")
+        );
+    }
+
+    #[test]
+    fn non_literals_kept() {
+        assert_eq!(fold("Str1+\"A\"+\"B\""), tokenize("Str1+\"A\"+\"B\""));
+    }
+
+    #[test]
+    fn alignment_constants() {
+        let (mut tokens, _) = test_tokenizer!().tokenize("LEFT+\"1\"").unwrap();
+        let mut expr = Expression::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+        expr.fold_string_concatenation();
+
+        let Expression::Operand(Operand::StringLiteral(string)) = &expr else {
+            panic!("LEFT+\"1\" should fold to a literal");
+        };
+        assert_eq!(string.data(), tokenize("01"));
+
+        // written as read, whatever the target
+        let center = TIString::new(tokenize("1"));
+        assert_eq!(
+            center.reconstruct(&test_version!().into()),
+            tokenize("\"1\"")
+        );
+        let config = Config::from(test_version!());
+        let program = Program::from_text("Disp LEFT,\"1\"", test_version!());
+        assert_eq!(program.reconstruct(&config), tokenize("Disp LEFT,\"1"));
+
+        let preserve = Config {
+            preserve_comment_strings: true,
+            ..config.clone()
+        };
+        for (text, expected) in [
+            ("Disp \"1\",\"A\"", "Disp CENTER,\"A"),
+            ("\"0\"+\"2\"->Str1", "\"02->Str1"),
+            ("Disp sub(\"012\",1,1)=\"0\"", "Disp sub(\"012\",1,1)=LEFT"),
+            ("Disp inString(Str1,\"2\")", "Disp inString(Str1,\"2"),
+            ("\"1\"", "\"1\""),
+        ] {
+            let mut program = Program::from_text(text, test_version!());
+            program.optimize_string_concatenation(&preserve);
+            assert_eq!(program.reconstruct(&preserve), tokenize(expected), "{text}");
+        }
+
+        let mut program = Program::from_text("Disp \"2\"", test_version!());
+        let mono = Config::from(titokens::version::LATEST_MONO.clone());
+        program.optimize_string_concatenation(&mono);
+        assert_eq!(program.reconstruct(&mono), tokenize("Disp \"2"));
+    }
+
+    #[test]
+    fn logged() {
+        let config = Config::from(test_version!());
+        let mut program = Program::from_text("Disp \"A\"+\"B\",\"1\"", test_version!());
+        program.change_log = Some(vec![]);
+        program.optimize_string_concatenation(&config);
+
+        assert_eq!(
+            program
+                .change_log
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "line 1: folded 1 string concatenation(s); wrote 1 string(s) as LEFT, CENTER, or RIGHT"
+            ]
+        );
+    }
+
+    #[test]
+    fn program() {
+        let mut tokens = load_test_data("/snippets/optimize/strings/concatenation.txt");
+        let tokenizer = test_tokenizer!();
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_string_concatenation(&test_version!().into());
+
+        let expected = load_test_data("/snippets/optimize/strings/concatenation-folded.txt");
+        assert_eq!(
+            program.reconstruct(&test_version!().into()),
+            expected.collect::<Vec<_>>()
+        );
+    }
+}
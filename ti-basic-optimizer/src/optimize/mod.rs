@@ -1,10 +1,20 @@
-use crate::parse::Program;
+use crate::parse::{statements::Statement, Program, Reconstruct};
 use crate::Config;
 
 mod control_flow;
+mod explain;
 mod expressions;
+mod opportunities;
+mod rewrite;
+mod statements;
 mod strategies;
 
+pub use explain::ChangeRecord;
+pub use opportunities::OptimizationOpportunity;
+use rewrite::Rewrite;
+pub(crate) use strategies::write_digits;
+pub use strategies::{CostModel, DefaultCostModel, FloatExt, MathConstantKind, StatementCandidate};
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub enum Priority {
     /// Provides a reasonable mix of both speed and size optimizations.
@@ -16,12 +26,396 @@ pub enum Priority {
     Size,
 }
 
+/// An optimization pass, as run by [`Program::optimize`].
+type Pass = fn(&mut Program, &Config);
+
+/// The analysis behind a [`Pass`], which finds the changes the pass would make without making
+/// them. Passes which repeat until nothing changes are previewed by their first round.
+type Detect = fn(&Program, &Config) -> Vec<Rewrite>;
+
 impl Program {
-    pub fn optimize(&mut self, _config: &Config) {
-        self.optimize_label_names();
+    /// The passes enabled by `config`, with their names and analyses, in the order they should
+    /// run.
+    fn passes(config: &Config) -> Vec<(&'static str, Pass, Detect)> {
+        let mut passes: Vec<(&'static str, Pass, Detect)> = vec![];
+
+        if config.rewrite_counting_loops {
+            passes.push((
+                "counting loops",
+                |p, _| p.optimize_counting_loops(),
+                |p, _| p.counting_loop_rewrites(),
+            ));
+        }
+
+        passes.push((
+            "adjacent labels",
+            |p, _| p.optimize_adjacent_labels(),
+            |p, _| p.adjacent_label_rewrites(),
+        ));
+        passes.push((
+            "label names",
+            |p, _| p.optimize_label_names(),
+            |p, _| p.label_name_rewrites(),
+        ));
+        passes.push((
+            "mode settings",
+            |p, _| p.optimize_mode_settings(),
+            |p, _| p.mode_setting_rewrites(),
+        ));
+        passes.push((
+            "redundant stores",
+            |p, c| p.optimize_redundant_stores(c),
+            |p, c| p.redundant_store_rewrites(c),
+        ));
+        passes.push((
+            "list element stores",
+            |p, c| p.optimize_list_element_stores(c),
+            |p, c| p.list_element_store_rewrites(c),
+        ));
+
+        if config.deduplicate_screen_commands {
+            passes.push((
+                "screen commands",
+                |p, c| p.optimize_screen_commands(c),
+                |p, c| p.screen_command_rewrites(c),
+            ));
+        }
+
+        if config.priority == Priority::Speed {
+            passes.push((
+                "loop invariants",
+                |p, c| p.optimize_loop_invariants(c),
+                |p, c| p.loop_invariant_rewrites(c),
+            ));
+            passes.push((
+                "list literal stores",
+                |p, c| p.optimize_list_literal_stores(c),
+                |p, c| p.list_literal_store_rewrites(c),
+            ));
+        }
+
+        if config.priority != Priority::Size {
+            passes.push((
+                "for loop parentheses",
+                |p, _| p.optimize_for_loop_parentheses(),
+                |p, _| p.for_loop_paren_rewrites(),
+            ));
+        }
+
+        passes.push((
+            "string accumulations",
+            |p, _| p.optimize_string_accumulations(),
+            |p, _| p.string_accumulation_rewrites(),
+        ));
+        passes.push((
+            "string concatenation",
+            |p, c| p.optimize_string_concatenation(c),
+            |p, c| p.string_concatenation_rewrites(c),
+        ));
+        passes.push((
+            "double negations",
+            |p, c| p.optimize_double_negations(c),
+            |p, c| p.double_negation_rewrites(c),
+        ));
+        passes.push((
+            "parentheses",
+            |p, c| p.optimize_line_parentheses(c),
+            |p, c| p.line_parenthesis_rewrites(c),
+        ));
+
+        passes
+    }
+
+    /// Removes line `idx`. If a one-line conditional guards it (see
+    /// [`Program::is_guarded_single_line`]), it is replaced with [`Statement::safe_noop`] instead,
+    /// so that the conditional doesn't start guarding the line after it, and `true` is returned so
+    /// that the pass can say so in its explanation. Passes which delete lines should do it through
+    /// this.
+    ///
+    /// The no-op is `Ans`, so if it is the last line the program runs, the home screen shows the
+    /// value of `Ans` instead of `Done`.
+    ///
+    /// Comment strings are never removed (see [`Config::preserve_comment_strings`]); passes only
+    /// remove the kinds of lines they look for, and a comment string is never one of them.
+    fn remove_line(&mut self, idx: usize) -> bool {
+        debug_assert!(
+            !self.lines[idx].is_comment_string(),
+            "a pass tried to remove a comment string"
+        );
+
+        let guarded = self.is_guarded_single_line(idx);
+        self.lines[idx] = if guarded {
+            Statement::safe_noop()
+        } else {
+            Statement::None
+        };
+
+        guarded
+    }
+
+    /// Replaces line `idx` with `lines`, keeping [`Program::separators`],
+    /// [`Program::original_lines`], and [`Program::original_positions`] in step. Each new line
+    /// ends with the separator of the line it replaces and reports problems at its position. Only
+    /// the first keeps its original tokens, so the rest show up in [`Program::changed_lines`].
+    fn splice_line(&mut self, idx: usize, lines: Vec<Statement>) {
+        let count = lines.len();
+        self.lines.splice(idx..=idx, lines);
+
+        if let Some(&separator) = self.separators.get(idx) {
+            self.separators
+                .splice(idx..=idx, std::iter::repeat_n(separator, count));
+        }
+        if let Some(original) = self.original_lines.get(idx).cloned() {
+            self.original_lines.splice(
+                idx..=idx,
+                std::iter::once(original).chain(std::iter::repeat_n(vec![], count - 1)),
+            );
+        }
+        if let Some(&position) = self.original_positions.get(idx) {
+            self.original_positions
+                .splice(idx..=idx, std::iter::repeat_n(position, count));
+        }
+    }
+
+    fn optimize_line_parentheses(&mut self, config: &Config) {
+        self.apply_rewrites(self.line_parenthesis_rewrites(config));
+    }
+
+    fn line_parenthesis_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for (idx, line) in self.statements() {
+            let mut optimized = line.clone();
+            optimized.optimize_parentheses();
+
+            if optimized.reconstruct(config) != line.reconstruct(config) {
+                rewrites.push(Rewrite::replace(
+                    idx,
+                    optimized,
+                    Some("removed unnecessary parentheses".to_string()),
+                ));
+            }
+        }
+
+        rewrites
+    }
+
+    /// Run every enabled optimization pass. With [`Config::explain`], returns a note for each
+    /// change that was made, in the order the changes were made; otherwise returns nothing.
+    pub fn optimize(&mut self, config: &Config) -> Vec<ChangeRecord> {
+        self.change_log = config.explain.then(Vec::new);
+
+        // Blocks without an `End` run until the end of the program. Passes must not close them or
+        // leave new ones open; moving and removing lines is fine as long as the `End`s stay put.
+        let eof_abusers = self.block_failure_paths().1.len();
+
+        for (_, pass, _) in Self::passes(config) {
+            pass(self, config);
+        }
+
+        debug_assert_eq!(
+            self.block_failure_paths().1.len(),
+            eof_abusers,
+            "an optimization changed which blocks run until the end of the program"
+        );
+
+        self.change_log.take().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::statements::{ControlFlow, Statement};
+    use test_files::{
+        assert_idempotent, load_test_data, load_test_programs, test_tokenizer, test_version,
+    };
+    use titokens::Token;
+
+    #[test]
+    fn eof_abuse() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/optimize/control-flow/eof-abuse.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        let config = Config {
+            priority: Priority::Speed,
+            deduplicate_screen_commands: true,
+            rewrite_counting_loops: true,
+            ..Config::from(test_version!())
+        };
+        program.optimize(&config);
+
+        let (expected, _) = tokenizer
+            .tokenize("0->K\nClrHome\n1->I\nFor(I,1,5\nOutput(1,1,I\nEnd\n5->B\nFor(J,1,3\nDisp J\nB->C\nEnd\nWhile 1\ngetKey->K\nIf K\nDisp K")
+            .unwrap();
+        assert_eq!(program.reconstruct(&config), expected.collect::<Vec<_>>());
+
+        let (_, eof_abusers) = program.block_failure_paths();
+        assert_eq!(eof_abusers.len(), 1);
+        assert!(matches!(
+            program.lines[*eof_abusers.first().unwrap()],
+            Statement::ControlFlow(ControlFlow::While(_))
+        ));
+    }
+
+    /// Runs `pass` on `text` with [`Config::preserve_comment_strings`] set, and checks that the
+    /// pass changed something but kept every comment string, exactly as it was read and in step
+    /// with [`Program::original_lines`].
+    pub(crate) fn assert_comment_strings_kept(text: &str, pass: Pass) {
+        let config = Config {
+            preserve_comment_strings: true,
+            priority: Priority::Speed,
+            deduplicate_screen_commands: true,
+            rewrite_counting_loops: true,
+            ..Config::from(test_version!())
+        };
+
+        let mut program = Program::from_text(text, test_version!());
+        let comments = |program: &Program| {
+            (0..program.lines.len())
+                .filter(|&idx| program.lines[idx].is_comment_string())
+                .collect::<Vec<_>>()
+        };
+        let count = comments(&program).len();
+        assert!(count > 0, "{text:?} has no comment strings");
+
+        let before = program.reconstruct(&config);
+        pass(&mut program, &config);
+        assert_ne!(
+            program.reconstruct(&config),
+            before,
+            "{text:?} was not changed"
+        );
+
+        assert_eq!(comments(&program).len(), count, "{text:?}");
+        for idx in comments(&program) {
+            assert_eq!(
+                program.lines[idx].reconstruct(&config),
+                program.original_lines[idx],
+                "{text:?}, line {idx}"
+            );
+        }
+    }
+
+    #[test]
+    fn remove_line() {
+        let mut program = Program::from_text("If A\nDisp 1\nDisp 2", test_version!());
+
+        assert!(program.remove_line(1));
+        assert_eq!(program.lines[1], Statement::safe_noop());
+        assert!(!program.remove_line(2));
+        assert_eq!(program.lines[2], Statement::None);
+    }
+
+    /// Passes which change a line must discard the analyses of the program as it was.
+    #[test]
+    fn passes_invalidate_analyses() {
+        let config = Config {
+            priority: Priority::Speed,
+            deduplicate_screen_commands: true,
+            rewrite_counting_loops: true,
+            ..Config::from(test_version!())
+        };
+
+        // the corpus doesn't exercise every pass
+        let snippet = Program::from_text(
+            "Lbl A\nLbl B\nGoto B\nDisp \"A\"+\"B\"\n~~C->D\nFor(I,1,5\nIf (E)\nDisp 1\nEnd",
+            test_version!(),
+        );
+        let programs = load_test_programs()
+            .into_iter()
+            .filter_map(|(path, mut tokens)| {
+                Some((path, Program::try_from_tokens(&mut tokens).ok()?))
+            })
+            .chain([("snippet".to_string(), snippet)]);
+
+        for (path, original) in programs {
+            for (name, pass, _) in Program::passes(&config) {
+                let mut program = original.clone();
+                if program.control_flow_lookup().is_err() {
+                    continue;
+                }
+
+                pass(&mut program, &config);
+                assert!(
+                    program.lines == original.lines || program.control_flow_lookup.get().is_none(),
+                    "{path}: {name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn priority() {
+        let tokenizer = test_tokenizer!();
+        let optimized = |priority| {
+            let mut tokens = load_test_data("/snippets/optimize/control-flow/for-loop-paren.txt");
+            let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+            let config = Config {
+                priority,
+                ..Config::from(test_version!())
+            };
+            program.optimize(&config);
+
+            program.reconstruct(&config)
+        };
+
+        let closed = Token::OneByte(0x11);
+        assert!(optimized(Priority::Neutral).contains(&closed));
+        assert!(optimized(Priority::Speed).contains(&closed));
+        assert!(!optimized(Priority::Size).contains(&closed));
+    }
+
+    #[test]
+    fn explain() {
+        let text = "5->A\n5->A\n0->B\nDisp \"A\"+\"B\"\nLbl Q\nGoto Q";
+        let config = Config {
+            explain: true,
+            ..Config::from(test_version!())
+        };
+
+        let mut program = Program::from_text(text, test_version!());
+        let changes = program
+            .optimize(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                "line 5: renamed label Q→A (usage rank 0)",
+                "line 2: removed redundant store",
+                "line 4: folded 1 string concatenation(s)",
+            ]
+        );
+
+        let mut program = Program::from_text(text, test_version!());
+        assert!(program.optimize(&Config::from(test_version!())).is_empty());
+    }
+
+    /// Every pass should reach a fixed point in one run. Passes are checked one at a time on each
+    /// program in the corpus, with every pass enabled.
+    #[test]
+    fn passes_are_idempotent() {
+        let config = Config {
+            priority: Priority::Speed,
+            deduplicate_screen_commands: true,
+            rewrite_counting_loops: true,
+            ..Config::from(test_version!())
+        };
+
+        for (path, mut tokens) in load_test_programs() {
+            let Ok(program) = Program::try_from_tokens(&mut tokens) else {
+                continue;
+            };
 
-        for statement in self.lines.iter_mut() {
-            statement.optimize_parentheses();
+            for (name, pass, _) in Program::passes(&config) {
+                assert_idempotent(
+                    |program| pass(program, &config),
+                    &program,
+                    format!("{path}: {name}"),
+                );
+            }
         }
     }
 }
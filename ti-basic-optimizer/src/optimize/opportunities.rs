@@ -0,0 +1,208 @@
+//! # Opportunities
+//! Lists what [`Program::optimize`] would change without changing anything, so that each change
+//! can be reviewed, and accepted or rejected, on its own.
+
+use crate::optimize::explain::removal_note;
+use crate::optimize::rewrite::{Change, Rewrite};
+use crate::optimize::ChangeRecord;
+use crate::parse::{statements::Statement, Program, Reconstruct};
+use crate::Config;
+
+/// What one optimization pass would do to a program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimizationOpportunity {
+    /// The name of the pass, like `redundant stores`.
+    pub pass: &'static str,
+    /// Each change the pass would make, with its line in the unoptimized program.
+    pub changes: Vec<ChangeRecord>,
+    /// The change in the program's size in bytes; negative if it gets smaller.
+    pub size_delta: isize,
+    /// The estimated change in clock cycles from running each changed line once; negative if it
+    /// gets faster. Only passes with a timing model in [`Config::cost_model`] estimate this.
+    pub speed_delta: Option<i64>,
+}
+
+impl OptimizationOpportunity {
+    /// The affected lines, in order and without repeats.
+    pub fn lines(&self) -> Vec<usize> {
+        let mut lines = self
+            .changes
+            .iter()
+            .map(|change| change.line)
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.dedup();
+
+        lines
+    }
+}
+
+/// The size of `line` in bytes, counting the separator after it.
+fn size(line: &Statement, config: &Config) -> isize {
+    match line {
+        Statement::None | Statement::Fiction(_) => 0,
+        _ => {
+            line.reconstruct(config)
+                .iter()
+                .map(|token| token.byte_len() as isize)
+                .sum::<isize>()
+                + 1
+        }
+    }
+}
+
+impl Program {
+    /// For each pass enabled by `config` which would change this program, what it would change,
+    /// in the order [`Program::optimize`] runs them.
+    ///
+    /// Each pass is previewed from its analysis of the unoptimized program, so opportunities may
+    /// overlap, and running them all can save more or less than the sum of their deltas. Changes
+    /// which only become possible after other changes, like a second round of a pass which
+    /// repeats until nothing changes, aren't listed.
+    pub fn applicable_optimizations(&self, config: &Config) -> Vec<OptimizationOpportunity> {
+        Self::passes(config)
+            .into_iter()
+            .filter_map(|(pass, _, detect)| {
+                let rewrites = detect(self, config);
+
+                (!rewrites.is_empty()).then(|| self.preview(pass, &rewrites, config))
+            })
+            .collect()
+    }
+
+    fn preview(
+        &self,
+        pass: &'static str,
+        rewrites: &[Rewrite],
+        config: &Config,
+    ) -> OptimizationOpportunity {
+        let mut changes = vec![];
+        let mut size_delta = 0;
+
+        for (idx, rewrite) in rewrites.iter().enumerate() {
+            let line = rewrite.line;
+            size_delta -= size(&self.lines[line], config);
+
+            match &rewrite.change {
+                Change::Replace(lines, note) => {
+                    size_delta += lines.iter().map(|line| size(line, config)).sum::<isize>();
+
+                    if let Some(note) = note {
+                        changes.push(ChangeRecord {
+                            line,
+                            note: note.clone(),
+                        });
+                    }
+                }
+                Change::Remove(what) => {
+                    let guarded = self.is_guarded_after(&rewrites[..idx], line);
+                    if guarded {
+                        size_delta += size(&Statement::safe_noop(), config);
+                    }
+
+                    changes.push(ChangeRecord {
+                        line,
+                        note: removal_note(what, guarded),
+                    });
+                }
+            }
+        }
+
+        OptimizationOpportunity {
+            pass,
+            changes,
+            size_delta,
+            speed_delta: rewrites
+                .iter()
+                .filter_map(|rewrite| rewrite.speed_delta)
+                .reduce(|a, b| a + b),
+        }
+    }
+
+    /// Like [`Program::is_guarded_single_line`], but as line `idx` would be after making
+    /// `earlier`, which are the rewrites before it, in order.
+    fn is_guarded_after(&self, earlier: &[Rewrite], idx: usize) -> bool {
+        for line in (0..idx).rev() {
+            let statement = match earlier.binary_search_by_key(&line, |rewrite| rewrite.line) {
+                Ok(found) => match &earlier[found].change {
+                    Change::Replace(lines, _) => lines.last(),
+                    // neither a removed line nor the no-op left in its place guards anything
+                    Change::Remove(_) => return false,
+                },
+                Err(_) => Some(&self.lines[line]),
+            };
+
+            match statement {
+                None | Some(Statement::None | Statement::Fiction(_)) => {}
+                Some(statement) => return statement.guards_single_line(),
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    #[test]
+    fn applicable_optimizations() {
+        let config = Config::from(test_version!());
+        let program = Program::from_text("5->A\n5->A\n0->B\nDisp \"A\"+\"B\"", test_version!());
+        let unchanged = program.clone();
+
+        let opportunities = program.applicable_optimizations(&config);
+        assert!(program == unchanged);
+
+        assert_eq!(
+            opportunities
+                .iter()
+                .map(|opportunity| (
+                    opportunity.pass,
+                    opportunity.lines(),
+                    opportunity.size_delta,
+                    opportunity.speed_delta
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("redundant stores", vec![1], -4, None),
+                ("string concatenation", vec![3], -3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn guarded_removals() {
+        let config = Config::from(test_version!());
+        let program = Program::from_text("If B\nLbl Q\nDisp 1", test_version!());
+
+        let opportunities = program.applicable_optimizations(&config);
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(
+            opportunities[0].changes,
+            vec![ChangeRecord {
+                line: 1,
+                note: "replaced unused label Q with a no-op".to_string(),
+            }]
+        );
+        // `Lbl Q` becomes `Ans`
+        assert_eq!(opportunities[0].size_delta, -1);
+    }
+
+    #[test]
+    fn list_element_store_speed() {
+        let config = Config::from(test_version!());
+        let program = Program::from_text("DelVar L1\n1->L1(1\n2->L1(2\n0", test_version!());
+
+        let opportunities = program.applicable_optimizations(&config);
+        let opportunity = opportunities
+            .iter()
+            .find(|opportunity| opportunity.pass == "list element stores")
+            .unwrap();
+        assert_eq!(opportunity.lines(), vec![1, 2]);
+        // two entries are cheaper to store one at a time
+        assert!(opportunity.speed_delta.is_some_and(|delta| delta > 0));
+    }
+}
@@ -0,0 +1,98 @@
+//! # Rewrites
+//! Each pass is split in two: an analysis which finds the [`Rewrite`]s it would make without
+//! changing the program, and [`Program::apply_rewrites`], which makes them. The analyses also back
+//! [`Program::applicable_optimizations`], which previews each pass.
+
+use crate::optimize::explain::removal_note;
+use crate::parse::{statements::Statement, Program};
+
+/// What a [`Rewrite`] does to its line.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Change {
+    /// Replace the line with these lines, explaining why if there is a note. No lines deletes it
+    /// outright, so this is only for lines which the rest of the rewrite leaves unguarded.
+    Replace(Vec<Statement>, Option<String>),
+    /// Remove the line with [`Program::remove_line`]. The note names what it was, like
+    /// `redundant store`, for [`removal_note`].
+    Remove(String),
+}
+
+/// A change an optimization pass would make to one line.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Rewrite {
+    /// The line to change, as it is before any of the pass's rewrites are made.
+    pub line: usize,
+    pub change: Change,
+    /// The estimated change in clock cycles each time the line runs, according to
+    /// [`Config::cost_model`](crate::Config::cost_model), if the pass has a timing model.
+    pub speed_delta: Option<i64>,
+}
+
+impl Rewrite {
+    /// Replace line `line` with `statement`.
+    pub fn replace(line: usize, statement: Statement, note: Option<String>) -> Self {
+        Rewrite::splice(line, vec![statement], note)
+    }
+
+    /// Replace line `line` with `lines`.
+    pub fn splice(line: usize, lines: Vec<Statement>, note: Option<String>) -> Self {
+        Rewrite {
+            line,
+            change: Change::Replace(lines, note),
+            speed_delta: None,
+        }
+    }
+
+    /// Remove line `line`, which is a `what`.
+    pub fn remove(line: usize, what: impl Into<String>) -> Self {
+        Rewrite {
+            line,
+            change: Change::Remove(what.into()),
+            speed_delta: None,
+        }
+    }
+
+    pub fn with_speed_delta(mut self, speed_delta: i64) -> Self {
+        self.speed_delta = Some(speed_delta);
+
+        self
+    }
+}
+
+impl Program {
+    /// Makes `rewrites`, which must be in order of line and change each line at most once, and
+    /// explains each one. Lines added by earlier rewrites push the later ones down.
+    pub(crate) fn apply_rewrites(&mut self, rewrites: Vec<Rewrite>) {
+        let mut shift = 0;
+        let mut previous = None;
+
+        for Rewrite { line, change, .. } in rewrites {
+            debug_assert!(previous < Some(line), "rewrites out of order");
+            previous = Some(line);
+
+            let idx = line + shift;
+            match change {
+                Change::Replace(mut lines, note) => {
+                    match lines.len() {
+                        0 => self.lines[idx] = Statement::None,
+                        1 => self.lines[idx] = lines.pop().unwrap(),
+                        count => {
+                            self.splice_line(idx, lines);
+                            shift += count - 1;
+                        }
+                    }
+
+                    if let Some(note) = note {
+                        self.explain(idx, || note);
+                    }
+                }
+                Change::Remove(what) => {
+                    let replaced = self.remove_line(idx);
+                    self.explain(idx, || removal_note(&what, replaced));
+                }
+            }
+        }
+
+        self.invalidate_analyses();
+    }
+}
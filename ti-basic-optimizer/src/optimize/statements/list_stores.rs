@@ -0,0 +1,264 @@
+//! # List Element Stores
+//! Filling an empty list one element at a time, like `DelVar L1:5->L1(1):6->L1(2)`, can be done
+//! with a single list literal store, `DelVar L1:{5,6->L1`.
+//!
+//! The literal is smaller, but it has to build a whole new list before storing it, so for short
+//! lists the element stores can be faster. With [`Priority::Speed`](crate::optimize::Priority),
+//! list literal stores are offered both ways as [`StatementCandidate`]s, priced by
+//! [`Config::cost_model`].
+
+use tifloats::{tifloat, Float};
+
+use crate::optimize::rewrite::Rewrite;
+use crate::optimize::StatementCandidate;
+use crate::parse::{
+    components::{ListIndex, ListIndexable, Operand, StoreTarget, TIList},
+    expression::Expression,
+    statements::Statement,
+    Program,
+};
+use crate::Config;
+
+/// The estimated clock cycles to store `entries` values to a list with one list literal store,
+/// and one element at a time. The values being stored cost the same either way.
+fn list_store_costs(entries: u32, config: &Config) -> (u32, u32) {
+    let costs = &config.cost_model;
+
+    (
+        costs.list_literal_store_cost() + costs.list_literal_entry_cost() * entries,
+        costs.element_store_cost() * entries,
+    )
+}
+
+impl Program {
+    /// Replaces each run of list element stores with one store of a list literal.
+    ///
+    /// See also: [`Program::list_element_store_runs`]
+    pub fn optimize_list_element_stores(&mut self, config: &Config) {
+        self.apply_rewrites(self.list_element_store_rewrites(config));
+    }
+
+    pub(crate) fn list_element_store_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for (list, run) in self.list_element_store_runs(config) {
+            let entries = run
+                .iter()
+                .map(|&idx| match &self.lines[idx] {
+                    Statement::Store(value, _) => value.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+
+            let (literal_cost, element_cost) = list_store_costs(run.len() as u32, config);
+            rewrites.push(
+                Rewrite::replace(
+                    run[0],
+                    Statement::Store(
+                        Expression::Operand(Operand::ListLiteral(TIList { entries })),
+                        StoreTarget::List(list),
+                    ),
+                    Some(format!(
+                        "combined {} list element stores into one",
+                        run.len()
+                    )),
+                )
+                .with_speed_delta(literal_cost as i64 - element_cost as i64),
+            );
+            rewrites.extend(run[1..].iter().map(|&idx| {
+                Rewrite::remove(
+                    idx,
+                    format!("list element store (combined into line {})", run[0] + 1),
+                )
+            }));
+        }
+
+        rewrites
+    }
+
+    /// Replaces list literal stores with stores to each element when
+    /// [`StatementCandidate::choose`] prefers it.
+    ///
+    /// See also: [`Program::expandable_list_literal_stores`]
+    pub fn optimize_list_literal_stores(&mut self, config: &Config) {
+        self.apply_rewrites(self.list_literal_store_rewrites(config));
+    }
+
+    pub(crate) fn list_literal_store_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for idx in self.expandable_list_literal_stores(config) {
+            let Statement::Store(
+                Expression::Operand(Operand::ListLiteral(TIList { entries })),
+                StoreTarget::List(name),
+            ) = &self.lines[idx]
+            else {
+                unreachable!()
+            };
+            let entry_count = entries.len() as u32;
+
+            let one = tifloat!(0x0010000000000000 * 10 ^ 0);
+            let mut index = one;
+            let mut stores = vec![];
+            for entry in entries {
+                stores.push(Statement::Store(
+                    entry.clone(),
+                    StoreTarget::ListIndex(ListIndex {
+                        subject: ListIndexable::List(*name),
+                        index: Box::new(Expression::Operand(Operand::NumericLiteral(index))),
+                    }),
+                ));
+                // lists have at most 999 elements
+                index = (index + one).unwrap();
+            }
+
+            let (literal_cost, element_cost) = list_store_costs(entry_count, config);
+            let literal =
+                StatementCandidate::new(vec![self.lines[idx].clone()], literal_cost, config);
+            let expanded = StatementCandidate::new(stores, element_cost, config);
+
+            let Some(chosen) = StatementCandidate::choose(vec![literal, expanded], config) else {
+                continue;
+            };
+            if chosen.len() == 1 {
+                continue;
+            }
+
+            rewrites.push(
+                Rewrite::splice(
+                    idx,
+                    chosen,
+                    Some(format!(
+                        "expanded list literal store into {entry_count} element stores"
+                    )),
+                )
+                .with_speed_delta(element_cost as i64 - literal_cost as i64),
+            );
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::optimize::{CostModel, Priority};
+    use crate::parse::Reconstruct;
+    use std::sync::Arc;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn list_element_stores_combined() {
+        let mut tokens = load_test_data("/snippets/analysis/list-stores.txt");
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_list_element_stores(&config);
+
+        let stringify = |idx: usize| {
+            tokenizer
+                .stringify(&program.lines[idx].reconstruct(&config))
+                .to_string()
+        };
+        assert_eq!(stringify(1), "{5,A+1,7->L1");
+        assert_eq!(stringify(18), "{1,2->L2");
+        assert!(matches!(program.lines[19], Statement::None));
+        assert_eq!(stringify(6), "{1,2->L3");
+        assert_eq!(stringify(8), "3->L3(4");
+
+        assert!(program.list_element_store_runs(&config).is_empty());
+    }
+
+    #[test]
+    fn list_literal_stores_expanded() {
+        let text = "DelVar L1\n{5,A->L1\nClrList L2\n{1,2,3,4,5->L2\n0";
+
+        for (priority, expected) in [
+            (Priority::Size, text),
+            (Priority::Neutral, text),
+            (
+                Priority::Speed,
+                "DelVar L1\n5->L1(1\nA->L1(2\nClrList L2\n{1,2,3,4,5->L2\n0",
+            ),
+        ] {
+            let config = Config {
+                priority,
+                ..Config::from(test_version!())
+            };
+
+            let mut program = Program::from_text(text, test_version!());
+            program.optimize_list_literal_stores(&config);
+
+            let tokenizer = test_tokenizer!();
+            assert_eq!(
+                tokenizer
+                    .stringify(&program.reconstruct(&config))
+                    .to_string(),
+                expected,
+                "{priority:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn list_literal_store_costs() {
+        #[derive(Debug)]
+        struct FreeAllocation;
+
+        impl CostModel for FreeAllocation {
+            fn list_literal_store_cost(&self) -> u32 {
+                0
+            }
+        }
+
+        let text = "DelVar L1\n{5,A->L1\n0";
+        let config = Config {
+            priority: Priority::Speed,
+            cost_model: Arc::new(FreeAllocation),
+            ..Config::from(test_version!())
+        };
+
+        let mut program = Program::from_text(text, test_version!());
+        program.optimize_list_literal_stores(&config);
+        assert!(program == Program::from_text(text, test_version!()));
+    }
+
+    #[test]
+    fn list_literal_stores_keep_separators() {
+        let config = Config {
+            priority: Priority::Speed,
+            ..Config::from(test_version!())
+        };
+
+        let mut program =
+            Program::from_text("DelVar L1:{5,A->L1\nDisp 1:Disp 2\n0", test_version!());
+        program.optimize_list_literal_stores(&config);
+
+        assert_eq!(program.lines.len(), program.separators.len());
+        assert_eq!(program.lines.len(), program.original_lines.len());
+        assert_eq!(program.lines.len(), program.original_positions.len());
+        assert_eq!(
+            test_tokenizer!()
+                .stringify(&program.reconstruct(&config))
+                .to_string(),
+            "DelVar L1:5->L1(1\nA->L1(2\nDisp 1:Disp 2\n0"
+        );
+    }
+
+    #[test]
+    fn list_literal_stores_keep_comment_strings() {
+        assert_comment_strings_kept("\"FILL L1\nDelVar L1\n{5,A->L1\n\"DONE", |p, c| {
+            p.optimize_list_literal_stores(c)
+        });
+    }
+
+    #[test]
+    fn list_element_stores_keep_comment_strings() {
+        assert_comment_strings_kept("\"FILL L1\nDelVar L1\n5->L1(1\n6->L1(2\n\"DONE", |p, c| {
+            p.optimize_list_element_stores(c)
+        });
+    }
+}
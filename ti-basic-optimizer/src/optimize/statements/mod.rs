@@ -0,0 +1,5 @@
+mod list_stores;
+mod mode_settings;
+mod screen;
+mod stores;
+mod strings;
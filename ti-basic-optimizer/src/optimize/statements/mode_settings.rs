@@ -0,0 +1,64 @@
+//! # Mode Setting Elimination
+//! Setting a mode to the value it already has does nothing, so such commands can be removed.
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::Program;
+
+impl Program {
+    /// Removes mode-setting commands (`Radian`, `Func`, `AxesOff`, ...) which are provably
+    /// redundant.
+    ///
+    /// See also: [`Program::redundant_mode_settings`]
+    pub fn optimize_mode_settings(&mut self) {
+        self.apply_rewrites(self.mode_setting_rewrites());
+    }
+
+    pub(crate) fn mode_setting_rewrites(&self) -> Vec<Rewrite> {
+        self.redundant_mode_settings()
+            .into_iter()
+            .map(|idx| Rewrite::remove(idx, "redundant mode setting"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::parse::statements::Statement;
+    use test_files::{load_test_data, test_tokenizer};
+
+    #[test]
+    fn redundant_settings_eliminated() {
+        let mut tokens = load_test_data("/snippets/analysis/modes/redundant.txt");
+        let tokenizer = test_tokenizer!();
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_mode_settings();
+
+        assert!(matches!(program.lines[2], Statement::None));
+        assert!(matches!(program.lines[4], Statement::None));
+        assert!(program.redundant_mode_settings().is_empty());
+    }
+
+    #[test]
+    fn necessary_settings_kept() {
+        let mut tokens = load_test_data("/snippets/analysis/modes/not-redundant.txt");
+        let tokenizer = test_tokenizer!();
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_mode_settings();
+
+        assert!(!program
+            .lines
+            .iter()
+            .any(|line| matches!(line, Statement::None)));
+    }
+
+    #[test]
+    fn mode_settings_keep_comment_strings() {
+        assert_comment_strings_kept("\"DEGREES\nDegree\n\"AGAIN\nDegree", |p, _| {
+            p.optimize_mode_settings()
+        });
+    }
+}
@@ -0,0 +1,52 @@
+//! # Screen Command Deduplication
+//! Clearing a screen that was just cleared, or writing text that was just written, does nothing
+//! visible. Only enabled with [`Config::deduplicate_screen_commands`] because it changes timing.
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::Program;
+use crate::Config;
+
+impl Program {
+    /// Removes screen commands which repeat the line before them.
+    ///
+    /// See also: [`Program::redundant_screen_commands`]
+    pub fn optimize_screen_commands(&mut self, config: &Config) {
+        self.apply_rewrites(self.screen_command_rewrites(config));
+    }
+
+    pub(crate) fn screen_command_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        self.redundant_screen_commands(config)
+            .into_iter()
+            .map(|idx| Rewrite::remove(idx, "repeated screen command"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::parse::statements::Statement;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn repeated_commands_eliminated() {
+        let mut tokens = load_test_data("/snippets/analysis/screen/redundant.txt");
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_screen_commands(&config);
+
+        assert!(matches!(program.lines[1], Statement::None));
+        assert!(matches!(program.lines[5], Statement::None));
+        assert!(program.redundant_screen_commands(&config).is_empty());
+    }
+
+    #[test]
+    fn screen_commands_keep_comment_strings() {
+        assert_comment_strings_kept("\"CLEAR\nClrHome\nClrHome\n\"DONE", |p, c| {
+            p.optimize_screen_commands(c)
+        });
+    }
+}
@@ -0,0 +1,52 @@
+//! # Redundant Store Elimination
+//! Storing a value a variable already has does nothing but set `Ans`, so when `Ans` is overwritten
+//! before it is read, the store can be removed.
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::Program;
+use crate::Config;
+
+impl Program {
+    /// Removes stores of numeric literals to real variables which are known to already hold that
+    /// value.
+    ///
+    /// See also: [`Program::redundant_stores`]
+    pub fn optimize_redundant_stores(&mut self, config: &Config) {
+        self.apply_rewrites(self.redundant_store_rewrites(config));
+    }
+
+    pub(crate) fn redundant_store_rewrites(&self, config: &Config) -> Vec<Rewrite> {
+        self.redundant_stores(config)
+            .into_iter()
+            .map(|idx| Rewrite::remove(idx, "redundant store"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use crate::parse::statements::Statement;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn redundant_stores_eliminated() {
+        let mut tokens = load_test_data("/snippets/analysis/stores/redundant.txt");
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_redundant_stores(&config);
+
+        assert!(matches!(program.lines[3], Statement::None));
+        assert!(program.redundant_stores(&config).is_empty());
+    }
+
+    #[test]
+    fn redundant_stores_keep_comment_strings() {
+        assert_comment_strings_kept("\"SET A\n5->A\n\"AGAIN\n5->A\n\"DONE", |p, c| {
+            p.optimize_redundant_stores(c)
+        });
+    }
+}
@@ -0,0 +1,100 @@
+//! # String Accumulation Folding
+//! `"AB"->Str1:Str1+"CD"->Str1` stores a string only to add onto it immediately, so both lines can
+//! be written as `"ABCD"->Str1`.
+
+use crate::optimize::rewrite::Rewrite;
+use crate::parse::{
+    components::{Operand, Operator, StoreTarget},
+    expression::Expression,
+    statements::Statement,
+    Program,
+};
+
+impl Program {
+    /// Folds literal stores to a string into the concatenation which directly follows them.
+    ///
+    /// See also: [`Program::foldable_string_accumulations`]
+    pub fn optimize_string_accumulations(&mut self) {
+        // each round folds one step of a chain like `"A"->Str1:Str1+"B"->Str1:Str1+"C"->Str1`
+        loop {
+            let rewrites = self.string_accumulation_rewrites();
+            if rewrites.is_empty() {
+                break;
+            }
+
+            self.apply_rewrites(rewrites);
+        }
+    }
+
+    /// The rewrites for one round of [`Program::optimize_string_accumulations`].
+    pub(crate) fn string_accumulation_rewrites(&self) -> Vec<Rewrite> {
+        let mut rewrites = vec![];
+
+        for (store, acc) in self.foldable_string_accumulations() {
+            let Statement::Store(Expression::Operand(literal @ Operand::StringLiteral(_)), _) =
+                &self.lines[store]
+            else {
+                unreachable!("foldable accumulations start with a literal store");
+            };
+
+            let (_, appends, _) = self.lines[acc].string_accumulation().unwrap();
+            let mut folded = self.lines[acc].clone();
+            let Statement::Store(expr, StoreTarget::String(_)) = &mut folded else {
+                unreachable!();
+            };
+            let Expression::Operator(Operator::Binary(binop)) = expr else {
+                unreachable!();
+            };
+
+            let operand = if appends {
+                &mut binop.left
+            } else {
+                &mut binop.right
+            };
+            **operand = Expression::Operand(literal.clone());
+            expr.fold_string_concatenation();
+
+            rewrites.push(Rewrite::remove(
+                store,
+                "string store (folded into the next line)",
+            ));
+            rewrites.push(Rewrite::replace(
+                acc,
+                folded,
+                Some("folded string store into concatenation".to_string()),
+            ));
+        }
+
+        rewrites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::assert_comment_strings_kept;
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    #[test]
+    fn string_accumulations_folded() {
+        let tokenizer = test_tokenizer!();
+        let mut tokens = load_test_data("/snippets/optimize/strings/accumulation.txt");
+        let mut program = Program::from_tokens(&mut tokens, &tokenizer);
+        program.optimize_string_accumulations();
+
+        let expected = load_test_data("/snippets/optimize/strings/accumulation-folded.txt");
+        assert_eq!(
+            program.reconstruct(&test_version!().into()),
+            expected.collect::<Vec<_>>()
+        );
+        assert!(program.foldable_string_accumulations().is_empty());
+    }
+
+    #[test]
+    fn string_accumulations_keep_comment_strings() {
+        assert_comment_strings_kept(
+            "\"BUILD\n\"AB\"->Str1\nStr1+\"CD\"->Str1\n\"DONE",
+            |p, _| p.optimize_string_accumulations(),
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! # Cost Models
+//! Strategies estimate their speed costs from timing measurements. `CostModel` supplies those
+//! measurements so that they can be replaced, e.g. when targeting an overclocked calculator.
+//!
+//! Every method has a default implementation returning the measured values used by
+//! [`DefaultCostModel`], so a custom model only needs to override the values it disagrees with.
+
+use std::fmt::Debug;
+use titokens::Token;
+
+/// time to parse `x`
+#[rustfmt::skip]
+macro_rules! ttp {
+    (1) => {7075};
+    (.1) => {8089};
+    (11) => {9051};
+    (10) => {9113};
+    (.01) => {9511};
+    (.11) => {9956};
+    (111) => {11106};
+
+    (1|E1) => {10621};
+    (1|E~1) => {11699};
+    (1|E11) => {11832};
+    (1|E21) => {11893};
+}
+
+const DIGIT_COST: u32 = ttp!(11) - ttp!(1);
+const FRAC_DIGIT_COST: u32 = ttp!(.11) - ttp!(.1);
+const SHIFTING_COST: u32 = (ttp!(111) - ttp!(11)) - DIGIT_COST;
+const BASE_COST: u32 = ttp!(1) - DIGIT_COST - SHIFTING_COST;
+const ZERO_SIGFIG_COST: u32 = ttp!(10) - ttp!(11);
+const FRAC_LEADING_ZERO_COST: u32 = ttp!(.01) - ttp!(.1);
+const DECIMAL_POINT_COST: u32 = ttp!(.1) - BASE_COST - FRAC_DIGIT_COST - SHIFTING_COST;
+
+const EXPONENT_DECADE_COST: u32 = ttp!(1 | E21) - ttp!(1 | E11);
+const EXPONENT_NEGATION_COST: u32 = ttp!(1|E~1) - ttp!(1 | E1);
+const EXPONENT_TENS_COST: u32 = ttp!(1 | E11) - ttp!(1 | E1) - EXPONENT_DECADE_COST;
+const EXPONENT_BASE_COST: u32 = ttp!(1 | E1) - BASE_COST - DIGIT_COST - SHIFTING_COST;
+
+/// Clock cycle estimates used by [`Strategy::speed_cost`](super::Strategy::speed_cost).
+///
+/// The numeric literal costs are documented in
+/// [my first post on writing numbers](https://www.cemetech.net/forum/viewtopic.php?p=308266#308266).
+pub trait CostModel: Debug + Send + Sync {
+    /// Base cost of parsing any numeric literal.
+    fn base_cost(&self) -> u32 {
+        BASE_COST
+    }
+
+    /// Cost of each digit before the decimal point.
+    fn digit_cost(&self) -> u32 {
+        DIGIT_COST
+    }
+
+    /// Cost of each digit after the decimal point.
+    fn frac_digit_cost(&self) -> u32 {
+        FRAC_DIGIT_COST
+    }
+
+    /// Digits are packed two to a byte; this is the cost of starting a new byte.
+    fn shifting_cost(&self) -> u32 {
+        SHIFTING_COST
+    }
+
+    /// Additional cost of a zero digit.
+    fn zero_sigfig_cost(&self) -> u32 {
+        ZERO_SIGFIG_COST
+    }
+
+    /// Cost of each zero between the decimal point and the first significant figure.
+    fn frac_leading_zero_cost(&self) -> u32 {
+        FRAC_LEADING_ZERO_COST
+    }
+
+    fn decimal_point_cost(&self) -> u32 {
+        DECIMAL_POINT_COST
+    }
+
+    /// Base cost of the `|E` in a literal.
+    fn exponent_base_cost(&self) -> u32 {
+        EXPONENT_BASE_COST
+    }
+
+    /// Cost of a negative exponent.
+    fn exponent_negation_cost(&self) -> u32 {
+        EXPONENT_NEGATION_COST
+    }
+
+    /// Cost of an exponent having a tens digit at all.
+    fn exponent_tens_cost(&self) -> u32 {
+        EXPONENT_TENS_COST
+    }
+
+    /// Cost of each increment of the exponent's tens digit.
+    fn exponent_decade_cost(&self) -> u32 {
+        EXPONENT_DECADE_COST
+    }
+
+    /// Cost of a color constant, like `BLUE`.
+    fn color_constant_cost(&self) -> u32 {
+        5898
+    }
+
+    /// Cost of `pi` or `e`.
+    fn math_constant_cost(&self, constant: MathConstantKind) -> u32 {
+        match constant {
+            MathConstantKind::Pi => 4819,
+            MathConstantKind::E => 4784,
+        }
+    }
+
+    /// Rough cost of storing a list literal, like `{5,6->L1`, apart from evaluating its entries:
+    /// the new list is allocated, then each entry is copied in (see
+    /// [`CostModel::list_literal_entry_cost`]).
+    fn list_literal_store_cost(&self) -> u32 {
+        9000
+    }
+
+    /// Rough cost of copying each entry of a stored list literal.
+    fn list_literal_entry_cost(&self) -> u32 {
+        1500
+    }
+
+    /// Rough cost of storing to one element of a list, like `5->L1(1)`, apart from evaluating the
+    /// value: the list and the index are looked up.
+    fn element_store_cost(&self) -> u32 {
+        4000
+    }
+}
+
+/// A constant which can be written in place of its digits. See
+/// [`CostModel::math_constant_cost`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MathConstantKind {
+    Pi,
+    E,
+}
+
+impl MathConstantKind {
+    /// The token for this constant.
+    pub fn token(self) -> Token {
+        match self {
+            MathConstantKind::Pi => Token::OneByte(0xAC),
+            MathConstantKind::E => Token::TwoByte(0xBB, 0x31),
+        }
+    }
+}
+
+/// The measured costs.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultCostModel;
+
+impl CostModel for DefaultCostModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::Priority;
+    use crate::parse::Reconstruct;
+    use crate::Config;
+    use std::sync::Arc;
+    use tifloats::{tifloat, Float};
+
+    #[derive(Debug)]
+    struct SlowColors;
+
+    impl CostModel for SlowColors {
+        fn color_constant_cost(&self) -> u32 {
+            100000
+        }
+    }
+
+    #[test]
+    fn custom_cost_model() {
+        let ten = tifloat!(0x0010000000000000 * 10 ^ 1);
+        let mut config = Config {
+            priority: Priority::Speed,
+            ..Config::from(titokens::version::LATEST.clone())
+        };
+
+        assert_eq!(ten.reconstruct(&config), vec![Token::TwoByte(0xEF, 0x41)]);
+
+        config.cost_model = Arc::new(SlowColors);
+        assert_eq!(
+            ten.reconstruct(&config),
+            vec![Token::OneByte(0x31), Token::OneByte(0x30)]
+        );
+    }
+}
@@ -3,8 +3,16 @@
 //!
 //! `Strategy` provides a systematic way to compare these alternatives so that adding a new strategy
 //! is easy. See [`numeric_literal`] for an example of how `Strategy` can be used to implement a
-//! peephole optimization for numeric literals.
+//! peephole optimization for numeric literals, and [`statement`] for choosing between rewrites of
+//! whole lines.
+mod cost_model;
 mod numeric_literal;
+mod statement;
+
+pub use cost_model::{CostModel, DefaultCostModel, MathConstantKind};
+pub(crate) use numeric_literal::write_digits;
+pub use numeric_literal::FloatExt;
+pub use statement::StatementCandidate;
 
 use crate::optimize::Priority;
 use crate::parse::Reconstruct;
@@ -17,8 +25,9 @@ pub trait Strategy<T>: Reconstruct {
 
     /// The exact number of bytes that this `Strategy` would use.
     fn size_cost(&self) -> Option<usize>;
-    /// Estimation of the average clock cycles that this `Strategy` would use.
-    fn speed_cost(&self) -> Option<u32>;
+    /// Estimation of the average clock cycles that this `Strategy` would use, according to the
+    /// configured [`CostModel`].
+    fn speed_cost(&self, config: &Config) -> Option<u32>;
 }
 
 impl<T> Strategy<T> for Box<dyn Strategy<T>> {
@@ -30,8 +39,8 @@ impl<T> Strategy<T> for Box<dyn Strategy<T>> {
         (**self).size_cost()
     }
 
-    fn speed_cost(&self) -> Option<u32> {
-        (**self).speed_cost()
+    fn speed_cost(&self, config: &Config) -> Option<u32> {
+        (**self).speed_cost(config)
     }
 }
 
@@ -43,32 +52,93 @@ impl<T> Reconstruct for Box<dyn Strategy<T>> {
 
 /// Compare two `Strategies`. This function makes the resource-allocation decision for balancing
 /// speed and size under [neutral](Priority::Neutral) optimization
-fn partial_cmp<T>(
-    a: &dyn Strategy<T>,
-    b: &dyn Strategy<T>,
-    priority: Priority,
-) -> Option<Ordering> {
-    match priority {
+///
+/// The product of the two costs is computed in `u128`, which can't overflow, so strategies which
+/// differ in either cost never compare equal.
+fn partial_cmp<T, S: Strategy<T> + ?Sized>(a: &S, b: &S, config: &Config) -> Option<Ordering> {
+    match config.priority {
         Priority::Neutral => {
-            let my_cost = (a.size_cost()? as u64).saturating_mul(a.speed_cost()? as u64);
-            let other_cost = (b.size_cost()? as u64).saturating_mul(b.speed_cost()? as u64);
+            let my_cost = a.size_cost()? as u128 * a.speed_cost(config)? as u128;
+            let other_cost = b.size_cost()? as u128 * b.speed_cost(config)? as u128;
 
             Some(my_cost.cmp(&other_cost))
         }
-        Priority::Speed => a.speed_cost().partial_cmp(&b.speed_cost()),
+        Priority::Speed => a.speed_cost(config).partial_cmp(&b.speed_cost(config)),
         Priority::Size => a.size_cost().partial_cmp(&b.size_cost()),
     }
 }
 
+/// The index of the best of `candidates` which [exists](Strategy::exists) under
+/// [`Config::priority`]. Ties go to the earliest candidate.
+fn select<T, S: Strategy<T>>(candidates: &[S], config: &Config) -> Option<usize> {
+    (0..candidates.len())
+        .filter(|&idx| candidates[idx].exists())
+        .min_by(|&a, &b| {
+            partial_cmp(&candidates[a], &candidates[b], config)
+                .expect("Strategy which `exists` returned `None` for a `_cost`.")
+        })
+}
+
 impl<T> Reconstruct for Vec<Box<dyn Strategy<T>>> {
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
-        self.iter()
-            .filter(|&x| x.exists())
-            .min_by(|&a, &b| {
-                partial_cmp(a, b, config.priority)
-                    .expect("Strategy which `exists` returned `None` for a `_cost`.")
-            })
-            .map(|x| x.reconstruct(config))
+        select(self, config)
+            .map(|idx| self[idx].reconstruct(config))
             .expect("No strategies were available!")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    struct Fixed {
+        size: usize,
+        speed: u32,
+        name: Token,
+    }
+
+    impl Strategy<()> for Fixed {
+        fn exists(&self) -> bool {
+            true
+        }
+
+        fn size_cost(&self) -> Option<usize> {
+            Some(self.size)
+        }
+
+        fn speed_cost(&self, _config: &Config) -> Option<u32> {
+            Some(self.speed)
+        }
+    }
+
+    impl Reconstruct for Fixed {
+        fn reconstruct(&self, _config: &Config) -> Vec<Token> {
+            vec![self.name]
+        }
+    }
+
+    #[test]
+    fn neutral_costs_do_not_saturate() {
+        let config = Config {
+            priority: Priority::Neutral,
+            ..Config::from(test_version!())
+        };
+
+        // both products are past u64::MAX
+        let strategies: Vec<Box<dyn Strategy<()>>> = vec![
+            Box::new(Fixed {
+                size: 1 << 40,
+                speed: u32::MAX,
+                name: Token::OneByte(0x41),
+            }),
+            Box::new(Fixed {
+                size: 1 << 40,
+                speed: u32::MAX - 1,
+                name: Token::OneByte(0x42),
+            }),
+        ];
+
+        assert_eq!(strategies.reconstruct(&config), vec![Token::OneByte(0x42)]);
+    }
+}
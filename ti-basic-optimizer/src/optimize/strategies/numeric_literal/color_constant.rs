@@ -30,12 +30,15 @@ impl Strategy<Float> for ColorConstant {
             && self.item.significant_figures().len() <= 2
     }
 
+    /// Color tokens are two bytes, the same size as the two digits they stand for, so they are
+    /// only chosen when speed matters.
     fn size_cost(&self) -> Option<usize> {
         self.exists().then_some(2)
     }
 
-    fn speed_cost(&self) -> Option<u32> {
-        self.exists().then_some(5898)
+    fn speed_cost(&self, config: &Config) -> Option<u32> {
+        self.exists()
+            .then(|| config.cost_model.color_constant_cost())
     }
 }
 
@@ -6,7 +6,7 @@
 //!
 //! Example: `1234` becomes `.1234|E4`
 
-use super::write_digits::WriteDigits;
+use super::{exponent_size, write_digits::WriteDigits, write_exponent};
 use crate::optimize::strategies::numeric_literal::integer_with_exponent::IntegerWithExponent;
 use crate::optimize::strategies::Strategy;
 use crate::parse::Reconstruct;
@@ -44,25 +44,22 @@ impl Strategy<Float> for FPartWithExponent {
             let sig_figs = self.original.significant_figures().len();
             let shift = self.original.exponent() - self.adjusted.exponent();
 
-            let exponent_cost = match shift {
-                0..=9 => 1,
-                -9..=-1 | 10..=99 => 2,
-                -99..=-10 => 3,
-                _ => unreachable!(),
-            };
-
-            negation_cost + 1 + sig_figs + 1 + exponent_cost
+            negation_cost + 1 + sig_figs + exponent_size(shift)
         })
     }
 
-    fn speed_cost(&self) -> Option<u32> {
+    fn speed_cost(&self, config: &Config) -> Option<u32> {
         self.exists().then(|| {
             // WriteDigits always exists
-            let mantissa_cost = WriteDigits::new(self.adjusted).speed_cost().unwrap();
+            let mantissa_cost = WriteDigits::new(self.adjusted).speed_cost(config).unwrap();
 
             let required_shift = self.original.exponent() - self.adjusted.exponent();
             // IntegerWithExponent::exponent_speed_cost is always Some if FPartWithExponent exists
-            let exponent_cost = IntegerWithExponent::exponent_speed_cost(required_shift).unwrap();
+            let exponent_cost = IntegerWithExponent::exponent_speed_cost(
+                required_shift,
+                config.cost_model.as_ref(),
+            )
+            .unwrap();
 
             mantissa_cost + exponent_cost
         })
@@ -74,20 +71,9 @@ impl Reconstruct for FPartWithExponent {
         assert!(self.exists());
 
         let mut result = WriteDigits::new(self.adjusted).reconstruct(config);
-
-        result.push(Token::OneByte(0x3B));
-
-        let mut exponent = self.original.exponent() - self.adjusted.exponent();
-        if exponent < 0 {
-            result.push(Token::OneByte(0xB0));
-            exponent = exponent.abs();
-        }
-
-        if exponent > 10 {
-            result.push(Token::OneByte(0x30 + (exponent as u8 / 10)));
-        }
-
-        result.push(Token::OneByte(0x30 + (exponent as u8 % 10)));
+        result.extend(write_exponent(
+            self.original.exponent() - self.adjusted.exponent(),
+        ));
 
         result
     }
@@ -115,7 +101,7 @@ mod tests {
 
     #[test]
     fn speed_cost() {
-        let version = &*titokens::version::LATEST;
+        let config = Config::from(titokens::version::LATEST.clone());
 
         let cases = vec![
             (tifloat!(0x0010000000000000 * 10 ^ 1), 11635),
@@ -127,7 +113,10 @@ mod tests {
         ];
 
         for (item, expected) in cases {
-            assert_eq!(FPartWithExponent::new(item).speed_cost(), Some(expected));
+            assert_eq!(
+                FPartWithExponent::new(item).speed_cost(&config),
+                Some(expected)
+            );
         }
     }
 }
@@ -2,7 +2,7 @@
 //! When available, using `pi` or `e` is substantially faster and smaller than writing out the
 //! digits.
 
-use crate::optimize::strategies::Strategy;
+use crate::optimize::strategies::{MathConstantKind, Strategy};
 use crate::parse::Reconstruct;
 use crate::Config;
 use tifloats::{tifloat, Float};
@@ -11,15 +11,15 @@ use titokens::Token;
 pub(super) struct MathConstant {
     item: Float,
 
-    kind: Option<Token>,
+    kind: Option<MathConstantKind>,
 }
 
 impl MathConstant {
-    pub fn kind(item: Float) -> Option<Token> {
+    pub fn kind(item: Float) -> Option<MathConstantKind> {
         if item == tifloat!(0x0031415926535898 * 10 ^ 0) {
-            Some(Token::OneByte(0xAC))
+            Some(MathConstantKind::Pi)
         } else if item == tifloat!(0x0027182818284590 * 10 ^ 0) {
-            Some(Token::TwoByte(0xBB, 0x31))
+            Some(MathConstantKind::E)
         } else {
             None
         }
@@ -41,18 +41,12 @@ impl Strategy<Float> for MathConstant {
     }
 
     fn size_cost(&self) -> Option<usize> {
-        self.exists().then(|| match self.kind.unwrap() {
-            Token::OneByte(_) => 1,
-            Token::TwoByte(_, _) => 2,
-        })
+        self.kind.map(|kind| kind.token().byte_len())
     }
 
-    fn speed_cost(&self) -> Option<u32> {
-        self.exists().then(|| match self.kind.unwrap() {
-            Token::OneByte(0xAC) => 4819,
-            Token::TwoByte(0xBB, 0x31) => 4784,
-            _ => unreachable!(),
-        })
+    fn speed_cost(&self, config: &Config) -> Option<u32> {
+        self.kind
+            .map(|kind| config.cost_model.math_constant_cost(kind))
     }
 }
 
@@ -60,6 +54,6 @@ impl Reconstruct for MathConstant {
     fn reconstruct(&self, _config: &Config) -> Vec<Token> {
         assert!(self.exists());
 
-        self.kind.into_iter().collect()
+        self.kind.into_iter().map(MathConstantKind::token).collect()
     }
 }
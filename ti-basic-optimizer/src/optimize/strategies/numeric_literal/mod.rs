@@ -16,6 +16,80 @@ use integer_with_exponent::IntegerWithExponent;
 use math_constant::MathConstant;
 use write_digits::WriteDigits;
 
+/// Tokens for the `|E<exponent>` suffix shared by the exponent strategies.
+fn write_exponent(mut exponent: i8) -> Vec<Token> {
+    let mut result = vec![Token::OneByte(0x3B)];
+
+    if exponent < 0 {
+        result.push(Token::OneByte(0xB0));
+        exponent = exponent.abs();
+    }
+
+    if exponent >= 10 {
+        result.push(Token::OneByte(0x30 + exponent as u8 / 10));
+    }
+
+    result.push(Token::OneByte(0x30 + exponent as u8 % 10));
+
+    result
+}
+
+/// Size of [`write_exponent`]'s output.
+fn exponent_size(exponent: i8) -> usize {
+    match exponent {
+        0..=9 => 2,
+        -9..=-1 | 10..=99 => 3,
+        -99..=-10 => 4,
+        _ => unreachable!(),
+    }
+}
+
+/// Writes `value` digit by digit, for places where only digits are read as a number, like the
+/// parts of a [`Fraction`](crate::parse::components::Fraction).
+pub(crate) fn write_digits(value: Float, config: &Config) -> Vec<Token> {
+    WriteDigits::new(value).reconstruct(config)
+}
+
+/// Helpers for writing numeric literal strategies, in addition to the accessors `Float` already
+/// provides ([`significant_figures`](Float::significant_figures), [`exponent`](Float::exponent),
+/// [`shift`](Float::shift), [`is_negative`](Float::is_negative), and
+/// [`mantissa_from`](Float::mantissa_from)).
+pub trait FloatExt: Sized {
+    /// The `Float` exactly equal to `value`, or `None` if `value` has more than 14 significant
+    /// figures.
+    fn from_i64(value: i64) -> Option<Self>;
+
+    /// True if this `Float` has no nonzero digits after the decimal point.
+    fn is_integer(&self) -> bool;
+}
+
+impl FloatExt for Float {
+    fn from_i64(value: i64) -> Option<Self> {
+        let digits = value
+            .unsigned_abs()
+            .to_string()
+            .bytes()
+            .map(|digit| digit - b'0')
+            .collect::<Vec<_>>();
+
+        if digits.iter().rposition(|&digit| digit != 0).unwrap_or(0) >= 14 {
+            return None;
+        }
+
+        let exponent = if value == 0 {
+            0
+        } else {
+            digits.len() as i8 - 1
+        };
+
+        Float::new(value < 0, exponent, Float::mantissa_from(&digits)).ok()
+    }
+
+    fn is_integer(&self) -> bool {
+        self.exponent() >= self.significant_figures().len() as i8 - 1
+    }
+}
+
 impl Reconstruct for Float {
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
         let strategies: Vec<Box<dyn Strategy<Self>>> = vec![
@@ -29,3 +103,219 @@ impl Reconstruct for Float {
         strategies.reconstruct(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::Priority;
+    use crate::parse::{components::Operand, Parse};
+    use test_files::test_version;
+    use tifloats::tifloat;
+    use titokens::Tokens;
+
+    fn parse(tokens: Vec<Token>) -> Float {
+        let negative = tokens.first() == Some(&Token::OneByte(0xB0));
+        let mut tokens = Tokens::from_vec(tokens, Some(test_version!()));
+        if negative {
+            tokens.next();
+        }
+
+        let Some(Operand::NumericLiteral(value)) =
+            Operand::parse(tokens.next().unwrap(), &mut tokens).unwrap()
+        else {
+            panic!("Expected a numeric literal.");
+        };
+        assert!(tokens.peek().is_none());
+
+        if negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    #[test]
+    fn from_i64() {
+        assert_eq!(
+            Float::from_i64(0),
+            Some(tifloat!(0x0000000000000000 * 10 ^ 0))
+        );
+        assert_eq!(
+            Float::from_i64(10),
+            Some(tifloat!(0x0010000000000000 * 10 ^ 1))
+        );
+        assert_eq!(
+            Float::from_i64(-12345),
+            Some(tifloat!(-0x0012345000000000 * 10 ^ 4))
+        );
+        assert_eq!(
+            Float::from_i64(10_000_000_000_000_000),
+            Some(tifloat!(0x0010000000000000 * 10 ^ 16))
+        );
+        assert_eq!(Float::from_i64(123_456_789_012_345), None);
+        assert_eq!(Float::from_i64(i64::MIN), None);
+    }
+
+    #[test]
+    fn is_integer() {
+        assert!(tifloat!(0x0000000000000000 * 10 ^ 0).is_integer());
+        assert!(tifloat!(0x0012000000000000 * 10 ^ 1).is_integer());
+        assert!(tifloat!(-0x0010000000000000 * 10 ^ 20).is_integer());
+        assert!(!tifloat!(0x0012000000000000 * 10 ^ 0).is_integer());
+        assert!(!tifloat!(0x0010000000000000 * 10 ^ -1).is_integer());
+    }
+
+    #[test]
+    fn strategies_agree() {
+        let config = test_version!().into();
+        let cases = [
+            tifloat!(0x0010000000000000 * 10 ^ 0),
+            tifloat!(0x0015000000000000 * 10 ^ 0),
+            tifloat!(0x0010000000000000 * 10 ^ 5),
+            tifloat!(-0x0010000000000000 * 10 ^ 5),
+            tifloat!(0x0012345678900000 * 10 ^ 9),
+            tifloat!(0x0012345678900000 * 10 ^ 10),
+            tifloat!(0x0012345678900000 * 10 ^ 20),
+            tifloat!(0x0012300000000000 * 10 ^ -3),
+            tifloat!(-0x0012300000000000 * 10 ^ -12),
+            tifloat!(0x0010000000000000 * 10 ^ -50),
+        ];
+
+        for case in cases {
+            let strategies: Vec<Box<dyn Strategy<Float>>> = vec![
+                Box::new(WriteDigits::new(case)),
+                Box::new(IntegerWithExponent::new(case)),
+                Box::new(FPartWithExponent::new(case)),
+            ];
+
+            for strategy in strategies.iter().filter(|strategy| strategy.exists()) {
+                let tokens = strategy.reconstruct(&config);
+
+                assert_eq!(strategy.size_cost(), Some(tokens.len()), "{case:?}");
+                assert_eq!(parse(tokens), case);
+            }
+        }
+    }
+
+    #[test]
+    fn color_constants() {
+        let mono = titokens::version::LATEST_MONO.clone();
+
+        for color in 10..=24 {
+            let value = Float::from_i64(color).unwrap();
+            let constant = ColorConstant::new(value, &test_version!());
+            let digits = WriteDigits::new(value);
+            let config = Config::from(test_version!());
+
+            assert_eq!(
+                constant.size_cost(),
+                Some(Token::byte_len(&constant.reconstruct(&config)[0]))
+            );
+            assert_eq!(constant.size_cost(), digits.size_cost());
+            assert!(constant.speed_cost(&config) < digits.speed_cost(&config));
+            assert!(!ColorConstant::new(value, &mono).exists());
+
+            for (version, priority, expected) in [
+                (test_version!(), Priority::Size, digits.reconstruct(&config)),
+                (
+                    test_version!(),
+                    Priority::Speed,
+                    constant.reconstruct(&config),
+                ),
+                (
+                    test_version!(),
+                    Priority::Neutral,
+                    constant.reconstruct(&config),
+                ),
+                (mono.clone(), Priority::Speed, digits.reconstruct(&config)),
+            ] {
+                let config = Config {
+                    priority,
+                    ..Config::from(version)
+                };
+
+                assert_eq!(
+                    value.reconstruct(&config),
+                    expected,
+                    "{color} under {priority:?}"
+                );
+            }
+        }
+
+        for value in [
+            Float::from_i64(9).unwrap(),
+            Float::from_i64(25).unwrap(),
+            Float::from_i64(-10).unwrap(),
+            tifloat!(0x0010500000000000 * 10 ^ 1),
+        ] {
+            assert!(!ColorConstant::new(value, &test_version!()).exists());
+        }
+    }
+
+    /// xorshift64; deterministic so that failures are reproducible.
+    struct Generator(u64);
+
+    impl Generator {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+
+            self.0 % bound
+        }
+
+        fn float(&mut self) -> Float {
+            let sig_figs = 1 + self.next(14) as usize;
+            let mut digits = vec![1 + self.next(9) as u8];
+            digits.extend((1..sig_figs).map(|_| self.next(10) as u8));
+
+            // most literals in real programs are small
+            let exponent = if self.next(2) == 0 {
+                (self.next(199) as i16 - 99) as i8
+            } else {
+                self.next(20) as i8 - 5
+            };
+
+            Float::new(self.next(4) == 0, exponent, Float::mantissa_from(&digits)).unwrap()
+        }
+    }
+
+    #[test]
+    fn generated_round_trip() {
+        let mut generator = Generator(0x5EED_CA1C_0FAC_ADE5);
+
+        let mut cases = vec![
+            tifloat!(0x0000000000000000 * 10 ^ 0),
+            tifloat!(0x0031415926535898 * 10 ^ 0),
+            tifloat!(0x0027182818284590 * 10 ^ 0),
+            tifloat!(-0x0031415926535898 * 10 ^ 0),
+            tifloat!(0x0099999999999999 * 10 ^ 99),
+            tifloat!(0x0010000000000000 * 10 ^ -99),
+        ];
+        cases.extend((10..=25).map(|color| Float::from_i64(color).unwrap()));
+        cases.extend((0..3000).map(|_| generator.float()));
+
+        let versions = [
+            test_version!(),
+            titokens::version::LATEST.clone(),
+            titokens::version::LATEST_MONO.clone(),
+        ];
+
+        for version in versions {
+            for priority in [Priority::Neutral, Priority::Speed, Priority::Size] {
+                let config = Config {
+                    priority,
+                    ..Config::from(version.clone())
+                };
+
+                for case in &cases {
+                    assert_eq!(
+                        parse(case.reconstruct(&config)),
+                        *case,
+                        "{case:?} under {priority:?} for {version:?}"
+                    );
+                }
+            }
+        }
+    }
+}
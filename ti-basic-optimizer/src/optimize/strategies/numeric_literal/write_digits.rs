@@ -9,26 +9,6 @@ use std::cmp::max;
 use tifloats::Float;
 use titokens::Token;
 
-/// time to parse `x`
-#[rustfmt::skip]
-macro_rules! ttp {
-    (1) => {7075};
-    (.1) => {8089};
-    (11) => {9051};
-    (10) => {9113};
-    (.01) => {9511};
-    (.11) => {9956};
-    (111) => {11106};
-}
-
-pub(super) const DIGIT_COST: u32 = ttp!(11) - ttp!(1);
-pub(super) const FRAC_DIGIT_COST: u32 = ttp!(.11) - ttp!(.1);
-pub(super) const SHIFTING_COST: u32 = (ttp!(111) - ttp!(11)) - DIGIT_COST;
-pub(super) const BASE_COST: u32 = ttp!(1) - DIGIT_COST - SHIFTING_COST;
-pub(super) const ZERO_SIGFIG_COST: u32 = ttp!(10) - ttp!(11);
-pub(super) const FRAC_LEADING_ZERO_COST: u32 = ttp!(.01) - ttp!(.1);
-pub(super) const DECIMAL_POINT_COST: u32 = ttp!(.1) - BASE_COST - FRAC_DIGIT_COST - SHIFTING_COST;
-
 pub(super) struct WriteDigits {
     item: Float,
 }
@@ -46,14 +26,25 @@ impl Strategy<Float> for WriteDigits {
 
     fn size_cost(&self) -> Option<usize> {
         self.exists().then(|| {
-            1 + max(
-                self.item.exponent().unsigned_abs() as usize,
-                self.item.significant_figures().len(),
-            ) + self.item.is_negative() as usize
+            let exponent = self.item.exponent();
+            let sig_figs = self.item.significant_figures().len();
+
+            let digits = if exponent < 0 {
+                // decimal point, leading zeros, then the significant figures
+                exponent.unsigned_abs() as usize + sig_figs
+            } else {
+                let integer_digits = exponent as usize + 1;
+
+                max(integer_digits, sig_figs) + (sig_figs > integer_digits) as usize
+            };
+
+            digits + self.item.is_negative() as usize
         })
     }
 
-    fn speed_cost(&self) -> Option<u32> {
+    fn speed_cost(&self, config: &Config) -> Option<u32> {
+        let costs = &config.cost_model;
+
         self.exists().then(|| {
             let exponent = self.item.exponent();
             /* I deliberately glossed over significant figures in my post & define them there as
@@ -65,35 +56,38 @@ impl Strategy<Float> for WriteDigits {
              */
             let digits = self.item.significant_figures();
 
-            let mut clock_cycles = BASE_COST;
+            let mut clock_cycles = costs.base_cost();
 
             if exponent < 0 {
-                clock_cycles += FRAC_LEADING_ZERO_COST * (exponent.unsigned_abs() as u32 - 1);
+                clock_cycles +=
+                    costs.frac_leading_zero_cost() * (exponent.unsigned_abs() as u32 - 1);
             } else if (digits.len() as i8) < exponent + 1 {
                 let trailing_zero_count = exponent.unsigned_abs() as u32 + 1 - digits.len() as u32;
-                clock_cycles += (DIGIT_COST + ZERO_SIGFIG_COST) * trailing_zero_count
-                    + SHIFTING_COST * (((1 - digits.len() as u32 % 2) + trailing_zero_count) / 2);
+                clock_cycles += (costs.digit_cost() + costs.zero_sigfig_cost())
+                    * trailing_zero_count
+                    + costs.shifting_cost()
+                        * (((1 - digits.len() as u32 % 2) + trailing_zero_count) / 2);
             }
 
             if digits.len() as i8 > exponent + 1 {
-                clock_cycles += DECIMAL_POINT_COST;
+                clock_cycles += costs.decimal_point_cost();
             }
 
             // this could definitely be described in a more rusty way with fold, but I'd rather this
             // was obviously a direct translation of my posted python code.
             for (index, digit) in digits.iter().enumerate() {
                 if index as i8 > exponent {
-                    clock_cycles += FRAC_DIGIT_COST;
+                    clock_cycles += costs.frac_digit_cost();
                 } else {
-                    clock_cycles += DIGIT_COST;
+                    clock_cycles += costs.digit_cost();
                 }
 
                 if index % 2 == 0 {
-                    clock_cycles += SHIFTING_COST;
+                    clock_cycles += costs.shifting_cost();
                 }
 
                 if *digit == 0 {
-                    clock_cycles += ZERO_SIGFIG_COST;
+                    clock_cycles += costs.zero_sigfig_cost();
                 }
             }
 
@@ -157,6 +151,7 @@ impl Reconstruct for WriteDigits {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_files::test_version;
     use tifloats::tifloat;
 
     #[test]
@@ -168,8 +163,9 @@ mod tests {
             (tifloat!(0x0011110000000000 * 10 ^ 2), 14096),
         ];
 
+        let config = test_version!().into();
         for (case, expected) in cases {
-            assert_eq!(WriteDigits::new(case).speed_cost(), Some(expected))
+            assert_eq!(WriteDigits::new(case).speed_cost(&config), Some(expected))
         }
     }
 }
@@ -0,0 +1,116 @@
+//! # Statement Strategies
+//! Some rewrites replace whole lines with other lines that do the same thing, like `If A:Then:
+//! Disp 1:End` with `If A:Disp 1`, or a `While` loop with a `For(` loop. Which one is better
+//! depends on [`Priority`](crate::optimize::Priority), just like it does for numeric literals, so
+//! rewrites offer each form as a [`StatementCandidate`] and let [`StatementCandidate::choose`]
+//! decide.
+//!
+//! There is no timing model for whole statements, so each candidate brings its own speed estimate.
+
+use titokens::Token;
+
+use crate::optimize::strategies::{select, Strategy};
+use crate::parse::{statements::Statement, Reconstruct};
+use crate::Config;
+
+/// One way of writing some lines of a program.
+#[derive(Clone, Debug)]
+pub struct StatementCandidate {
+    lines: Vec<Statement>,
+    tokens: Vec<Token>,
+    speed_cost: u32,
+}
+
+impl StatementCandidate {
+    /// `lines`, which take about `speed_cost` clock cycles to run once, as written with `config`.
+    pub fn new(lines: Vec<Statement>, speed_cost: u32, config: &Config) -> Self {
+        let tokens = lines
+            .iter()
+            .map(|line| line.reconstruct(config))
+            .collect::<Vec<_>>()
+            .join(&Token::OneByte(0x3E));
+
+        StatementCandidate {
+            lines,
+            tokens,
+            speed_cost,
+        }
+    }
+
+    /// The lines of the best candidate under [`Config::priority`]; ties go to the earliest one.
+    /// Returns `None` if there are no candidates.
+    pub fn choose(
+        mut candidates: Vec<StatementCandidate>,
+        config: &Config,
+    ) -> Option<Vec<Statement>> {
+        let best = select(&candidates, config)?;
+
+        Some(candidates.swap_remove(best).lines)
+    }
+}
+
+impl Strategy<Statement> for StatementCandidate {
+    fn exists(&self) -> bool {
+        true
+    }
+
+    /// Lines are separated by one byte, either `:` or a newline.
+    fn size_cost(&self) -> Option<usize> {
+        Some(self.tokens.iter().map(Token::byte_len).sum())
+    }
+
+    fn speed_cost(&self, _config: &Config) -> Option<u32> {
+        Some(self.speed_cost)
+    }
+}
+
+impl Reconstruct for StatementCandidate {
+    fn reconstruct(&self, _config: &Config) -> Vec<Token> {
+        self.tokens.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::Priority;
+    use test_files::test_version;
+
+    fn candidate(text: &str, speed_cost: u32, config: &Config) -> StatementCandidate {
+        let lines = text
+            .split('\n')
+            .map(|line| Statement::from_text(line, test_version!()).unwrap())
+            .collect();
+
+        StatementCandidate::new(lines, speed_cost, config)
+    }
+
+    #[test]
+    fn choose() {
+        for (priority, expected) in [
+            (Priority::Size, 2),
+            (Priority::Speed, 4),
+            (Priority::Neutral, 2),
+        ] {
+            let config = Config {
+                priority,
+                ..Config::from(test_version!())
+            };
+
+            let candidates = vec![
+                candidate("If A\nThen\nDisp 1\nEnd", 900, &config),
+                candidate("If A\nDisp 1", 1000, &config),
+            ];
+            assert_eq!(candidates[0].size_cost(), Some(9));
+            assert_eq!(candidates[1].size_cost(), Some(5));
+
+            let chosen = StatementCandidate::choose(candidates, &config).unwrap();
+            assert_eq!(chosen.len(), expected, "{priority:?}");
+        }
+
+        assert_eq!(
+            StatementCandidate::choose(vec![], &Config::from(test_version!())),
+            None
+        );
+    }
+}
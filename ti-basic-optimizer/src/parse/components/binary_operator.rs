@@ -1,11 +1,13 @@
-use crate::parse::components::{ListIndexable, MatrixIndexable, Operand, Operator, OperatorKind};
+use crate::parse::components::{
+    ListIndexable, ListName, MatrixIndexable, Operand, Operator, OperatorKind, UnOp,
+};
 use crate::parse::expression::Expression;
 use crate::parse::Reconstruct;
 use crate::Config;
 use std::iter::once;
 use titokens::{Token, Version};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BinOp {
     pub kind: Token,
     pub left: Box<Expression>,
@@ -83,7 +85,21 @@ impl BinOp {
 
 impl Reconstruct for BinOp {
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
-        let mut implicit_mul_viable = true;
+        let right = match &*self.right {
+            Expression::Operator(Operator::Binary(right_binop))
+                if right_binop.precedence() <= self.precedence()
+                    && !(self.kind == right_binop.kind && self.associative()) =>
+            {
+                once(Token::OneByte(0x10))
+                    .chain(right_binop.reconstruct(config))
+                    .chain(once(Token::OneByte(0x11)))
+                    .collect()
+            }
+
+            expr => expr.reconstruct(config),
+        };
+
+        let mut implicit_mul_viable = config.implicit_multiplication;
         let mut result = match &*self.left {
             Expression::Operator(Operator::Binary(left_binop))
                 if left_binop.precedence() < self.precedence() =>
@@ -94,16 +110,32 @@ impl Reconstruct for BinOp {
                     .collect()
             }
 
+            // negation binds more loosely than ^ and xroot: `~2^2` is `~(2^2)`
+            negation @ Expression::Operator(Operator::Unary(UnOp {
+                kind: Token::OneByte(0xB0),
+                ..
+            })) if self.precedence() >= 70 => once(Token::OneByte(0x10))
+                .chain(negation.reconstruct(config))
+                .chain(once(Token::OneByte(0x11)))
+                .collect(),
+
             Expression::Operand(operand) => {
-                if self.kind == Token::OneByte(0x82)
-                    && (ListIndexable::try_from(operand).is_ok()
-                        || MatrixIndexable::try_from(operand).is_ok())
-                    || (matches!(operand, Operand::NumericLiteral(_))
-                        && matches!(
-                            &*self.right,
-                            Expression::Operand(Operand::NumericLiteral(_))
-                        ))
-                {
+                let right_start = right.first().copied();
+
+                // `L1(2)` and `[A](2)` are accesses, but `L1sin(2)` is a multiplication.
+                let access = (ListIndexable::try_from(operand).is_ok()
+                    || MatrixIndexable::try_from(operand).is_ok())
+                    && right_start == Some(Token::OneByte(0x10));
+                // `|LAB` followed by `C` would become `|LABC`
+                let longer_name = matches!(operand, Operand::ListName(ListName::Custom(_)))
+                    && right_start.is_some_and(|tok| tok.is_alphanumeric());
+                // `2` followed by `3` would become `23`
+                let longer_number = matches!(operand, Operand::NumericLiteral(_))
+                    && matches!(right_start, Some(Token::OneByte(0x30..=0x3B)));
+                // `1n/d2` followed by `A` might read `2A` as the denominator
+                let fraction = matches!(operand, Operand::Fraction(_));
+
+                if access || longer_name || longer_number || fraction {
                     implicit_mul_viable = false;
                 }
 
@@ -117,25 +149,80 @@ impl Reconstruct for BinOp {
             result.push(self.kind)
         }
 
-        match &*self.right {
-            Expression::Operator(Operator::Binary(right_binop))
-                if right_binop.precedence() <= self.precedence()
-                    && !(self.kind == right_binop.kind && self.associative()) =>
-            {
-                result.push(Token::OneByte(0x10));
-                result.extend(right_binop.reconstruct(config));
-                result.push(Token::OneByte(0x11));
-            }
-
-            expr => result.extend(expr.reconstruct(config)),
-        }
+        result.extend(right);
 
         result
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse::Parse;
+    use test_files::{test_tokenizer, test_version};
+
+    fn reconstruct(text: &str) -> String {
+        reconstruct_with(text, &test_version!().into())
+    }
+
+    fn reconstruct_with(text: &str, config: &Config) -> String {
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+        let expr = Expression::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+        assert!(tokens.peek().is_none());
+
+        let mut tokens = expr.reconstruct(config);
+        Expression::strip_closing_parenthesis(&mut tokens);
+
+        tokenizer.stringify(&tokens).to_string()
+    }
+
+    #[test]
+    fn power_and_xroot() {
+        for (text, expected) in [
+            ("3xroot(X^2)", "3xroot(X^2"),
+            ("X^(1/2)", "X^(1/2"),
+            ("(X^2)^3", "X^2^3"),
+            ("X^2^3", "X^2^3"),
+            ("X^(2^3)", "X^(2^3"),
+            ("(3xroot8)^2", "3xroot8^2"),
+            ("3xroot(8^2)", "3xroot(8^2"),
+            ("(2^3)xroot64", "2^3xroot64"),
+            ("2^(3xroot64)", "2^(3xroot64"),
+            ("2xroot(3xroot64)", "2xroot(3xroot64"),
+            ("~2^2", "~2^2"),
+            ("(~2)^2", "(~2)^2"),
+            ("(~8)xroot2", "(~8)xroot2"),
+            ("2^~(3+1)", "2^~(3+1"),
+            ("3xroot~8", "3xroot~8"),
+        ] {
+            assert_eq!(reconstruct(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn explicit_multiplication() {
+        let explicit = Config {
+            implicit_multiplication: false,
+            ..Config::from(test_version!())
+        };
+
+        for (text, implicit_expected, explicit_expected) in [
+            ("2*X", "2X", "2*X"),
+            ("2X", "2X", "2*X"),
+            ("A*B*C", "ABC", "A*B*C"),
+            ("2*3", "2*3", "2*3"),
+        ] {
+            assert_eq!(reconstruct(text), implicit_expected, "{text}");
+            assert_eq!(
+                reconstruct_with(text, &explicit),
+                explicit_expected,
+                "{text}"
+            );
+        }
+    }
 
     #[test]
     fn no_precedence_if_not_binop() {
@@ -9,7 +9,7 @@ use titokens::{Token, Tokens};
 
 use super::EquationName;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ListIndexable {
     List(ListName),
     TblInput,
@@ -44,7 +44,7 @@ impl Reconstruct for ListIndexable {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MatrixIndexable {
     Matrix(MatrixName),
     Ans,
@@ -77,7 +77,7 @@ impl TryFrom<&Operand> for MatrixIndexable {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MatrixIndex {
     pub subject: MatrixIndexable,
     pub row: Box<Expression>,
@@ -137,7 +137,7 @@ impl Reconstruct for MatrixIndex {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ListIndex {
     pub subject: ListIndexable,
     pub index: Box<Expression>,
@@ -182,7 +182,7 @@ impl Reconstruct for ListIndex {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EquationIndex {
     pub subject: EquationName,
     pub index: Box<Expression>,
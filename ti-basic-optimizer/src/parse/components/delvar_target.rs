@@ -9,7 +9,7 @@ use crate::parse::{
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DelVarTarget {
     NumericVar(NumericVarName),
     List(ListName),
@@ -3,7 +3,7 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct EquationName(Token);
 
 impl Parse for EquationName {
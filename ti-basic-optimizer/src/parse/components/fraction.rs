@@ -0,0 +1,146 @@
+use tifloats::Float;
+use titokens::{Token, Tokens};
+
+use crate::error_reporting::{expect_some, expect_tok, next_or_err, TokenReport};
+use crate::optimize::write_digits;
+use crate::parse::{Parse, Reconstruct};
+use crate::Config;
+
+/// A MathPrint fraction, like `1n/d2`, or a mixed number, like `1Un/d1n/d2` (one and a half).
+///
+/// The calculator stores the quotient, but a fraction isn't equal to any `Float` (`1n/d3` is not
+/// the same literal as `.3333333333333`), so it is kept as it was written. Only numbers are
+/// supported in each part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fraction {
+    /// The whole part of a mixed number, before `Un/d`.
+    pub whole: Option<Float>,
+    pub numerator: Float,
+    pub denominator: Float,
+}
+
+impl Fraction {
+    /// Parses the rest of a fraction whose first number, `first`, has already been parsed. Returns
+    /// `None` if `first` isn't followed by `n/d` or `Un/d`.
+    pub(crate) fn parse(first: Float, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
+        match more.peek() {
+            Some(Token::TwoByte(0xEF, 0x2E)) => {
+                // n/d
+                more.next();
+
+                Ok(Some(Fraction {
+                    whole: None,
+                    numerator: first,
+                    denominator: Fraction::part(more)?,
+                }))
+            }
+            Some(Token::TwoByte(0xEF, 0x2F)) => {
+                // Un/d
+                more.next();
+                let numerator = Fraction::part(more)?;
+                expect_tok!(more, Token::TwoByte(0xEF, 0x2E), "n/d")?;
+
+                Ok(Some(Fraction {
+                    whole: Some(first),
+                    numerator,
+                    denominator: Fraction::part(more)?,
+                }))
+            }
+
+            _ => Ok(None),
+        }
+    }
+
+    fn part(more: &mut Tokens) -> Result<Float, TokenReport> {
+        expect_some!(
+            Float::parse(next_or_err!(more)?, more)?,
+            more,
+            "a number",
+            "Only numbers are supported in fractions."
+        )
+    }
+}
+
+impl Reconstruct for Fraction {
+    /// Each part is written digit by digit, since a color or math constant in its place would no
+    /// longer be read as part of the fraction.
+    fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        let mut result = vec![];
+
+        if let Some(whole) = self.whole {
+            result.extend(write_digits(whole, config));
+            result.push(Token::TwoByte(0xEF, 0x2F));
+        }
+
+        result.extend(write_digits(self.numerator, config));
+        result.push(Token::TwoByte(0xEF, 0x2E));
+        result.extend(write_digits(self.denominator, config));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{components::Operand, expression::Expression, Program};
+    use test_files::{load_test_data, test_tokenizer, test_version};
+    use tifloats::tifloat;
+
+    #[test]
+    fn round_trip() {
+        let config = Config::from(test_version!());
+
+        for path in [
+            "/snippets/parsing/numbers/fraction.txt",
+            "/snippets/parsing/numbers/mixed-fraction.txt",
+        ] {
+            let original = load_test_data(path).collect::<Vec<_>>();
+            let program = Program::try_from_tokens(&mut load_test_data(path)).unwrap();
+            let reconstructed = program.reconstruct(&config);
+            assert_eq!(reconstructed, original, "{path}");
+
+            let reparsed = Program::try_from_tokens(&mut Tokens::from_vec(reconstructed, None));
+            assert!(reparsed.is_ok_and(|reparsed| reparsed.lines == program.lines));
+        }
+    }
+
+    #[test]
+    fn mixed_number() {
+        let mut tokens = load_test_data("/snippets/parsing/numbers/mixed-fraction.txt");
+        let first = tokens.next().unwrap();
+
+        assert_eq!(
+            Expression::parse(first, &mut tokens).unwrap(),
+            Some(Expression::Operand(Operand::Fraction(Fraction {
+                whole: Some(tifloat!(0x10000000000000 * 10 ^ 0)),
+                numerator: tifloat!(0x10000000000000 * 10 ^ 0),
+                denominator: tifloat!(0x20000000000000 * 10 ^ 0),
+            })))
+        );
+    }
+
+    #[test]
+    fn in_expressions() {
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+
+        for text in ["Disp 2*1n/d2", "Disp 1n/d2*A", "Disp A1Un/d1n/d2"] {
+            let program = Program::from_text(text, test_version!());
+
+            assert_eq!(
+                tokenizer
+                    .stringify(&program.reconstruct(&config))
+                    .to_string(),
+                text
+            );
+        }
+
+        for text in ["1n/dA", "1Un/d2", "1Un/d2+3"] {
+            assert!(
+                Program::try_from_tokens(&mut tokenizer.tokenize(text).unwrap().0).is_err(),
+                "{text}"
+            );
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::Config;
 use itertools::Itertools;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FunctionCall {
     pub kind: Token,
     pub arguments: Vec<Expression>,
@@ -17,6 +17,35 @@ impl Parse for FunctionCall {
             return Ok(None);
         }
 
+        let arguments = FunctionCall::parse_arguments(more)?;
+
+        Ok(Some(FunctionCall {
+            kind: token,
+            arguments,
+        }))
+    }
+}
+
+impl Reconstruct for FunctionCall {
+    fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        use std::iter::once;
+
+        once(self.kind)
+            .chain(
+                self.arguments
+                    .iter()
+                    .map(|x| x.reconstruct(config))
+                    .intersperse(vec![Token::OneByte(0x2B)])
+                    .flatten(),
+            )
+            .chain(once(Token::OneByte(0x11)))
+            .collect()
+    }
+}
+
+impl FunctionCall {
+    /// Parse a comma-separated argument list, consuming the closing parenthesis if present.
+    pub(crate) fn parse_arguments(more: &mut Tokens) -> Result<Vec<Expression>, TokenReport> {
         let mut arguments = vec![];
         if let Some(mut next) = more.next() {
             while let Some(expr) = Expression::parse(next, more)? {
@@ -51,31 +80,9 @@ impl Parse for FunctionCall {
             }
         }
 
-        Ok(Some(FunctionCall {
-            kind: token,
-            arguments,
-        }))
-    }
-}
-
-impl Reconstruct for FunctionCall {
-    fn reconstruct(&self, config: &Config) -> Vec<Token> {
-        use std::iter::once;
-
-        once(self.kind)
-            .chain(
-                self.arguments
-                    .iter()
-                    .map(|x| x.reconstruct(config))
-                    .intersperse(vec![Token::OneByte(0x2B)])
-                    .flatten(),
-            )
-            .chain(once(Token::OneByte(0x11)))
-            .collect()
+        Ok(arguments)
     }
-}
 
-impl FunctionCall {
     fn recognize(token: Token) -> bool {
         matches!(
             token.into(),
@@ -185,8 +192,7 @@ impl FunctionCall {
             0xEF35 | // RandIntNoRep
             0xEF95 | // InvBinom
             0xEF97 | // ToString
-            0xEF98 | // Eval
-            0xEFA6 // Piecewise
+            0xEF98 // Eval
         )
     }
 }
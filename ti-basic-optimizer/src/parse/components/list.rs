@@ -5,7 +5,7 @@ use crate::Config;
 use itertools::Itertools;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TIList {
     pub entries: Vec<Expression>,
 }
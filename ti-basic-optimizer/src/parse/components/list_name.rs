@@ -13,7 +13,7 @@ pub const DEFAULT_LISTS: [ListName; 6] = [
     ListName::Default(Token::TwoByte(0x5D, 0x05)),
 ];
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum ListName {
     /// L1, L2, ..., L6
     Default(Token),
@@ -34,9 +34,24 @@ impl TryFrom<NumericVarName> for ListName {
 }
 
 impl ListName {
-    /// Parse the up-to-5-character custom list name, without the beginning |L.
+    /// True for `L1` through `L6`. See [`DEFAULT_LISTS`].
+    pub fn is_default(&self) -> bool {
+        matches!(self, ListName::Default(_))
+    }
+
+    /// The number in the name of a default list: 1 for `L1`, and so on up to 6 for `L6`.
+    pub fn default_index(&self) -> Option<u8> {
+        match self {
+            ListName::Default(Token::TwoByte(0x5D, x)) => Some(x + 1),
+            _ => None,
+        }
+    }
+
+    /// Parse the up-to-5-character custom list name, without the beginning |L. Names start with a
+    /// letter A-Z or θ, followed by letters, θ, or digits.
     pub fn parse_custom_name(tokens: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         let start_position = tokens.current_position() - 1;
+        let name_position = tokens.current_position();
         let mut name = [0_u8; 5];
         let mut index = 0;
 
@@ -44,16 +59,18 @@ impl ListName {
             if (index == 0 && token.is_alpha()) || (index > 0 && token.is_alphanumeric()) {
                 // 0-indexed
                 if index >= 5 {
+                    let excess_position = tokens.current_position() - 1;
+
                     return Err(TokenReport::new(
                         start_position,
                         "List name has too many characters (max 5)",
                         None,
                     )
                     .with_span_label(
-                        start_position..start_position + 7,
+                        name_position..excess_position,
                         "This part is a valid list name.",
                     )
-                    .with_label(tokens.current_position(), "The part starting here is not."))?;
+                    .with_label(excess_position, "The part starting here is not."))?;
                 }
 
                 name[index] = token.byte();
@@ -122,7 +139,8 @@ impl Reconstruct for ListName {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use test_files::test_version;
+    use crate::error_reporting::Report;
+    use test_files::{test_tokenizer, test_version};
 
     #[test]
     fn parse() {
@@ -141,4 +159,62 @@ mod tests {
             .unwrap();
         assert_eq!(parsed.reconstruct(&test_version!().into()), name);
     }
+
+    #[test]
+    fn theta() {
+        let tokenizer = test_tokenizer!();
+        let parse = |text: &str| {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let parsed = ListName::parse(tokens.next().unwrap(), &mut tokens);
+
+            (parsed, tokens)
+        };
+
+        for (text, name) in [
+            ("|LthetaAB", [0x5B, 0x41, 0x42, 0, 0]),
+            ("|LABtheta", [0x41, 0x42, 0x5B, 0, 0]),
+            ("|Ltheta9", [0x5B, 0x39, 0, 0, 0]),
+        ] {
+            let (parsed, _) = parse(text);
+            let parsed = parsed.unwrap().unwrap();
+            assert_eq!(parsed, ListName::Custom(name), "{text}");
+
+            let (expected, _) = tokenizer.tokenize(text).unwrap();
+            assert_eq!(
+                parsed.reconstruct(&test_version!().into()),
+                expected.collect::<Vec<_>>()
+            );
+        }
+
+        // [A] is the token right after θ
+        let (parsed, mut rest) = parse("|LA[A]");
+        assert_eq!(parsed.unwrap(), Some(ListName::Custom([0x41, 0, 0, 0, 0])));
+        assert_eq!(rest.next(), Some(Token::TwoByte(0x5C, 0x00)));
+
+        assert!(parse("|L1AB").0.is_err());
+        assert!(parse("|L[A]").0.is_err());
+    }
+
+    #[test]
+    fn default_lists() {
+        for (idx, list) in DEFAULT_LISTS.iter().enumerate() {
+            assert!(list.is_default());
+            assert_eq!(list.default_index(), Some(idx as u8 + 1));
+        }
+
+        let custom = ListName::Custom([0x41, 0, 0, 0, 0]);
+        assert!(!custom.is_default());
+        assert_eq!(custom.default_index(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-errors")]
+    fn too_long() {
+        let (mut tokens, boundaries) = test_tokenizer!().tokenize("|LABCDEF").unwrap();
+
+        let report = ListName::parse(tokens.next().unwrap(), &mut tokens).unwrap_err();
+
+        // must not panic
+        report.translate(&boundaries);
+    }
 }
@@ -3,7 +3,7 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct MatrixName(Token);
 
 impl Parse for MatrixName {
@@ -4,12 +4,14 @@ pub use crate::parse::components::{
     data_access::{EquationIndex, ListIndex, ListIndexable, MatrixIndex, MatrixIndexable},
     delvar_target::DelVarTarget,
     equation_name::EquationName,
+    fraction::Fraction,
     function_call::FunctionCall,
     list::TIList,
     list_name::{ListName, DEFAULT_LISTS},
     matrix_name::MatrixName,
-    numeric_var_name::NumericVarName,
+    numeric_var_name::{NumericVarName, LETTER_VARIABLES, RESERVED_VARIABLES},
     pic_image_name::{ImageName, PicName},
+    piecewise::Piecewise,
     pseudovariable::PseudoVariable,
     rand::Rand,
     store_target::StoreTarget,
@@ -27,6 +29,7 @@ mod binary_operator;
 mod data_access;
 mod delvar_target;
 mod equation_name;
+mod fraction;
 mod function_call;
 mod list;
 mod list_name;
@@ -34,6 +37,7 @@ mod matrix_name;
 mod numeric_literal;
 mod numeric_var_name;
 mod pic_image_name;
+mod piecewise;
 mod pseudovariable;
 mod rand;
 mod store_target;
@@ -42,7 +46,7 @@ mod string_name;
 mod unary_operator;
 mod window_var_name;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Operator {
     Binary(BinOp),
     Unary(UnOp),
@@ -72,7 +76,7 @@ impl Reconstruct for Operator {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Operand {
     NumericVarName(NumericVarName),
     ListName(ListName),
@@ -87,10 +91,12 @@ pub enum Operand {
     Rand(Rand),
     PseudoVariable(PseudoVariable),
     NumericLiteral(tifloats::Float),
+    Fraction(Fraction),
     StringLiteral(TIString),
     ListLiteral(TIList),
     TblInput,
     WindowVarName(WindowVarName),
+    Piecewise(Piecewise),
     /// for expr and seq and such
     Expression(Box<Expression>),
 }
@@ -99,7 +105,14 @@ impl Parse for Operand {
     fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         match token {
             Token::OneByte(0x30..=0x39 | 0x3A | 0x3B) => {
-                Ok(tifloats::Float::parse(token, more)?.map(Self::NumericLiteral))
+                let Some(value) = tifloats::Float::parse(token, more)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(match Fraction::parse(value, more)? {
+                    Some(fraction) => Self::Fraction(fraction),
+                    None => Self::NumericLiteral(value),
+                }))
             }
             Token::OneByte(0x41..=0x5B) | Token::TwoByte(0x62, 0x21) => {
                 Ok(NumericVarName::parse(token, more)?.map(Self::NumericVarName))
@@ -115,18 +128,32 @@ impl Parse for Operand {
                         "an expression"
                     )?;
 
-                    match more.peek() {
-                        Some(Token::OneByte(0x2B)) => {
-                            // , -> matrix access
-                            todo!()
-                        }
-                        Some(Token::OneByte(0x11)) => {
+                    if more.peek() == Some(Token::OneByte(0x2B)) {
+                        // , -> matrix access
+                        more.next();
+                        let col = expect_some!(
+                            Expression::parse(next_or_err!(more)?, more)?,
+                            more,
+                            "an expression",
+                            "This is a matrix access. Matrix accesses require both a row and column."
+                        )?;
+
+                        if more.peek() == Some(Token::OneByte(0x11)) {
                             // )
                             more.next();
                         }
 
-                        _ => {}
-                    };
+                        return Ok(Some(Self::MatrixAccess(MatrixIndex {
+                            subject: MatrixIndexable::Ans,
+                            row: Box::new(index),
+                            col: Box::new(col),
+                        })));
+                    }
+
+                    if more.peek() == Some(Token::OneByte(0x11)) {
+                        // )
+                        more.next();
+                    }
 
                     Ok(Some(Self::ListAccess(ListIndex {
                         subject: ListIndexable::Ans,
@@ -181,9 +208,10 @@ impl Parse for Operand {
                 }
             }
             Token::TwoByte(0x63, 0x2A) => Ok(Some(Self::TblInput)), // todo: TblIndex(n) list access
-            Token::TwoByte(0x63, 0x00..=0x2A | 0x32..=0x38) => {
+            Token::TwoByte(0x63, 0x00..=0x29 | 0x2B..=0x38) => {
                 Ok(WindowVarName::parse(token, more)?.map(Self::WindowVarName))
             }
+            Token::TwoByte(0xEF, 0xA6) => Ok(Piecewise::parse(token, more)?.map(Self::Piecewise)),
             _ => Ok(numeric_literal::parse_constant(token, more)),
         }
     }
@@ -205,10 +233,12 @@ impl Reconstruct for Operand {
             Operand::Rand(x) => x.reconstruct(config),
             Operand::PseudoVariable(x) => x.reconstruct(config),
             Operand::NumericLiteral(x) => x.reconstruct(config),
+            Operand::Fraction(x) => x.reconstruct(config),
             Operand::StringLiteral(x) => x.reconstruct(config),
             Operand::ListLiteral(x) => x.reconstruct(config),
             Operand::TblInput => vec![Token::TwoByte(0x63, 0x2A)],
             Operand::WindowVarName(x) => x.reconstruct(config),
+            Operand::Piecewise(x) => x.reconstruct(config),
             Operand::Expression(x) => x.reconstruct(config),
         }
     }
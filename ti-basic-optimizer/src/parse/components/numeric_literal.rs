@@ -66,14 +66,6 @@ impl<'a> Builder<'a> {
             Some(x) if x.is_numeric() => {
                 let before_decimal = self.digits();
 
-                match self.tokens.peek() {
-                    Some(Token::TwoByte(0xEF, 0x2F)) => {
-                        todo!()
-                    }
-
-                    _ => {}
-                }
-
                 if before_decimal.len() < 99 {
                     // #[allow(clippy::cast_lossless)] once it's stabilized
                     self.exponent = (before_decimal.len() - 1) as i8;
@@ -182,8 +174,8 @@ impl Parse for tifloats::Float {
         match token {
             Token::OneByte(0x30..=0x3B) => {
                 more.backtrack_once();
-                let mut builder = Builder::new(more);
-                Ok(Some(builder.parse()))
+
+                Ok(Some(Builder::new(more).parse()))
             }
 
             _ => Ok(None),
@@ -217,23 +209,9 @@ pub(crate) fn parse_constant(tok: Token, more: &mut Tokens) -> Option<Operand> {
         Token::TwoByte(0xEF, 0x4E) => Some(NL(tifloat!(0x0023000000000000 * 10 ^ 1))),
         Token::TwoByte(0xEF, 0x4F) => Some(NL(tifloat!(0x0024000000000000 * 10 ^ 1))),
 
-        // LEFT
-        Token::TwoByte(0xEF, 0x92) => {
-            Some(Operand::StringLiteral(TIString::new(vec![Token::OneByte(
-                0x30,
-            )])))
-        }
-        // CENTER
-        Token::TwoByte(0xEF, 0x93) => {
-            Some(Operand::StringLiteral(TIString::new(vec![Token::OneByte(
-                0x31,
-            )])))
-        }
-        // RIGHT
-        Token::TwoByte(0xEF, 0x94) => {
-            Some(Operand::StringLiteral(TIString::new(vec![Token::OneByte(
-                0x32,
-            )])))
+        // LEFT, CENTER, RIGHT
+        Token::TwoByte(0xEF, 0x92..=0x94) => {
+            TIString::from_alignment_constant(tok).map(Operand::StringLiteral)
         }
         _ => None,
     }
@@ -317,6 +295,27 @@ mod tests {
             "/snippets/parsing/numbers/earth-mass.txt",
             tifloat!(0x59724000000000 * 10 ^ 24)
         );
+
+        /// The number before `n/d` or `Un/d` is read by itself, and the rest is left for
+        /// [`Fraction`](crate::parse::components::Fraction).
+        #[test]
+        fn fractions() {
+            use test_files::load_test_data;
+
+            for path in [
+                "/snippets/parsing/numbers/fraction.txt",
+                "/snippets/parsing/numbers/mixed-fraction.txt",
+            ] {
+                let mut tokens = load_test_data(path);
+                let value = Builder::new(&mut tokens).parse();
+
+                assert_eq!(value, tifloat!(0x10000000000000 * 10 ^ 0));
+                assert!(matches!(
+                    tokens.peek(),
+                    Some(Token::TwoByte(0xEF, 0x2E | 0x2F))
+                ));
+            }
+        }
     }
 
     mod reconstruct {
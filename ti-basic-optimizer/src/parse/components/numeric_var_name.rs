@@ -3,9 +3,38 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Copy, Clone, Debug)]
+/// The 27 real variables `A` through `theta`.
+pub const LETTER_VARIABLES: [NumericVarName; 27] = {
+    let mut variables = [NumericVarName(Token::OneByte(0x41)); 27];
+
+    let mut idx = 0;
+    while idx < variables.len() {
+        variables[idx] = NumericVarName(Token::OneByte(0x41 + idx as u8));
+        idx += 1;
+    }
+
+    variables
+};
+
+/// Real variables which the calculator itself writes: `n`, which is set while graphing sequences
+/// and by some statistics commands.
+pub const RESERVED_VARIABLES: [NumericVarName; 1] = [NumericVarName(Token::TwoByte(0x62, 0x21))];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct NumericVarName(pub Token);
 
+impl NumericVarName {
+    /// True for `A` through `theta`. See [`LETTER_VARIABLES`].
+    pub fn is_letter(&self) -> bool {
+        matches!(self.0, Token::OneByte(0x41..=0x5B))
+    }
+
+    /// True for variables the calculator may write on its own. See [`RESERVED_VARIABLES`].
+    pub fn is_reserved(&self) -> bool {
+        RESERVED_VARIABLES.contains(self)
+    }
+}
+
 impl Parse for NumericVarName {
     fn parse(token: Token, _more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         Ok(match token {
@@ -21,3 +50,29 @@ impl Reconstruct for NumericVarName {
         vec![self.0]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enumeration() {
+        assert_eq!(LETTER_VARIABLES[0], NumericVarName(Token::OneByte(0x41)));
+        assert_eq!(LETTER_VARIABLES[26], NumericVarName(Token::OneByte(0x5B)));
+
+        let mut tokens = Tokens::from_vec(vec![], None);
+        for variable in LETTER_VARIABLES.iter().chain(&RESERVED_VARIABLES) {
+            assert_eq!(
+                NumericVarName::parse(variable.0, &mut tokens).unwrap(),
+                Some(*variable)
+            );
+        }
+
+        assert!(LETTER_VARIABLES
+            .iter()
+            .all(|x| x.is_letter() && !x.is_reserved()));
+        assert!(RESERVED_VARIABLES
+            .iter()
+            .all(|x| !x.is_letter() && x.is_reserved()));
+    }
+}
@@ -3,7 +3,7 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PicName(Token);
 
 impl Parse for PicName {
@@ -21,7 +21,7 @@ impl Reconstruct for PicName {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ImageName(Token);
 
 impl Parse for ImageName {
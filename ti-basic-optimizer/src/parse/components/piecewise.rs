@@ -0,0 +1,132 @@
+use crate::error_reporting::TokenReport;
+use crate::parse::components::FunctionCall;
+use crate::parse::expression::Expression;
+use crate::parse::{Parse, Reconstruct};
+use crate::Config;
+use itertools::Itertools;
+use titokens::{Token, Tokens};
+
+/// `piecewise(value1, condition1, value2, condition2, ...)`
+///
+/// The first value whose condition is true is the result. A trailing value with no condition is
+/// kept in [`Piecewise::otherwise`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Piecewise {
+    /// (value, condition)
+    pub pairs: Vec<(Expression, Expression)>,
+    pub otherwise: Option<Box<Expression>>,
+}
+
+impl Parse for Piecewise {
+    fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
+        if token != Token::TwoByte(0xEF, 0xA6) {
+            return Ok(None);
+        }
+
+        let start_position = more.current_position() - 1;
+        let mut arguments = FunctionCall::parse_arguments(more)?;
+
+        if arguments.len() < 2 {
+            Err(TokenReport::new(
+                start_position,
+                "Malformed piecewise(",
+                Some("piecewise( needs at least one value and condition."),
+            )
+            .with_span_label(
+                start_position..more.current_position(),
+                "Expected a value and a condition",
+            ))?;
+        }
+
+        let otherwise = if arguments.len() % 2 == 1 {
+            arguments.pop().map(Box::new)
+        } else {
+            None
+        };
+
+        let pairs = arguments.into_iter().tuples().collect();
+
+        Ok(Some(Piecewise { pairs, otherwise }))
+    }
+}
+
+impl Reconstruct for Piecewise {
+    fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        use std::iter::once;
+
+        once(Token::TwoByte(0xEF, 0xA6))
+            .chain(
+                self.arguments()
+                    .map(|x| x.reconstruct(config))
+                    .intersperse(vec![Token::OneByte(0x2B)])
+                    .flatten(),
+            )
+            .chain(once(Token::OneByte(0x11)))
+            .collect()
+    }
+}
+
+impl Piecewise {
+    /// All arguments, in source order.
+    pub fn arguments(&self) -> impl Iterator<Item = &Expression> {
+        self.pairs
+            .iter()
+            .flat_map(|(value, condition)| [value, condition])
+            .chain(self.otherwise.as_deref())
+    }
+
+    /// The last argument, which is the one that may lose its closing parenthesis.
+    pub fn last_argument_mut(&mut self) -> Option<&mut Expression> {
+        if let Some(otherwise) = &mut self.otherwise {
+            Some(otherwise)
+        } else {
+            self.pairs.last_mut().map(|(_, condition)| condition)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{test_tokenizer, test_version};
+
+    fn parse(text: &str) -> Result<Option<Piecewise>, TokenReport> {
+        let (mut tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+
+        Piecewise::parse(tokens.next().unwrap(), &mut tokens)
+    }
+
+    #[test]
+    fn pairs() {
+        let piecewise = parse("piecewise(1,X<0,2,X>0").unwrap().unwrap();
+
+        assert_eq!(piecewise.pairs.len(), 2);
+        assert!(piecewise.otherwise.is_none());
+    }
+
+    #[test]
+    fn otherwise() {
+        let piecewise = parse("piecewise(1,X<0,2)").unwrap().unwrap();
+
+        assert_eq!(piecewise.pairs.len(), 1);
+        assert!(piecewise.otherwise.is_some());
+    }
+
+    #[test]
+    fn reconstruct() {
+        let text = "piecewise(1,X<0,2,X>0,3)";
+        let (tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+        let piecewise = parse(text).unwrap().unwrap();
+
+        assert_eq!(
+            piecewise.reconstruct(&test_version!().into()),
+            tokens.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(parse("piecewise(1)").is_err());
+        assert!(parse("piecewise(").is_err());
+    }
+}
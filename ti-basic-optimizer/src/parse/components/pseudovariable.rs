@@ -5,7 +5,7 @@ use titokens::{Token, Tokens};
 
 /// Pseudo-variables are like `GetKey` and `IsClockOn`- functions that return a value and never
 /// accept arguments.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PseudoVariable {
     pub kind: Token,
 }
@@ -12,7 +12,7 @@ use crate::{
 
 use super::{expect_some, next_or_err, Reconstruct};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Rand {
     pub count: Option<Box<Expression>>,
 }
@@ -4,13 +4,14 @@ use crate::parse::{
         EquationName, ListIndex, ListName, MatrixIndex, MatrixName, NumericVarName, StringName,
         WindowVarName,
     },
+    expression::Expression,
     Parse, Reconstruct,
 };
 use crate::Config;
 use std::iter::once;
 use titokens::{Token, Tokens, Version};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StoreTarget {
     NumericVarOrListName(NumericVarName),
     NumericVar(NumericVarName),
@@ -23,6 +24,10 @@ pub enum StoreTarget {
     String(StringName),
     Equation(EquationName),
     WindowVar(WindowVarName),
+    /// `->rand` seeds the random number generator.
+    ///
+    /// `rand` on the left of the arrow is always read as a [`Rand`](super::Rand) operand; the
+    /// expression parser stops at the arrow, so there is no ambiguity with this target.
     RandSeed,
 }
 
@@ -81,7 +86,7 @@ impl Parse for StoreTarget {
                 }
             }
             Token::TwoByte(0x5E, _) => Ok(EquationName::parse(token, more)?.map(Self::Equation)),
-            Token::TwoByte(0x63, 0x00..=0x2A | 0x32..=0x38) => {
+            Token::TwoByte(0x63, 0x00..=0x29 | 0x2B..=0x38) => {
                 Ok(WindowVarName::parse(token, more)?.map(Self::WindowVar))
             }
             Token::OneByte(0xAB) => Ok(Some(Self::RandSeed)),
@@ -113,6 +118,27 @@ impl Reconstruct for StoreTarget {
     }
 }
 
+impl StoreTarget {
+    /// The index expressions in this target, like the `2` in `->L1(2)`.
+    pub fn indices(&self) -> Vec<&Expression> {
+        match self {
+            StoreTarget::ListIndex(access) => vec![&access.index],
+            StoreTarget::MatrixIndex(access) => vec![&access.row, &access.col],
+
+            _ => vec![],
+        }
+    }
+
+    pub fn indices_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            StoreTarget::ListIndex(access) => vec![&mut access.index],
+            StoreTarget::MatrixIndex(access) => vec![&mut access.row, &mut access.col],
+
+            _ => vec![],
+        }
+    }
+}
+
 impl From<NumericVarName> for StoreTarget {
     fn from(value: NumericVarName) -> Self {
         StoreTarget::NumericVar(value)
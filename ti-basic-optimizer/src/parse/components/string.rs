@@ -5,14 +5,50 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
+/// A string literal.
+///
+/// On the calculator, a newline always ends the line, even inside a string; only `:` can appear in
+/// a string without ending it. A string therefore never contains a newline, `->`, or `"`, and
+/// [`TIString::parse`] stops at the first of these without consuming the newline or `->`.
 #[derive(Clone, Debug)]
 pub struct TIString {
     data: Vec<Token>,
+    /// False if the string was read without its closing quote. Only used to write a comment
+    /// string back exactly as it was read; see [`Config::preserve_comment_strings`].
+    closed: bool,
+    /// True if the string is written as `LEFT`, `CENTER`, or `RIGHT`. See
+    /// [`TIString::alignment_constant`].
+    constant: bool,
 }
 
+impl PartialEq for TIString {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for TIString {}
+
 impl TIString {
     pub fn new(data: Vec<Token>) -> Self {
-        TIString { data }
+        TIString {
+            data,
+            closed: true,
+            constant: false,
+        }
+    }
+
+    /// The string read from `LEFT`, `CENTER`, or `RIGHT`, which is written back the same way.
+    pub(crate) fn from_alignment_constant(token: Token) -> Option<Self> {
+        match token {
+            Token::TwoByte(0xEF, constant @ 0x92..=0x94) => Some(TIString {
+                data: vec![Token::OneByte(0x30 + (constant - 0x92))],
+                closed: true,
+                constant: true,
+            }),
+
+            _ => None,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -23,6 +59,63 @@ impl TIString {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+
+    /// The tokens in the string, without quotes.
+    pub fn data(&self) -> &[Token] {
+        &self.data
+    }
+
+    /// The string exactly as it was read: the opening quote, its tokens, and the closing quote
+    /// if there was one.
+    pub fn verbatim(&self) -> Vec<Token> {
+        if self.constant {
+            return self.reconstruct_constant();
+        }
+
+        let mut tokens = Vec::with_capacity(self.data.len() + 2);
+        tokens.push(Token::OneByte(0x2A));
+        tokens.extend_from_slice(&self.data);
+        if self.closed {
+            tokens.push(Token::OneByte(0x2A));
+        }
+
+        tokens
+    }
+
+    /// The result of `self+other`. Strings hold tokens, so this never splits a two-byte token.
+    pub fn concat(&self, other: &TIString) -> TIString {
+        TIString::new([self.data.as_slice(), other.data.as_slice()].concat())
+    }
+
+    /// `LEFT`, `CENTER`, and `RIGHT` are the strings `"0"`, `"1"`, and `"2"`. Returns the one
+    /// equal to this string, if any. These tokens only exist on newer calculators, so check that
+    /// the target has it before writing the string this way.
+    pub fn alignment_constant(&self) -> Option<Token> {
+        match self.data.as_slice() {
+            [Token::OneByte(digit @ 0x30..=0x32)] => {
+                Some(Token::TwoByte(0xEF, 0x92 + (digit - 0x30)))
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Writes the string as its [`TIString::alignment_constant`] from now on. Returns false, and
+    /// changes nothing, if it has none or is already written that way.
+    pub(crate) fn write_as_alignment_constant(&mut self) -> bool {
+        if self.constant || self.alignment_constant().is_none() {
+            return false;
+        }
+
+        self.constant = true;
+        true
+    }
+
+    fn reconstruct_constant(&self) -> Vec<Token> {
+        vec![self
+            .alignment_constant()
+            .expect("only one-digit strings are written as alignment constants")]
+    }
 }
 
 impl Parse for TIString {
@@ -35,16 +128,25 @@ impl Parse for TIString {
             .peeking_take_while(|tok| !matches!(tok, Token::OneByte(0x04 | 0x3F | 0x2A))) // ->, \n, "
             .collect();
 
-        if let Some(Token::OneByte(0x2A)) = more.peek() {
+        let closed = more.peek() == Some(Token::OneByte(0x2A));
+        if closed {
             more.next();
         }
 
-        Ok(Some(TIString::new(data)))
+        Ok(Some(TIString {
+            data,
+            closed,
+            constant: false,
+        }))
     }
 }
 
 impl Reconstruct for TIString {
     fn reconstruct(&self, _config: &Config) -> Vec<Token> {
+        if self.constant {
+            return self.reconstruct_constant();
+        }
+
         let mut tokens = Vec::with_capacity(self.data.len() + 2);
         tokens.push(Token::OneByte(0x2A));
         tokens.extend_from_slice(&self.data);
@@ -54,8 +156,11 @@ impl Reconstruct for TIString {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse::Program;
+    use test_files::{test_tokenizer, test_version};
 
     #[test]
     fn parse_quote_terminated_string() {
@@ -73,4 +178,25 @@ mod tests {
             .any(|&t| matches!(t, Token::OneByte(0x2A))));
         assert_eq!(result.data.len(), 13);
     }
+
+    #[test]
+    fn newline_terminates_string() {
+        let text = "Disp \"A:B\nDisp \"C";
+
+        let (mut tokens, _) = test_tokenizer!().tokenize("\"A:B\nDisp \"C").unwrap();
+        let result = TIString::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(tokens.next(), Some(Token::OneByte(0x3F)));
+
+        let program = Program::from_text(text, test_version!());
+        assert_eq!(program.lines.len(), 2);
+
+        let (expected, _) = test_tokenizer!().tokenize(text).unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(test_version!())),
+            expected.collect::<Vec<_>>()
+        );
+    }
 }
@@ -4,7 +4,7 @@ use crate::parse::Reconstruct;
 use crate::Config;
 use titokens::Token;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UnOp {
     pub kind: Token,
     pub child: Box<Expression>,
@@ -30,10 +30,10 @@ impl Reconstruct for UnOp {
         if self.kind == Token::OneByte(0xB0) {
             result.push(self.kind);
             match *self.child {
-                Expression::Operator(Operator::Binary(BinOp { kind, .. })) if !matches!(kind, Token::OneByte(0x82 | 0x83)) /* mul, div */ => {
+                Expression::Operator(Operator::Binary(ref binop)) if !matches!(binop.kind, Token::OneByte(0x82 | 0x83)) /* mul, div */ && binop.precedence() < 70 /* ^, xroot */ => {
                     result.push(Token::OneByte(0x10)); // (
                     result.extend(self.child.reconstruct(config));
-                    result.push(Token::OneByte(0x10)); // )
+                    result.push(Token::OneByte(0x11)); // )
                 },
                 _ => result.extend(self.child.reconstruct(config)),
             }
@@ -3,13 +3,16 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Copy, Clone, Debug)]
+/// A window, table, or finance variable: `Xmin`, `ΔX`, `TblStart`, `PMT`, ...
+///
+/// This is every token in the `0x63` table except `TblInput`, which is a list.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct WindowVarName(Token);
 
 impl Parse for WindowVarName {
     fn parse(token: Token, _more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         Ok(match token {
-            Token::TwoByte(0x63, 0x00..=0x2A | 0x32..=0x38) => Some(WindowVarName(token)),
+            Token::TwoByte(0x63, 0x00..=0x29 | 0x2B..=0x38) => Some(WindowVarName(token)),
 
             _ => None,
         })
@@ -21,3 +24,53 @@ impl Reconstruct for WindowVarName {
         vec![self.0]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::components::{Operand, StoreTarget};
+    use crate::parse::expression::Expression;
+    use crate::parse::statements::Statement;
+    use test_files::{test_tokenizer, test_version};
+
+    #[test]
+    fn every_variable_round_trips() {
+        let config = test_version!().into();
+
+        for byte in (0x00..=0x38).filter(|&byte| byte != 0x2A) {
+            let token = Token::TwoByte(0x63, byte);
+            let tokens = vec![Token::OneByte(0x31), Token::OneByte(0x04), token];
+
+            let mut more = Tokens::from_vec(tokens.clone(), Some(test_version!()));
+            let statement = Statement::parse(more.next().unwrap(), &mut more)
+                .unwrap()
+                .unwrap();
+
+            assert!(
+                matches!(statement, Statement::Store(_, StoreTarget::WindowVar(_))),
+                "{byte:#04x}"
+            );
+            assert_eq!(statement.reconstruct(&config), tokens);
+
+            let mut more = Tokens::from_vec(vec![token], Some(test_version!()));
+            let expr = Expression::parse(more.next().unwrap(), &mut more)
+                .unwrap()
+                .unwrap();
+
+            assert!(
+                matches!(expr, Expression::Operand(Operand::WindowVarName(_))),
+                "{byte:#04x}"
+            );
+            assert_eq!(expr.reconstruct(&config), vec![token]);
+        }
+    }
+
+    #[test]
+    fn tbl_input_is_not_a_window_variable() {
+        let (mut tokens, _) = test_tokenizer!().tokenize("TblInput").unwrap();
+
+        assert!(WindowVarName::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! # 8xp Export
+//! Writes a program back out as an 8xp file, so that an 8xp can be optimized and sent straight
+//! back to a calculator.
+
+use std::fmt::{Display, Formatter};
+
+use titokens::ti_connect_file::TIProgram;
+use titokens::{Token, Tokens};
+
+use crate::parse::Program;
+use crate::Config;
+
+/// The most token data an 8xp can hold; its data length field (a `u16`) also counts the 19 bytes
+/// of the variable header.
+const MAX_DATA_LENGTH: usize = u16::MAX as usize - 19;
+
+/// Reasons [`Program::to_8xp_bytes`] can fail.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExportError {
+    /// Program names are one to eight uppercase letters, digits, or `θ`, and start with a letter
+    /// or `θ`.
+    InvalidName(String),
+    /// The reconstructed program has this many bytes, which is more than an 8xp can hold.
+    TooLarge(usize),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::InvalidName(name) => write!(
+                f,
+                "\"{name}\" is not a valid program name; use one to eight uppercase letters, \
+                digits, or θ, starting with a letter or θ"
+            ),
+            ExportError::TooLarge(size) => write!(
+                f,
+                "The program is {size} bytes, but an 8xp can hold at most {MAX_DATA_LENGTH} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// The eight bytes of `name` in an 8xp's variable header, padded with zeros.
+fn encode_name(name: &str) -> Result<[u8; 8], ExportError> {
+    let invalid = || ExportError::InvalidName(name.to_string());
+
+    let mut encoded = [0; 8];
+    for (idx, char) in name.chars().enumerate() {
+        let byte = match char {
+            'A'..='Z' => char as u8,
+            'θ' => 0x5B,
+            '0'..='9' if idx > 0 => char as u8,
+            _ => return Err(invalid()),
+        };
+
+        *encoded.get_mut(idx).ok_or_else(invalid)? = byte;
+    }
+
+    if encoded[0] == 0 {
+        return Err(invalid());
+    }
+
+    Ok(encoded)
+}
+
+impl Program {
+    /// Reconstructs this program with `config` and returns the bytes of an 8xp file holding it,
+    /// named `name`, like `"HELLO"`.
+    ///
+    /// The file is an unprotected, unarchived program with a `Created by tibo` comment.
+    pub fn to_8xp_bytes(&self, name: &str, config: &Config) -> Result<Vec<u8>, ExportError> {
+        let name = encode_name(name)?;
+
+        let tokens = self.reconstruct(config);
+        let size = tokens.iter().map(Token::byte_len).sum();
+        if size > MAX_DATA_LENGTH {
+            return Err(ExportError::TooLarge(size));
+        }
+
+        let mut file = TIProgram::new(name);
+        file.update_tokens(Tokens::from_vec(tokens, Some(config.mrov.clone())));
+
+        Ok(file.write())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    #[test]
+    fn to_8xp_bytes() {
+        let config = Config::from(test_version!());
+        let program = Program::from_text("Disp 1\nGoto A\nLbl A", test_version!());
+
+        let bytes = program.to_8xp_bytes("θHELLO2", &config).unwrap();
+        let file = TIProgram::read(&bytes).unwrap();
+        assert_eq!(file.name, *b"[HELLO2\0");
+        assert_eq!(
+            file.read_tokens().collect::<Vec<_>>(),
+            program.reconstruct(&config)
+        );
+    }
+
+    #[test]
+    fn names() {
+        let config = Config::from(test_version!());
+        let program = Program::from_text("Disp 1", test_version!());
+
+        for name in ["", "2A", "hello", "ABCDEFGHI", "A B"] {
+            assert_eq!(
+                program.to_8xp_bytes(name, &config),
+                Err(ExportError::InvalidName(name.to_string()))
+            );
+        }
+        assert!(program.to_8xp_bytes("ABCDEFGH", &config).is_ok());
+    }
+}
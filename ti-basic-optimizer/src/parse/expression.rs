@@ -1,10 +1,10 @@
 use crate::error_reporting::TokenReport;
 use crate::parse::components::*;
-use crate::parse::{Parse, Reconstruct};
+use crate::parse::{parse_text, Parse, Reconstruct};
 use crate::Config;
-use titokens::{Token, Tokens};
+use titokens::{Token, Tokens, Version};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expression {
     Operator(Operator),
     Operand(Operand),
@@ -116,20 +116,7 @@ impl<'a> Builder<'a> {
         if let Some(operand) = Operand::parse(next, self.tokens)? {
             self.check_implicit_mul()?;
 
-            self.emit_operand(operand.clone());
-
-            if let Some(Token::OneByte(0x10)) = self.tokens.peek() {
-                // (
-                if let Operand::Ans = &operand {
-                    Err(TokenReport::new(
-                        self.tokens.current_position(),
-                        "Unsupported Ans( operation",
-                        None,
-                    ))?
-                }
-            }
-
-            self.implicit_mul_allowed = true;
+            self.emit_operand(operand);
 
             Ok(true)
         } else if let Some(func) = FunctionCall::parse(next, self.tokens)? {
@@ -195,12 +182,15 @@ impl<'a> Builder<'a> {
 
         self.implicit_mul_allowed = false;
 
-        while self.operator_stack.last().is_some_and(
-            |tok| {
+        while self.operator_stack.last().is_some_and(|tok| match tok {
+            // negation binds more loosely than ^ and xroot, but more tightly than everything else
+            Token::OneByte(0xB0) => precedence < 70,
+            _ => {
                 UnOp::recognize(*tok)
                     || (BinOp::recognize_precedence(*tok).unwrap_or(0) >= precedence)
-            }, // always false if not BinOp
-        ) {
+                // always false if not BinOp
+            }
+        }) {
             let token = self.operator_stack.pop().unwrap();
 
             self.process_operator(token)?;
@@ -289,6 +279,91 @@ impl Reconstruct for Expression {
     }
 }
 
+impl Expression {
+    /// Tokenize and parse `text` as exactly one expression. Anything left over after the
+    /// expression, including a newline, is an error.
+    pub fn from_text(text: &str, version: Version) -> Result<Expression, TokenReport> {
+        parse_text(text, &Config::from(version), "an expression")
+    }
+
+    /// Calls `f` on every subexpression of this expression, children before parents, ending with
+    /// this expression itself.
+    pub fn walk(&self, f: &mut impl FnMut(&Expression)) {
+        match self {
+            Expression::Operator(Operator::Binary(binop)) => {
+                binop.left.walk(f);
+                binop.right.walk(f);
+            }
+            Expression::Operator(Operator::Unary(unop)) => unop.child.walk(f),
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                call.arguments.iter().for_each(|arg| arg.walk(f));
+            }
+            Expression::Operand(Operand::ListAccess(access)) => access.index.walk(f),
+            Expression::Operand(Operand::MatrixAccess(access)) => {
+                access.row.walk(f);
+                access.col.walk(f);
+            }
+            Expression::Operand(Operand::EquationAccess(access)) => access.index.walk(f),
+            Expression::Operand(Operand::Rand(Rand { count: Some(count) })) => count.walk(f),
+            Expression::Operand(Operand::ListLiteral(list)) => {
+                list.entries.iter().for_each(|entry| entry.walk(f));
+            }
+            Expression::Operand(Operand::Piecewise(piecewise)) => {
+                for (value, condition) in piecewise.pairs.iter() {
+                    value.walk(f);
+                    condition.walk(f);
+                }
+                if let Some(otherwise) = &piecewise.otherwise {
+                    otherwise.walk(f);
+                }
+            }
+            Expression::Operand(Operand::Expression(expr)) => expr.walk(f),
+
+            _ => {}
+        }
+
+        f(self);
+    }
+
+    /// Like [`Expression::walk`], but `f` may change each subexpression.
+    pub fn walk_mut(&mut self, f: &mut impl FnMut(&mut Expression)) {
+        match self {
+            Expression::Operator(Operator::Binary(binop)) => {
+                binop.left.walk_mut(f);
+                binop.right.walk_mut(f);
+            }
+            Expression::Operator(Operator::Unary(unop)) => unop.child.walk_mut(f),
+            Expression::Operator(Operator::FunctionCall(call)) => {
+                call.arguments.iter_mut().for_each(|arg| arg.walk_mut(f));
+            }
+            Expression::Operand(Operand::ListAccess(access)) => access.index.walk_mut(f),
+            Expression::Operand(Operand::MatrixAccess(access)) => {
+                access.row.walk_mut(f);
+                access.col.walk_mut(f);
+            }
+            Expression::Operand(Operand::EquationAccess(access)) => access.index.walk_mut(f),
+            Expression::Operand(Operand::Rand(Rand { count: Some(count) })) => count.walk_mut(f),
+            Expression::Operand(Operand::ListLiteral(list)) => {
+                list.entries.iter_mut().for_each(|entry| entry.walk_mut(f));
+            }
+            Expression::Operand(Operand::Piecewise(piecewise)) => {
+                for (value, condition) in piecewise.pairs.iter_mut() {
+                    value.walk_mut(f);
+                    condition.walk_mut(f);
+                }
+                if let Some(otherwise) = &mut piecewise.otherwise {
+                    otherwise.walk_mut(f);
+                }
+            }
+            Expression::Operand(Operand::Expression(expr)) => expr.walk_mut(f),
+
+            _ => {}
+        }
+
+        f(self);
+    }
+}
+
 impl<T: Into<Operand>> From<T> for Box<Expression> {
     fn from(value: T) -> Self {
         Box::new(Expression::Operand(value.into()))
@@ -363,4 +438,78 @@ mod tests {
         iverson_bracket,
         "/snippets/parsing/formulas/iverson-bracket.txt"
     );
+
+    #[test]
+    fn ans_access() {
+        let tokenizer = test_files::test_tokenizer!();
+
+        for (text, matrix) in [("Ans(2)+1", false), ("Ans(1,2)+1", true)] {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let data = tokens.clone().collect::<Vec<_>>();
+
+            let builder = Builder::new(&mut tokens);
+            let expr = builder.build().unwrap().unwrap();
+            assert_eq!(expr.reconstruct(&test_version!().into()), data);
+
+            let Expression::Operator(Operator::Binary(BinOp { left, .. })) = expr else {
+                panic!("Expected an addition in {text}.");
+            };
+            if matrix {
+                assert!(matches!(
+                    *left,
+                    Expression::Operand(Operand::MatrixAccess(_))
+                ));
+            } else {
+                assert!(matches!(*left, Expression::Operand(Operand::ListAccess(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn implicit_mul_before_function() {
+        for case in [
+            "number-function.txt",
+            "variable-function.txt",
+            "list-function.txt",
+        ] {
+            let data =
+                load_test_data(&("/snippets/parsing/implicit-multiplication/".to_string() + case));
+            let mut tokens = data.clone();
+
+            let builder = Builder::new(&mut tokens);
+            let expr = builder.build().unwrap().unwrap();
+
+            let mut reconstructed = expr.reconstruct(&test_version!().into());
+            Expression::strip_closing_parenthesis(&mut reconstructed);
+            assert_eq!(reconstructed, data.collect::<Vec<_>>());
+
+            let Expression::Operator(Operator::Binary(BinOp { kind, left, right })) = expr else {
+                panic!("Expected an implicit multiplication in {case}.");
+            };
+
+            assert_eq!(kind, Token::OneByte(0x82)); // *
+            assert!(matches!(*left, Expression::Operand(_)));
+            assert!(matches!(
+                *right,
+                Expression::Operator(Operator::FunctionCall(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn from_text() {
+        let expr = Expression::from_text("2+sqrt(A", test_version!()).unwrap();
+        assert!(matches!(
+            expr,
+            Expression::Operator(Operator::Binary(BinOp {
+                kind: Token::OneByte(0x70),
+                ..
+            }))
+        ));
+
+        assert!(Expression::from_text("", test_version!()).is_err());
+        assert!(Expression::from_text("Disp 2", test_version!()).is_err());
+        assert!(Expression::from_text("2+A\n", test_version!()).is_err());
+        assert!(Expression::from_text("2+A->B", test_version!()).is_err());
+    }
 }
@@ -0,0 +1,76 @@
+//! # Listings
+//! Calculators show every line flush left, which makes nested blocks hard to follow when reading
+//! a program. A listing is the program's text with each line indented by how deeply it is nested,
+//! for reading only: the indentation is not part of the program and can't be sent back to a
+//! calculator.
+
+use crate::parse::{statements::Statement, Program, Reconstruct};
+use crate::Config;
+
+impl Program {
+    /// The text of the program, one statement per line, with each line indented by `indent` spaces
+    /// for every [lexical block](Program::lexical_blocks) it is inside. Lines which open or close
+    /// a block (including an `Else`) line up with the lines around the block.
+    ///
+    /// Statements are written exactly as [`Program::reconstruct`] would write them with `config`.
+    pub fn to_listing(&self, config: &Config, indent: usize) -> String {
+        let tokenizer = config.tokenizer();
+        let (blocks, eof_abusers) = self.block_failure_paths();
+
+        let depth = |idx: usize| {
+            blocks
+                .iter()
+                .filter(|&(&start, &end)| {
+                    // the last line of a block without an `End` is still inside it
+                    let closed = !eof_abusers.contains(&start);
+
+                    start < idx && (idx + 1 < end || !closed && idx < end)
+                })
+                .count()
+        };
+
+        let mut listing = vec![];
+        for (idx, line) in self.statements() {
+            if matches!(line, Statement::Fiction(_)) {
+                continue;
+            }
+
+            let prefix = " ".repeat(indent * depth(idx));
+            let text = tokenizer.stringify(&line.reconstruct(config)).to_string();
+
+            // `If`-`Then` is one statement written on two lines
+            listing.extend(text.split('\n').map(|text| format!("{prefix}{text}")));
+        }
+
+        listing.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    #[test]
+    fn to_listing() {
+        let text = "If A\nThen\nDisp 1\nElse\nWhile B\nB-1->B\nEnd\nEnd\nRepeat C\ngetKey->C";
+        let program = Program::from_text(text, test_version!());
+
+        assert_eq!(
+            program.to_listing(&Config::from(test_version!()), 2),
+            [
+                "If A",
+                "Then",
+                "  Disp 1",
+                "Else",
+                "  While B",
+                "    B-1->B",
+                "  End",
+                "End",
+                "Repeat C",
+                "  getKey->C",
+            ]
+            .join("\n")
+        );
+    }
+}
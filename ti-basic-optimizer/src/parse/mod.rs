@@ -2,11 +2,18 @@ use itertools::Itertools;
 use titokens::{Token, Tokens};
 
 pub mod components;
+#[cfg(feature = "deku-8xp")]
+mod export;
 pub mod expression;
+mod listing;
 mod program;
+#[cfg(feature = "sexpr")]
+mod sexpr;
 pub mod statements;
 
-pub use program::Program;
+#[cfg(feature = "deku-8xp")]
+pub use export::ExportError;
+pub use program::{Program, SeparatorKind};
 
 use crate::{error_reporting::TokenReport, Config};
 
@@ -14,6 +21,42 @@ pub(crate) trait Parse: Sized {
     fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport>;
 }
 
+/// Tokenize `text` and parse exactly one `T` from it, for [`Expression::from_text`] and
+/// [`Statement::from_text`]. `what` describes a `T` in error messages.
+///
+/// [`Expression::from_text`]: expression::Expression::from_text
+/// [`Statement::from_text`]: statements::Statement::from_text
+pub(crate) fn parse_text<T: Parse>(
+    text: &str,
+    config: &Config,
+    what: &str,
+) -> Result<T, TokenReport> {
+    let (mut tokens, _) = config
+        .tokenizer()
+        .tokenize(text)
+        .map_err(|_| TokenReport::new(0, "Failed to tokenize input", None))?;
+
+    let parsed = match tokens.next() {
+        Some(first) => T::parse(first, &mut tokens)?,
+        None => None,
+    }
+    .ok_or_else(|| {
+        TokenReport::new(0, &format!("Expected {what}"), Some("Parsing failed here."))
+    })?;
+
+    if tokens.peek().is_some() {
+        Err(TokenReport::new(
+            tokens.current_position(),
+            "Unexpected tokens",
+            Some(&format!(
+                "Only {what} was expected; remove everything after it."
+            )),
+        ))?
+    }
+
+    Ok(parsed)
+}
+
 pub(crate) trait Reconstruct {
     fn reconstruct(&self, config: &Config) -> Vec<Token>;
 }
@@ -1,31 +1,109 @@
-use itertools::Itertools;
+use std::cell::OnceCell;
+use std::collections::BTreeSet;
 
+use crate::analyze::ControlFlowLookup;
 use crate::error_reporting::{Report, TokenReport};
+use crate::optimize::ChangeRecord;
 use crate::parse::statements::{ControlFlow, Statement};
 use crate::parse::{Parse, Reconstruct};
 use crate::Config;
-use titokens::{Token, Tokenizer, Tokens, Version};
+use std::fmt::{Display, Formatter};
+use titokens::{tokenizer::TokenBoundaries, Token, Tokenizer, Tokens, Version};
+
+/// The token which ended a line: `:` or a newline.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum SeparatorKind {
+    Colon,
+    #[default]
+    Newline,
+}
+
+impl SeparatorKind {
+    /// The kind of separator `token` is, if it is one.
+    pub fn of(token: Token) -> Option<SeparatorKind> {
+        match token {
+            Token::OneByte(0x3E) => Some(SeparatorKind::Colon),
+            Token::OneByte(0x3F) => Some(SeparatorKind::Newline),
+            _ => None,
+        }
+    }
+
+    pub fn token(self) -> Token {
+        match self {
+            SeparatorKind::Colon => Token::OneByte(0x3E),
+            SeparatorKind::Newline => Token::OneByte(0x3F),
+        }
+    }
+}
 
 pub struct Program {
     pub lines: Vec<Statement>,
+
+    /// The separator which ended each line in the original program, in step with
+    /// [`Program::lines`]. The last line has one too, even if the program ended there.
+    ///
+    /// Passes which move lines around should move their separators along with them.
+    pub separators: Vec<SeparatorKind>,
+
+    /// The tokens each line was parsed from, in step with [`Program::lines`], without the
+    /// separator which ended the line. See [`Program::changed_lines`].
+    ///
+    /// Like [`Program::separators`], passes which move lines around should move these too.
+    pub original_lines: Vec<Vec<Token>>,
+
+    /// The position of the first token of each line in the tokens it was parsed from, in step
+    /// with [`Program::lines`], for reporting problems found after parsing. See
+    /// [`Program::lint`].
+    pub original_positions: Vec<usize>,
+
+    /// Notes left by optimization passes while [`Program::optimize`] runs with
+    /// [`Config::explain`]; `None` otherwise.
+    pub(crate) change_log: Option<Vec<ChangeRecord>>,
+
+    /// Cached analysis; see [`Program::invalidate_analyses`].
+    pub(crate) control_flow_lookup: OnceCell<Box<ControlFlowLookup>>,
+
+    /// The text this program was parsed from, if known.
+    source: Option<TokenBoundaries>,
 }
 
+/// Clones the lines, separators, original lines and positions, and source; cached analyses are
+/// recomputed as needed.
+impl Clone for Program {
+    fn clone(&self) -> Self {
+        Program {
+            lines: self.lines.clone(),
+            separators: self.separators.clone(),
+            original_lines: self.original_lines.clone(),
+            original_positions: self.original_positions.clone(),
+            change_log: self.change_log.clone(),
+            control_flow_lookup: OnceCell::new(),
+            source: self.source.clone(),
+        }
+    }
+}
+
+/// Two programs are equal if their lines are structurally equal and separated the same way. Where
+/// they were parsed from is not compared, so a program equals a copy of itself which was written
+/// out and parsed again.
+impl PartialEq for Program {
+    fn eq(&self, other: &Self) -> bool {
+        self.lines == other.lines && self.separators == other.separators
+    }
+}
+
+impl Eq for Program {}
+
 impl Program {
     pub fn from_text(text: &str, version: Version) -> Self {
-        let tokenizer = Tokenizer::new(version, "en");
-        if let Ok((mut tokens, boundaries)) = tokenizer.tokenize(text) {
-            match Program::parse(&mut tokens) {
-                Ok(prog) => prog,
-                Err(report) => {
-                    report.report(boundaries);
+        Program::from_text_with_config(text, &Config::from(version))
+    }
 
-                    if cfg!(test) {
-                        panic!("Error thrown; aborting.");
-                    } else {
-                        std::process::exit(1);
-                    }
-                }
-            }
+    /// Like [`Program::from_text`], but reads token names in [`Config::lang`].
+    pub fn from_text_with_config(text: &str, config: &Config) -> Self {
+        let tokenizer = config.tokenizer();
+        if let Ok((mut tokens, boundaries)) = tokenizer.tokenize(text) {
+            Program::from_tokens_with_source(&mut tokens, boundaries)
         } else {
             eprintln!("Failed to tokenize input file.");
             if cfg!(test) {
@@ -36,8 +114,26 @@ impl Program {
         }
     }
 
-    pub fn from_tokens(tokens: &mut Tokens, tokenizer: &Tokenizer) -> Self {
+    /// Parse `tokens`, which were produced by tokenizing the text in `boundaries`.
+    ///
+    /// Errors are reported against that text, and it is kept as [`Program::source`].
+    pub fn from_tokens_with_source(tokens: &mut Tokens, boundaries: TokenBoundaries) -> Self {
         match Program::parse(tokens) {
+            Ok(prog) => prog.with_source(boundaries),
+            Err(report) => {
+                report.report(boundaries);
+
+                if cfg!(test) {
+                    panic!("Error thrown; aborting.");
+                } else {
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    pub fn from_tokens(tokens: &mut Tokens, tokenizer: &Tokenizer) -> Self {
+        match Program::try_from_tokens(tokens) {
             Ok(prog) => prog,
             Err(report) => {
                 let boundaries = tokens.stringify_with_boundaries(tokenizer);
@@ -52,10 +148,27 @@ impl Program {
         }
     }
 
+    /// Like [`Program::from_tokens`], but returns parse errors instead of reporting them and
+    /// exiting.
+    pub fn try_from_tokens(tokens: &mut Tokens) -> Result<Program, TokenReport> {
+        Program::parse(tokens)
+    }
+
     fn parse(tokens: &mut Tokens) -> Result<Program, TokenReport> {
         let mut lines: Vec<Statement> = vec![];
+        let mut separators: Vec<SeparatorKind> = vec![];
+        let mut original_lines: Vec<Vec<Token>> = vec![];
+        let mut original_positions: Vec<usize> = vec![];
+
+        let first_position = tokens.current_position();
+        let source = tokens.clone().collect::<Vec<_>>();
+        // `Tokens` counts reads past the end as positions, so `end` may be past the last token
+        let original = |start: usize, end: usize| {
+            source[start - first_position..(end - first_position).min(source.len())].to_vec()
+        };
 
         let mut line_number = 1;
+        let mut previous_start = first_position;
         while let Some(next) = tokens.next() {
             match next {
                 Token::OneByte(0x3E) => continue,
@@ -66,12 +179,21 @@ impl Program {
                 _ => {}
             }
 
+            let start = tokens.current_position() - 1;
             if let Some(statement) = Statement::parse(next, tokens)? {
+                let mut line = original(start, tokens.current_position());
+                let mut position = start;
+
                 // coalesce if-then into IfThen (we can't do this in `ControlFlow::parse` because it would break our line counter)
                 if let Statement::ControlFlow(ControlFlow::Then) = statement {
                     if let Some(Statement::ControlFlow(ControlFlow::If(cond))) = lines.pop() {
                         lines.push(Statement::ControlFlow(ControlFlow::IfThen(cond.clone())));
-                        continue;
+                        separators.pop();
+
+                        original_lines.pop();
+                        original_positions.pop();
+                        line = original(previous_start, tokens.current_position());
+                        position = previous_start;
                     } else {
                         Err(TokenReport::new(
                             tokens.current_position() - 1,
@@ -79,9 +201,19 @@ impl Program {
                             Some("Add an If Statement before the Then to form an If-Then"),
                         ))?
                     }
+                } else {
+                    lines.push(statement);
                 }
 
-                lines.push(statement);
+                separators.push(
+                    tokens
+                        .peek()
+                        .and_then(SeparatorKind::of)
+                        .unwrap_or_default(),
+                );
+                original_lines.push(line);
+                original_positions.push(position);
+                previous_start = start;
             }
 
             match tokens.peek() {
@@ -96,26 +228,237 @@ impl Program {
             }
         }
 
-        Ok(Program { lines })
+        Ok(Program {
+            lines,
+            separators,
+            original_lines,
+            original_positions,
+            change_log: None,
+            control_flow_lookup: OnceCell::new(),
+            source: None,
+        })
+    }
+
+    /// Attach the text this program was tokenized from, as returned by
+    /// [`Tokenizer::tokenize`](titokens::Tokenizer::tokenize), so that errors can point into
+    /// what the user actually wrote rather than a regenerated rendering of it.
+    pub fn with_source(mut self, boundaries: TokenBoundaries) -> Self {
+        self.source = Some(boundaries);
+
+        self
+    }
+
+    /// The text this program was parsed from, if known. See [`Program::with_source`].
+    pub fn source(&self) -> Option<&TokenBoundaries> {
+        self.source.as_ref()
+    }
+
+    /// Iterate over the statements in this program along with their line numbers. Lines which
+    /// have been removed (i.e. are [`Statement::None`]) are skipped, so line numbers are stable
+    /// across removals.
+    pub fn statements(&self) -> impl Iterator<Item = (usize, &Statement)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Statement::None))
+    }
+
+    /// Like [`Program::statements`], but allows the statements to be modified. Cached analyses are
+    /// discarded.
+    pub fn statements_mut(&mut self) -> impl Iterator<Item = (usize, &mut Statement)> {
+        self.invalidate_analyses();
+
+        self.lines
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Statement::None))
+    }
+
+    /// The statement on line `idx`, if there is one. Removed lines have no statement.
+    pub fn statement(&self, idx: usize) -> Option<&Statement> {
+        self.lines
+            .get(idx)
+            .filter(|line| !matches!(line, Statement::None))
+    }
+
+    /// Discard all cached analyses. Passes which modify [`Program::lines`] must call this.
+    pub fn invalidate_analyses(&mut self) {
+        self.control_flow_lookup.take();
     }
 
+    /// Reconstruct this program. Lines are separated as they were in the original program, or
+    /// as [`Config::separator`] says if it is set.
+    ///
+    /// A line which would leave a string open is always followed by a newline, since a `:` would
+    /// become part of the string.
+    ///
+    /// [Fictional statements](Statement::Fiction) are skipped: they only exist for analysis, and
+    /// running them for real would change `Ans`.
     pub fn reconstruct(&self, config: &Config) -> Vec<Token> {
-        // We choose to exclusively output 0x3F as a newline character because it means we never have
-        // to worry about closing strings.
+        let mut tokens = vec![];
+        let mut separator = None;
+
+        for (idx, line) in self.statements() {
+            if matches!(line, Statement::Fiction(_)) {
+                continue;
+            }
+
+            tokens.extend(separator.map(SeparatorKind::token));
+
+            let line = line.reconstruct(config);
+            // a colon after an unclosed string would become part of the string
+            separator = Some(if leaves_string_open(&line) {
+                SeparatorKind::Newline
+            } else {
+                config
+                    .separator
+                    .or_else(|| self.separators.get(idx).copied())
+                    .unwrap_or_default()
+            });
+
+            tokens.extend(line);
+        }
+
+        tokens
+    }
+
+    /// Like [`Program::reconstruct`], but fails if the output uses tokens which don't exist on
+    /// [`Config::mrov`], since the program would not run there. The error holds every such token.
+    /// `tokenizer` must be for [`Config::mrov`], like the one from [`Config::tokenizer`].
+    ///
+    /// See also: [`Program::validate_for_version`]
+    pub fn reconstruct_checked(
+        &self,
+        config: &Config,
+        tokenizer: &Tokenizer,
+    ) -> Result<Vec<Token>, BTreeSet<Token>> {
+        let tokens = self.reconstruct(config);
+
+        let unavailable = Program::validate_for_version(&tokens, tokenizer)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect::<BTreeSet<_>>();
+
+        if unavailable.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(unavailable)
+        }
+    }
+
+    /// Every token in `tokens` which doesn't exist on the version of `tokenizer`, with its
+    /// position, in order. Building a [`Tokenizer`] reads the whole token sheet, so callers
+    /// checking several programs should build it once.
+    ///
+    /// Reconstruction avoids newer tokens where it chooses between equivalents (like color
+    /// constants), but it writes out whatever else the program uses, so a program optimized for
+    /// an older model should be checked with this before it is sent to one.
+    pub fn validate_for_version(tokens: &[Token], tokenizer: &Tokenizer) -> Vec<(usize, Token)> {
+        tokens
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, token)| !tokenizer.is_available(token))
+            .collect()
+    }
+
+    /// True if [`Program::reconstruct`] gives exactly `original`.
+    pub fn reconstruct_unchanged(&self, original: &[Token], config: &Config) -> bool {
+        self.reconstruct(config) == original
+    }
+
+    /// The lines whose reconstruction differs from the tokens they were parsed from, in order.
+    /// Removed lines and lines added after parsing are included; fictional statements, which are
+    /// never output, are not.
+    ///
+    /// Reconstruction normalizes some things even before any optimization, like redundant
+    /// parentheses, so lines can show up here without a pass having touched them.
+    pub fn changed_lines(&self, config: &Config) -> Vec<usize> {
         self.lines
             .iter()
-            .filter(|x| !matches!(x, Statement::None))
-            .map(|line| line.reconstruct(config))
-            .intersperse(vec![Token::OneByte(0x3F)])
-            .flatten()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Statement::Fiction(_)))
+            .filter(|&(idx, line)| self.original_lines.get(idx) != Some(&line.reconstruct(config)))
+            .map(|(idx, _)| idx)
             .collect()
     }
+
+    /// Reconstruct this program, parse the result, and reconstruct it again. Both reconstructions
+    /// should be identical; if they are not, the returned error points at the first token where
+    /// they differ.
+    pub fn check_round_trip(&self, config: &Config) -> Result<(), TokenReport> {
+        let a = self.reconstruct(config);
+        let b = Program::parse(&mut Tokens::from_vec(a.clone(), Some(config.mrov.clone())))?
+            .reconstruct(config);
+
+        let mismatch = a
+            .iter()
+            .zip(&b)
+            .position(|(x, y)| x != y)
+            .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())));
+
+        if let Some(position) = mismatch {
+            Err(TokenReport::new(
+                position,
+                "Reconstruction changed after reparsing",
+                Some("Please report this."),
+            ))?
+        }
+
+        Ok(())
+    }
+
+    /// Displays the reconstructed program as source text.
+    ///
+    /// ```ignore
+    /// println!("{}", program.display(&tokenizer, &config));
+    /// ```
+    pub fn display<'a>(
+        &'a self,
+        tokenizer: &'a Tokenizer,
+        config: &'a Config,
+    ) -> impl Display + 'a {
+        ProgramDisplay {
+            program: self,
+            tokenizer,
+            config,
+        }
+    }
+}
+
+/// True if `line` ends inside a string, which is closed by a quote, a store arrow, or the end of
+/// the line.
+fn leaves_string_open(line: &[Token]) -> bool {
+    let mut in_string = false;
+    for token in line {
+        match token {
+            Token::OneByte(0x2A) => in_string = !in_string, // "
+            Token::OneByte(0x04) => in_string = false,      // ->
+            _ => {}
+        }
+    }
+
+    in_string
+}
+
+struct ProgramDisplay<'a> {
+    program: &'a Program,
+    tokenizer: &'a Tokenizer,
+    config: &'a Config,
+}
+
+impl Display for ProgramDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let tokens = self.program.reconstruct(self.config);
+
+        write!(f, "{}", self.tokenizer.stringify(&tokens))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use test_files::{load_test_data, test_tokenizer};
+    use test_files::{load_test_data, load_test_programs, test_tokenizer};
 
     #[test]
     fn parses_newlines_correctly_with_strings() {
@@ -133,6 +476,227 @@ mod tests {
         assert_eq!(program.lines.len(), 0);
     }
 
+    #[test]
+    fn statements() {
+        let mut program = Program::from_text("Disp 1\nDisp 2\nDisp 3", test_files::test_version!());
+        program.lines[1] = Statement::None;
+
+        assert_eq!(
+            program.statements().map(|(idx, _)| idx).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert!(program.statement(0).is_some());
+        assert!(program.statement(1).is_none());
+        assert!(program.statement(3).is_none());
+
+        program.control_flow_lookup().unwrap();
+        for (_, statement) in program.statements_mut() {
+            *statement = Statement::None;
+        }
+        assert!(program.control_flow_lookup.get().is_none());
+        assert_eq!(program.statements().count(), 0);
+    }
+
+    #[test]
+    fn changed_lines() {
+        let version = test_files::test_version!();
+        let config = Config::from(version.clone());
+        let text = "5->A\n5->A\n0->B\nDisp (1+2)\nIf A\nThen\nDisp 1\nEnd";
+
+        let mut program = Program::from_text(text, version);
+        assert_eq!(program.lines.len(), program.original_lines.len());
+        assert_eq!(program.changed_lines(&config), vec![3]);
+
+        program.optimize_redundant_stores(&config);
+        assert_eq!(program.changed_lines(&config), vec![1, 3]);
+
+        let (original, _) = test_tokenizer!().tokenize(text).unwrap();
+        let original = original.collect::<Vec<_>>();
+        assert!(!program.reconstruct_unchanged(&original, &config));
+
+        let program = Program::from_tokens(
+            &mut Tokens::from_vec(original.clone(), None),
+            &test_tokenizer!(),
+        );
+        assert_eq!(program.original_lines[4], original[19..23]);
+        assert_eq!(program.original_positions[4], 19);
+    }
+
+    #[test]
+    fn clone_and_eq() {
+        let version = test_files::test_version!();
+        let program = Program::from_text("5->A\nDisp A+1", version.clone());
+        program.control_flow_lookup().unwrap();
+
+        let mut copy = program.clone();
+        assert!(copy.control_flow_lookup.get().is_none());
+        assert!(program == copy);
+
+        let reparsed = Program::from_tokens(
+            &mut Tokens::from_vec(program.reconstruct(&Config::from(version.clone())), None),
+            &test_tokenizer!(),
+        );
+        assert!(program == reparsed);
+
+        assert!(program != Program::from_text("5->A:Disp A+1", version.clone()));
+        assert!(program != Program::from_text("5->A\nDisp 1+A", version));
+
+        copy.lines[1] = Statement::None;
+        assert!(program != copy);
+    }
+
+    #[test]
+    fn display() {
+        let tokenizer = test_tokenizer!();
+        let config = test_files::test_version!().into();
+        let program = Program::from_text("Disp 1\nDisp 2", test_files::test_version!());
+
+        assert_eq!(
+            program.display(&tokenizer, &config).to_string(),
+            "Disp 1\nDisp 2"
+        );
+    }
+
+    #[test]
+    fn separators() {
+        let tokenizer = test_tokenizer!();
+        let version = test_files::test_version!();
+        let program = Program::from_text("1->A:Disp A\nDisp \"B\":Disp 2:", version.clone());
+
+        assert_eq!(
+            program.separators,
+            vec![
+                SeparatorKind::Colon,
+                SeparatorKind::Newline,
+                SeparatorKind::Colon,
+                SeparatorKind::Colon
+            ]
+        );
+
+        let (expected, _) = tokenizer.tokenize("1->A:Disp A\nDisp \"B\nDisp 2").unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(version.clone())),
+            expected.collect::<Vec<_>>()
+        );
+
+        let config = Config {
+            separator: Some(SeparatorKind::Newline),
+            ..Config::from(version)
+        };
+        let (expected, _) = tokenizer
+            .tokenize("1->A\nDisp A\nDisp \"B\nDisp 2")
+            .unwrap();
+        assert_eq!(program.reconstruct(&config), expected.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unclosed_string_before_more_code() {
+        let version = test_files::test_version!();
+        let config = Config::from(version.clone());
+        let mut program = Program::from_text("Disp \"A\"+\"B\":Disp 2:\"C\"->Str1:Disp 3", version);
+        program.optimize_string_concatenation(&config);
+
+        // a colon after `Disp "AB` would be part of the string; a store arrow closes it
+        let (expected, _) = test_tokenizer!()
+            .tokenize("Disp \"AB\nDisp 2:\"C->Str1:Disp 3")
+            .unwrap();
+        assert_eq!(program.reconstruct(&config), expected.collect::<Vec<_>>());
+        assert!(program.check_round_trip(&config).is_ok());
+    }
+
+    #[test]
+    fn fiction_not_reconstructed() {
+        let version = test_files::test_version!();
+        let mut program = Program::from_text("Disp 1\n1->A\nDisp Ans", version.clone());
+        program.lines[1] = Statement::Fiction(Box::new(program.lines[1].clone()));
+
+        let (expected, _) = test_tokenizer!().tokenize("Disp 1\nDisp Ans").unwrap();
+        assert_eq!(
+            program.reconstruct(&Config::from(version)),
+            expected.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unavailable_tokens() {
+        let text = "Wait 1";
+        let program = Program::from_text(text, titokens::version::LATEST.clone());
+
+        let latest = Config::from(titokens::version::LATEST.clone());
+        assert!(program
+            .reconstruct_checked(&latest, &latest.tokenizer())
+            .is_ok());
+
+        let mono = Config::from(titokens::version::LATEST_MONO.clone());
+        assert_eq!(
+            program.reconstruct_checked(&mono, &mono.tokenizer()),
+            Err(BTreeSet::from([Token::TwoByte(0xEF, 0x96)]))
+        );
+    }
+
+    #[test]
+    fn validate_for_version() {
+        let text = "Disp 1\nWait 1\nWait 2";
+        let program = Program::from_text(text, titokens::version::LATEST.clone());
+        let tokens = program.reconstruct(&Config::from(titokens::version::LATEST.clone()));
+
+        let latest = Tokenizer::new(titokens::version::LATEST.clone(), "en");
+        assert!(Program::validate_for_version(&tokens, &latest).is_empty());
+
+        let mono = Tokenizer::new(titokens::version::LATEST_MONO.clone(), "en");
+        assert_eq!(
+            Program::validate_for_version(&tokens, &mono),
+            vec![
+                (3, Token::TwoByte(0xEF, 0x96)),
+                (6, Token::TwoByte(0xEF, 0x96))
+            ]
+        );
+    }
+
+    #[test]
+    fn source() {
+        let text = "1->A\nDisp A";
+        let program = Program::from_text(text, test_files::test_version!());
+
+        assert_eq!(program.source().unwrap().to_string(), text);
+
+        let mut tokens = load_test_data("/snippets/parsing/statements/disp.txt");
+        let program = Program::from_tokens(&mut tokens, &test_tokenizer!());
+
+        assert!(program.source().is_none());
+    }
+
+    /// Round-trip every program in the corpus. Programs which do not parse in the first place are
+    /// not round-trip failures, but they must be the ones listed here, so that a regression in the
+    /// parser can't hide a program from this test.
+    #[test]
+    fn corpus_round_trip() {
+        // line 2, `TextColor(Black`: color tokens are not parsed as expressions yet
+        const UNPARSEABLE: [&str; 1] = ["/programs/stick_hero/optimized.txt"];
+
+        let config = test_files::test_version!().into();
+
+        let mut failures = vec![];
+        let mut unparseable = vec![];
+        for (path, mut tokens) in load_test_programs() {
+            match Program::parse(&mut tokens) {
+                Ok(program) => {
+                    if let Err(report) = program.check_round_trip(&config) {
+                        failures.push(format!("{path}: {report:?}"));
+                    }
+                }
+                Err(_) => unparseable.push(path),
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "Round trip failed:\n{}",
+            failures.join("\n")
+        );
+        assert_eq!(unparseable, UNPARSEABLE);
+    }
+
     /// TI-Toolkit defines "round-trip" as the following process:
     /// 1. Import original
     /// 2. Export to file A
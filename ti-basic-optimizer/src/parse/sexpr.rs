@@ -0,0 +1,336 @@
+//! # S-Expressions
+//! Reconstructing a program shows its tokens, which hides the shape of the tree they were parsed
+//! into: `A+B*C` looks the same whether or not precedence was handled correctly. This renders the
+//! tree itself, like `(store (binop + (var A) (lit 5)) (target A))`, for debugging the parser.
+//!
+//! Names and literals are written as the tokens they reconstruct to, in quotes if they contain
+//! characters which would be confusing in an S-expression.
+
+use itertools::Itertools;
+use titokens::{version, Token, Tokenizer};
+
+use crate::parse::{
+    components::{DelVarTarget, ListIndex, MatrixIndex, Operand, Operator, StoreTarget, TIList},
+    expression::Expression,
+    statements::{
+        control_flow::{ForLoop, IsDs, Menu},
+        ControlFlow, DelVarChain, Generic, PlotArgument, Statement, UserInput,
+    },
+    Program, Reconstruct,
+};
+use crate::Config;
+
+struct Writer {
+    config: Config,
+    tokenizer: Tokenizer,
+}
+
+fn list(head: &str, items: impl IntoIterator<Item = String>) -> String {
+    let items = items.into_iter().collect::<Vec<_>>();
+
+    if items.is_empty() {
+        format!("({head})")
+    } else {
+        format!("({head} {})", items.join(" "))
+    }
+}
+
+impl Writer {
+    fn atom(&self, tokens: &[Token]) -> String {
+        let text = self.tokenizer.stringify(tokens).to_string();
+
+        if text.is_empty() || text.contains(|c: char| c.is_whitespace() || "()\"\\;".contains(c)) {
+            format!("{text:?}")
+        } else {
+            text
+        }
+    }
+
+    fn leaf(&self, head: &str, item: &impl Reconstruct) -> String {
+        list(head, [self.atom(&item.reconstruct(&self.config))])
+    }
+
+    fn program(&self, program: &Program) -> String {
+        let lines = program
+            .statements()
+            .map(|(_, statement)| format!("\n  {}", self.statement(statement)))
+            .join("");
+
+        format!("(program{lines})")
+    }
+
+    fn statement(&self, statement: &Statement) -> String {
+        match statement {
+            Statement::None => list("none", []),
+            Statement::ControlFlow(control_flow) => self.control_flow(control_flow),
+            Statement::Generic(Generic { kind, arguments }) => list(
+                "command",
+                std::iter::once(self.atom(&[*kind])).chain(self.expressions(arguments)),
+            ),
+            Statement::DelVarChain(DelVarChain { deletions, valence }) => list(
+                "delvar",
+                deletions
+                    .iter()
+                    .map(|deletion: &DelVarTarget| self.atom(&deletion.reconstruct(&self.config)))
+                    .chain(valence.iter().map(|statement| self.statement(statement))),
+            ),
+            Statement::SetUpEditor(editor) => list(
+                "setupeditor",
+                editor
+                    .lists
+                    .iter()
+                    .map(|name| self.atom(&name.reconstruct(&self.config))),
+            ),
+            Statement::StatPlot(plot) => list(
+                "plot",
+                std::iter::once(self.atom(&[plot.kind]))
+                    .chain(plot.plot_type.map(|plot_type| self.atom(&[plot_type])))
+                    .chain(plot.arguments.iter().map(|argument| match argument {
+                        PlotArgument::Expression(expr) => self.expression(expr),
+                        PlotArgument::Mark(mark) => list("mark", [self.atom(&[*mark])]),
+                        PlotArgument::Axis(axis) => list("axis", [self.atom(&[*axis])]),
+                    })),
+            ),
+            Statement::UserInput(UserInput::Input { prompt, target }) => list(
+                "input",
+                prompt
+                    .iter()
+                    .map(|prompt| self.expression(prompt))
+                    .chain(target.iter().map(|target| self.target(target))),
+            ),
+            Statement::UserInput(UserInput::Prompt(targets)) => {
+                list("prompt", targets.iter().map(|target| self.target(target)))
+            }
+            Statement::Expression(expr) => self.expression(expr),
+            Statement::Store(expr, target) => {
+                list("store", [self.expression(expr), self.target(target)])
+            }
+            Statement::ProgramInvocation(name) => list("prgm", [self.atom(&name.name)]),
+            Statement::Fiction(statement) => list("fiction", [self.statement(statement)]),
+        }
+    }
+
+    fn control_flow(&self, control_flow: &ControlFlow) -> String {
+        match control_flow {
+            ControlFlow::If(condition) => list("if", [self.expression(condition)]),
+            ControlFlow::IfThen(condition) => list("if-then", [self.expression(condition)]),
+            ControlFlow::Then => list("then", []),
+            ControlFlow::Else => list("else", []),
+            ControlFlow::While(condition) => list("while", [self.expression(condition)]),
+            ControlFlow::Repeat(condition) => list("repeat", [self.expression(condition)]),
+            ControlFlow::For(ForLoop {
+                iterator,
+                start,
+                end,
+                step,
+                ..
+            }) => list(
+                "for",
+                [iterator, start, end]
+                    .into_iter()
+                    .chain(step)
+                    .map(|expr| self.expression(expr)),
+            ),
+            ControlFlow::End => list("end", []),
+            ControlFlow::Return => list("return", []),
+            ControlFlow::Lbl(name) => list("lbl", [name.to_string()]),
+            ControlFlow::Goto(name) => list("goto", [name.to_string()]),
+            ControlFlow::Stop => list("stop", []),
+            ControlFlow::IsGt(IsDs {
+                variable,
+                condition,
+            }) => list(
+                "is>",
+                [
+                    self.atom(&variable.reconstruct(&self.config)),
+                    self.expression(condition),
+                ],
+            ),
+            ControlFlow::DsLt(IsDs {
+                variable,
+                condition,
+            }) => list(
+                "ds<",
+                [
+                    self.atom(&variable.reconstruct(&self.config)),
+                    self.expression(condition),
+                ],
+            ),
+            ControlFlow::Menu(Menu {
+                title,
+                option_titles,
+                option_labels,
+            }) => list(
+                "menu",
+                std::iter::once(self.expression(title)).chain(
+                    option_titles
+                        .iter()
+                        .zip(option_labels)
+                        .map(|(title, label)| {
+                            list("option", [self.expression(title), label.to_string()])
+                        }),
+                ),
+            ),
+        }
+    }
+
+    fn target(&self, target: &StoreTarget) -> String {
+        match target {
+            StoreTarget::ListIndex(index) => list("target", [self.list_index(index)]),
+            StoreTarget::MatrixIndex(index) => list("target", [self.matrix_index(index)]),
+
+            _ => self.leaf("target", target),
+        }
+    }
+
+    fn expressions<'a>(
+        &'a self,
+        exprs: impl IntoIterator<Item = &'a Expression> + 'a,
+    ) -> impl Iterator<Item = String> + 'a {
+        exprs.into_iter().map(|expr| self.expression(expr))
+    }
+
+    fn expression(&self, expr: &Expression) -> String {
+        match expr {
+            Expression::Operand(operand) => self.operand(operand),
+
+            Expression::Operator(Operator::Binary(binop)) => list(
+                "binop",
+                [
+                    self.atom(&[binop.kind]),
+                    self.expression(&binop.left),
+                    self.expression(&binop.right),
+                ],
+            ),
+            Expression::Operator(Operator::Unary(unop)) => list(
+                "unop",
+                [self.atom(&[unop.kind]), self.expression(&unop.child)],
+            ),
+            Expression::Operator(Operator::FunctionCall(call)) => list(
+                "call",
+                std::iter::once(self.atom(&[call.kind])).chain(self.expressions(&call.arguments)),
+            ),
+
+            Expression::Operator(operator) => self.leaf("operator", operator),
+        }
+    }
+
+    fn list_index(&self, index: &ListIndex) -> String {
+        list(
+            "index",
+            [
+                self.atom(&index.subject.reconstruct(&self.config)),
+                self.expression(&index.index),
+            ],
+        )
+    }
+
+    fn matrix_index(&self, index: &MatrixIndex) -> String {
+        list(
+            "index",
+            [
+                self.atom(&index.subject.reconstruct(&self.config)),
+                self.expression(&index.row),
+                self.expression(&index.col),
+            ],
+        )
+    }
+
+    fn operand(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::NumericVarName(name) => self.leaf("var", name),
+            Operand::ListName(name) => self.leaf("list", name),
+            Operand::MatrixName(name) => self.leaf("matrix", name),
+            Operand::StringName(name) => self.leaf("string", name),
+            Operand::EquationName(name) => self.leaf("equation", name),
+            Operand::WindowVarName(name) => self.leaf("window", name),
+            Operand::PseudoVariable(pseudo) => self.leaf("pseudo", pseudo),
+
+            Operand::ListAccess(index) => self.list_index(index),
+            Operand::MatrixAccess(index) => self.matrix_index(index),
+            Operand::EquationAccess(index) => list(
+                "index",
+                [
+                    self.atom(&index.subject.reconstruct(&self.config)),
+                    self.expression(&index.index),
+                ],
+            ),
+
+            Operand::Ans => list("ans", []),
+            Operand::I => list("i", []),
+            Operand::TblInput => list("tblinput", []),
+            Operand::Rand(rand) => list(
+                "rand",
+                rand.count.iter().map(|count| self.expression(count)),
+            ),
+
+            Operand::NumericLiteral(_) | Operand::Fraction(_) => self.leaf("lit", operand),
+            Operand::StringLiteral(string) => list(
+                "lit",
+                [format!(
+                    "{:?}",
+                    self.tokenizer.stringify(string.data()).to_string()
+                )],
+            ),
+            Operand::ListLiteral(TIList { entries }) => list("list-lit", self.expressions(entries)),
+
+            Operand::Piecewise(piecewise) => list(
+                "piecewise",
+                piecewise
+                    .pairs
+                    .iter()
+                    .map(|(value, condition)| {
+                        list("case", [self.expression(value), self.expression(condition)])
+                    })
+                    .chain(piecewise.otherwise.iter().map(|expr| self.expression(expr))),
+            ),
+
+            Operand::Expression(expr) => self.expression(expr),
+        }
+    }
+}
+
+impl Program {
+    /// Renders the parsed program as nested S-expressions, one line per statement. See the
+    /// [module documentation](self) for the format.
+    ///
+    /// Tokens are named in English as on the newest monochrome calculators, so that numbers are
+    /// never written as color names.
+    pub fn to_sexpr(&self) -> String {
+        let config = Config::from(version::LATEST_MONO.clone());
+        let writer = Writer {
+            tokenizer: config.tokenizer(),
+            config,
+        };
+
+        writer.program(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::test_version;
+
+    #[test]
+    fn to_sexpr() {
+        let program = Program::from_text(
+            "A+5->A\n2+3*B\nIf A=1\nDisp \"HI\",L1(2\nFor(I,1,10\nEnd",
+            test_version!(),
+        );
+
+        assert_eq!(
+            program.to_sexpr(),
+            [
+                "(program",
+                "  (store (binop + (var A) (lit 5)) (target A))",
+                "  (binop + (lit 2) (binop * (lit 3) (var B)))",
+                "  (if (binop = (var A) (lit 1)))",
+                "  (command \"Disp \" (lit \"HI\") (index L1 (lit 2)))",
+                "  (for (var I) (lit 1) (lit 10))",
+                "  (end))",
+            ]
+            .join("\n")
+        );
+    }
+}
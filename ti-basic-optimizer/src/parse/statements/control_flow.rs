@@ -7,6 +7,7 @@ pub use {for_loop::ForLoop, isds::IsDs, menu::Menu};
 use crate::error_reporting::{expect_some, next_or_err, TokenReport};
 use crate::parse::{expression::Expression, Parse, Reconstruct};
 use crate::Config;
+use std::fmt::{Display, Formatter};
 use std::iter::once;
 use titokens::{Token, Tokens};
 
@@ -82,6 +83,12 @@ impl LabelName {
         self.0
     }
 
+    /// The number of bytes this name takes up wherever it is written. Every token a label name
+    /// can use is one byte long.
+    pub fn byte_len(&self) -> usize {
+        self.tokens().len()
+    }
+
     fn tokens(&self) -> Vec<Token> {
         let mut data = vec![Token::OneByte((self.0 >> 8) as u8)];
         if self.0 & 0xFF != 0 {
@@ -92,6 +99,19 @@ impl LabelName {
     }
 }
 
+impl Display for LabelName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for token in self.tokens() {
+            match token.byte() {
+                0x5B => write!(f, "theta")?,
+                byte => write!(f, "{}", byte as char)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Parse for LabelName {
     fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         if !token.is_alphanumeric() {
@@ -117,7 +137,7 @@ impl Reconstruct for LabelName {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ControlFlow {
     If(Expression),
     IfThen(Expression),
@@ -183,7 +203,17 @@ impl Reconstruct for ControlFlow {
             ControlFlow::Menu(menu) => menu.reconstruct(version),
         };
 
-        Expression::strip_closing_parenthesis(&mut tokens);
+        // see `Program::optimize_for_loop_parentheses`
+        let keep_paren = matches!(
+            self,
+            ControlFlow::For(ForLoop {
+                has_ending_paren: true,
+                ..
+            })
+        );
+        if version.strip_trailing_parens && !keep_paren {
+            Expression::strip_closing_parenthesis(&mut tokens);
+        }
         if matches!(self, ControlFlow::IfThen(_)) {
             tokens.extend(vec![Token::OneByte(0x3F), Token::OneByte(0xCF)]);
         }
@@ -7,7 +7,7 @@ use std::iter::once;
 use tifloats::{tifloat, Float};
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ForLoop {
     pub iterator: Expression,
     pub start: Expression,
@@ -5,7 +5,7 @@ use crate::Config;
 use titokens::{Token, Tokens};
 
 // IsDs the real life?
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IsDs {
     pub variable: NumericVarName,
     pub condition: Expression,
@@ -6,13 +6,21 @@ use crate::Config;
 use std::iter::once;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Menu {
     pub title: Expression,
     pub option_titles: Vec<Expression>,
     pub option_labels: Vec<LabelName>,
 }
 
+impl Menu {
+    /// The labels this `Menu(` may jump to, in option order. A label appears once for each option
+    /// that jumps to it.
+    pub fn branch_targets(&self) -> &[LabelName] {
+        &self.option_labels
+    }
+}
+
 impl Parse for Menu {
     fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         if token != Token::OneByte(0xE6) {
@@ -6,7 +6,7 @@ use titokens::{Token, Tokens};
 
 /// `DelVar` statements do not require a trailing newline, and so a series of `deletions` can be
 /// chained back-to-back.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DelVarChain {
     pub deletions: Vec<DelVarTarget>,
     pub valence: Option<Box<Statement>>,
@@ -6,7 +6,7 @@ use crate::parse::{Parse, Reconstruct};
 use crate::Config;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Generic {
     pub kind: Token,
     pub arguments: Vec<Expression>,
@@ -62,9 +62,14 @@ impl Parse for Generic {
 }
 
 impl Reconstruct for Generic {
+    /// Commands like `Line(` get their closing parenthesis back, which the statement strips again
+    /// unless [`Config::strip_trailing_parens`] is off.
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
         use std::iter::once;
 
+        let closing_paren =
+            Generic::has_opening_parenthesis(self.kind) && !self.arguments.is_empty();
+
         once(self.kind)
             .chain(
                 self.arguments
@@ -73,6 +78,7 @@ impl Reconstruct for Generic {
                     .intersperse(vec![Token::OneByte(0x2B)])
                     .flatten(),
             )
+            .chain(closing_paren.then_some(Token::OneByte(0x11)))
             .collect()
     }
 }
@@ -197,8 +203,6 @@ impl Generic {
             | 0xBB69 // UnArchive
             | 0xBBCE // GarbageCollect
             | 0xD8 // Pause
-            | 0xDC // Input
-            | 0xDD // Prompt
             | 0xDE // Disp
             | 0xDF // DispGraph
             | 0xE0 // Output
@@ -211,9 +215,6 @@ impl Generic {
             | 0xE8 // Get
             | 0xE9 // PlotsOn
             | 0xEA // PlotsOff
-            | 0xEC // Plot1
-            | 0xED // Plot2
-            | 0xEE // Plot3
             | 0xEF0F // ClockOff
             | 0xEF10 // ClockOn
             | 0xEF11 // OpenLib
@@ -261,6 +262,37 @@ impl Generic {
         )
     }
 
+    /// Whether running this command might change `Ans`.
+    ///
+    /// Evaluating an expression or storing a value always sets `Ans`, but commands generally leave
+    /// it alone: `Disp`, `Output(`, drawing commands, and mode settings never touch it. The
+    /// exceptions are the commands which run code the optimizer can't see or receive variables from
+    /// elsewhere, and the statistics commands, which write a large number of system variables and
+    /// whose effect on `Ans` has not been checked on every OS version. All of these are assumed to
+    /// change `Ans` (and, for libraries, to read it).
+    ///
+    /// References:
+    /// - <http://tibasicdev.wikidot.com/ans>
+    /// - TI-84 Plus CE Guidebook, "Using Last Answer (Ans) as a Variable"
+    pub fn affects_ans(&self) -> bool {
+        matches!(
+            self.kind.into(),
+            0xBB53 // GetCalc(
+            | 0xE8 // Get(
+            | 0xBB58 // Select(
+            | 0xEF11 // OpenLib(
+            | 0xEF12 // ExecLib
+            | 0x2E..=0x2F // CubicReg QuartReg
+            | 0xBB32..=0xBB34 // SinReg Logistic LinRegTTest
+            | 0xBB3B..=0xBB44 // Z-Test( through 2-PropZInt(
+            | 0xBB46..=0xBB49 // 2-SampTTest 2-SampFTest TInterval 2-SampTInt
+            | 0xBB59 // ANOVA(
+            | 0xEF14..=0xEF16 // χ²GOF-Test( LinRegTInt Manual-Fit
+            | 0xF2..=0xF9 // 1-Var Stats through QuadReg
+            | 0xFF // LinReg(ax+b)
+        )
+    }
+
     fn accepts_parameters(token: Token) -> bool {
         matches!(token.into(),
               0x2Eu16..=0x2Fu16
@@ -275,10 +307,10 @@ impl Generic {
             | 0xBB68..=0xBB69
             | 0xBBCE
             | 0xD8
-            | 0xDC..=0xDE
+            | 0xDE
             | 0xE0
             | 0xE2..=0xE4
-            | 0xE7..=0xEE
+            | 0xE7..=0xEA
             | 0xEF11..=0xEF12
             | 0xEF14..=0xEF16
             | 0xEF5A..=0xEF5B
@@ -307,10 +339,32 @@ impl Generic {
             | 0xE0
             | 0xE2..=0xE4
             | 0xE7..=0xE8
-            | 0xEC..=0xEE
             | 0xEF11..=0xEF15
             | 0xEF65
             | 0xEF67
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(kind: Token) -> Generic {
+        Generic {
+            kind,
+            arguments: vec![],
+        }
+    }
+
+    #[test]
+    fn affects_ans() {
+        assert!(!command(Token::OneByte(0xDE)).affects_ans()); // Disp
+        assert!(!command(Token::OneByte(0xE1)).affects_ans()); // ClrHome
+        assert!(!command(Token::OneByte(0x64)).affects_ans()); // Radian
+
+        assert!(command(Token::TwoByte(0xEF, 0x12)).affects_ans()); // ExecLib
+        assert!(command(Token::OneByte(0xF2)).affects_ans()); // 1-Var Stats
+        assert!(command(Token::TwoByte(0xBB, 0x53)).affects_ans()); // GetCalc(
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error_reporting::{expect_some, expect_tok, next_or_err, TokenReport};
+use crate::parse::components::{Operand, StoreTarget};
+use crate::parse::expression::Expression;
+use crate::parse::{Parse, Reconstruct};
+use crate::Config;
+use itertools::Itertools;
+use titokens::{Token, Tokens};
+
+/// `Input` and `Prompt`, which ask the user for values and store them to variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UserInput {
+    /// `Input`, `Input A`, `Input "A=",A`, or `Input Str1,A`. With no target, `Input` lets the
+    /// user move a cursor on the graph screen and stores its coordinates to X and Y.
+    Input {
+        prompt: Option<Expression>,
+        target: Option<StoreTarget>,
+    },
+    /// `Prompt A,B,...`
+    Prompt(Vec<StoreTarget>),
+}
+
+impl UserInput {
+    fn parse_target(token: Token, more: &mut Tokens) -> Result<StoreTarget, TokenReport> {
+        expect_some!(
+            StoreTarget::parse(token, more)?,
+            more,
+            "a variable",
+            "Input and Prompt store to variables."
+        )
+    }
+
+    fn parse_input(more: &mut Tokens) -> Result<Self, TokenReport> {
+        if matches!(more.peek(), Some(Token::OneByte(0x3E | 0x3F)) | None) {
+            return Ok(UserInput::Input {
+                prompt: None,
+                target: None,
+            });
+        }
+
+        let first = next_or_err!(more)?;
+        if first == Token::OneByte(0x2A) {
+            // "
+            let prompt = expect_some!(Expression::parse(first, more)?, more, "a prompt")?;
+            expect_tok!(
+                more,
+                Token::OneByte(0x2B),
+                "Expected a comma.",
+                "Input with a prompt needs a variable to store to."
+            )?;
+            let target = Self::parse_target(next_or_err!(more)?, more)?;
+
+            return Ok(UserInput::Input {
+                prompt: Some(prompt),
+                target: Some(target),
+            });
+        }
+
+        let first_position = more.current_position() - 1;
+        let first = Self::parse_target(first, more)?;
+        if more.peek() != Some(Token::OneByte(0x2B)) {
+            return Ok(UserInput::Input {
+                prompt: None,
+                target: Some(first),
+            });
+        }
+
+        more.next();
+        let StoreTarget::String(name) = first else {
+            Err(TokenReport::new(
+                first_position,
+                "Expected a string prompt",
+                Some("The prompt must be a string or string variable."),
+            ))?
+        };
+        let target = Self::parse_target(next_or_err!(more)?, more)?;
+
+        Ok(UserInput::Input {
+            prompt: Some(Expression::Operand(Operand::StringName(name))),
+            target: Some(target),
+        })
+    }
+
+    fn parse_prompt(more: &mut Tokens) -> Result<Self, TokenReport> {
+        let mut targets = vec![Self::parse_target(next_or_err!(more)?, more)?];
+        while more.peek() == Some(Token::OneByte(0x2B)) {
+            more.next();
+            targets.push(Self::parse_target(next_or_err!(more)?, more)?);
+        }
+
+        Ok(UserInput::Prompt(targets))
+    }
+
+    /// The variables this statement stores to.
+    pub fn targets(&self) -> &[StoreTarget] {
+        match self {
+            UserInput::Input { target, .. } => target.as_slice(),
+            UserInput::Prompt(targets) => targets,
+        }
+    }
+
+    /// The expressions which appear in this statement: the prompt, and any indices in the
+    /// targets.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            UserInput::Input { prompt, target } => prompt
+                .iter()
+                .chain(target.iter().flat_map(StoreTarget::indices))
+                .collect(),
+            UserInput::Prompt(targets) => targets.iter().flat_map(StoreTarget::indices).collect(),
+        }
+    }
+
+    pub fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            UserInput::Input { prompt, target } => prompt
+                .iter_mut()
+                .chain(target.iter_mut().flat_map(StoreTarget::indices_mut))
+                .collect(),
+            UserInput::Prompt(targets) => targets
+                .iter_mut()
+                .flat_map(StoreTarget::indices_mut)
+                .collect(),
+        }
+    }
+}
+
+impl Parse for UserInput {
+    fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
+        match token {
+            Token::OneByte(0xDC) => Self::parse_input(more).map(Some),
+            Token::OneByte(0xDD) => Self::parse_prompt(more).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Reconstruct for UserInput {
+    fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        match self {
+            UserInput::Input { prompt, target } => {
+                let mut tokens = vec![Token::OneByte(0xDC)];
+                if let Some(prompt) = prompt {
+                    tokens.extend(prompt.reconstruct(config));
+                    tokens.push(Token::OneByte(0x2B));
+                }
+                if let Some(target) = target {
+                    tokens.extend(target.reconstruct(config));
+                }
+
+                tokens
+            }
+            UserInput::Prompt(targets) => std::iter::once(vec![Token::OneByte(0xDD)])
+                .chain(
+                    targets
+                        .iter()
+                        .map(|target| target.reconstruct(config))
+                        .intersperse(vec![Token::OneByte(0x2B)]),
+                )
+                .flatten()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_files::{test_tokenizer, test_version};
+
+    fn parse(text: &str) -> Result<UserInput, TokenReport> {
+        let (mut tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+        let input = UserInput::parse(tokens.next().unwrap(), &mut tokens)?.unwrap();
+        assert_eq!(tokens.peek(), None);
+
+        Ok(input)
+    }
+
+    #[test]
+    fn round_trip() {
+        let tokenizer = test_tokenizer!();
+        let config = Config::from(test_version!());
+
+        for text in [
+            "Input ",
+            "Input A",
+            "Input \"A=\",A",
+            "Input Str1,B",
+            "Input L1",
+            "Input Str2",
+            "Prompt A",
+            "Prompt A,B,theta",
+        ] {
+            let (expected, _) = tokenizer.tokenize(text).unwrap();
+
+            assert_eq!(
+                parse(text).unwrap().reconstruct(&config),
+                expected.collect::<Vec<_>>(),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn targets() {
+        assert!(matches!(
+            parse("Input \"A=\",A").unwrap().targets(),
+            [StoreTarget::NumericVarOrListName(_)]
+        ));
+        assert!(matches!(
+            parse("Prompt A,Str1").unwrap().targets(),
+            [StoreTarget::NumericVarOrListName(_), StoreTarget::String(_)]
+        ));
+        assert!(parse("Input ").unwrap().targets().is_empty());
+
+        assert!(parse("Input A,B").is_err());
+        assert!(parse("Prompt ").is_err());
+    }
+}
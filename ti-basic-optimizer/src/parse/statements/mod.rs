@@ -1,39 +1,54 @@
 pub mod control_flow;
 mod delvar_chain;
 mod generic;
+mod input;
 mod prgm;
 mod setupeditor;
+mod stat_plot;
 
 pub use control_flow::{ControlFlow, LabelName};
 pub use delvar_chain::DelVarChain;
+pub use input::UserInput;
 pub use prgm::ProgramName;
 pub use setupeditor::SetUpEditor;
+pub use stat_plot::{PlotArgument, StatPlot};
 
 pub use generic::Generic;
 use std::iter::once;
 
 use crate::error_reporting::{expect_some, next_or_err, TokenReport};
-use crate::parse::components::StoreTarget;
-use crate::parse::{expression::Expression, Parse, Reconstruct};
+use crate::parse::components::{Operand, StoreTarget};
+use crate::parse::{expression::Expression, parse_text, Parse, Reconstruct};
 use crate::Config;
-use titokens::{Token, Tokens};
+use titokens::{Token, Tokens, Version};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Statement {
     None,
     ControlFlow(ControlFlow),
     Generic(Generic),
     DelVarChain(DelVarChain),
     SetUpEditor(SetUpEditor),
+    StatPlot(StatPlot),
+    UserInput(UserInput),
     Expression(Expression),
     Store(Expression, StoreTarget),
     ProgramInvocation(ProgramName),
 
-    /// Fictional statements are inserted to desugar the program. They do not affect Ans
+    /// Fictional statements are inserted to desugar the program. They do not affect Ans, and
+    /// [`Program::reconstruct`](crate::parse::Program::reconstruct) never outputs them.
     Fiction(Box<Statement>),
 }
 
 impl Statement {
+    /// Tokenize and parse `text` as exactly one statement. Anything left over after the
+    /// statement, including a newline, is an error.
+    ///
+    /// `If` and `Then` are separate statements, so this never produces a [`ControlFlow::IfThen`].
+    pub fn from_text(text: &str, version: Version) -> Result<Statement, TokenReport> {
+        parse_text(text, &Config::from(version), "a statement")
+    }
+
     /// Promote a fictional statement to a real statement.
     pub fn promote(statement: Self) -> Option<Self> {
         match statement {
@@ -42,10 +57,134 @@ impl Statement {
         }
     }
 
+    /// True if this line is a string literal and nothing else, like `"BY J. DOE"`. Such a line
+    /// only sets `Ans`, so programs use it as a comment. A string in any other position, or a line
+    /// with anything else on it, is not a comment.
+    ///
+    /// See [`Config::preserve_comment_strings`].
+    pub fn is_comment_string(&self) -> bool {
+        matches!(
+            self,
+            Statement::Expression(Expression::Operand(Operand::StringLiteral(_)))
+        )
+    }
+
+    /// A statement which does nothing, for a line which can't be removed because a one-line
+    /// conditional guards it (see [`Program::is_guarded_single_line`]). Removing the line instead
+    /// would make the conditional guard the line after it.
+    ///
+    /// This is `Ans` by itself, which costs one byte: it stores `Ans` to `Ans`, so every variable
+    /// keeps its value. The only difference it can make is that if it is the last line a program
+    /// runs, the home screen shows the value of `Ans` instead of `Done`.
+    ///
+    /// [`Program::is_guarded_single_line`]: crate::parse::Program::is_guarded_single_line
+    pub fn safe_noop() -> Self {
+        Statement::Expression(Expression::Operand(Operand::Ans))
+    }
+
+    /// True if this statement is control flow, including control flow attached to a `DelVar`
+    /// chain (`DelVar AIf B`) or wrapped in a [`Statement::Fiction`].
+    ///
+    /// See also: [`Statement::root`]
     pub fn is_control_flow(&self) -> bool {
         matches!(self.root(), Some(Statement::ControlFlow(_)))
     }
 
+    /// The expressions which appear directly in this statement, including those in store targets
+    /// and in the statement attached to a `DelVar` chain.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            Statement::ControlFlow(control_flow) => match control_flow {
+                ControlFlow::If(expr)
+                | ControlFlow::IfThen(expr)
+                | ControlFlow::While(expr)
+                | ControlFlow::Repeat(expr) => vec![expr],
+                ControlFlow::For(for_loop) => {
+                    let mut exprs = vec![&for_loop.iterator, &for_loop.start, &for_loop.end];
+                    exprs.extend(for_loop.step.as_ref());
+
+                    exprs
+                }
+                ControlFlow::IsGt(is_ds) | ControlFlow::DsLt(is_ds) => vec![&is_ds.condition],
+                ControlFlow::Menu(menu) => std::iter::once(&menu.title)
+                    .chain(menu.option_titles.iter())
+                    .collect(),
+
+                _ => vec![],
+            },
+            Statement::Generic(generic) => generic.arguments.iter().collect(),
+            Statement::StatPlot(plot) => plot.expressions().collect(),
+            Statement::DelVarChain(chain) => chain
+                .valence
+                .as_ref()
+                .map(|statement| statement.expressions())
+                .unwrap_or_default(),
+            Statement::Expression(expr) => vec![expr],
+            Statement::Store(expr, target) => {
+                let mut exprs = vec![expr];
+                exprs.extend(target.indices());
+
+                exprs
+            }
+            Statement::UserInput(input) => input.expressions(),
+            Statement::Fiction(statement) => statement.expressions(),
+
+            Statement::None | Statement::SetUpEditor(_) | Statement::ProgramInvocation(_) => {
+                vec![]
+            }
+        }
+    }
+
+    /// Like [`Statement::expressions`], but the expressions may be changed.
+    pub fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Statement::ControlFlow(control_flow) => match control_flow {
+                ControlFlow::If(expr)
+                | ControlFlow::IfThen(expr)
+                | ControlFlow::While(expr)
+                | ControlFlow::Repeat(expr) => vec![expr],
+                ControlFlow::For(for_loop) => {
+                    let mut exprs = vec![
+                        &mut for_loop.iterator,
+                        &mut for_loop.start,
+                        &mut for_loop.end,
+                    ];
+                    exprs.extend(for_loop.step.as_mut());
+
+                    exprs
+                }
+                ControlFlow::IsGt(is_ds) | ControlFlow::DsLt(is_ds) => vec![&mut is_ds.condition],
+                ControlFlow::Menu(menu) => std::iter::once(&mut menu.title)
+                    .chain(menu.option_titles.iter_mut())
+                    .collect(),
+
+                _ => vec![],
+            },
+            Statement::Generic(generic) => generic.arguments.iter_mut().collect(),
+            Statement::StatPlot(plot) => plot.expressions_mut().collect(),
+            Statement::DelVarChain(chain) => chain
+                .valence
+                .as_mut()
+                .map(|statement| statement.expressions_mut())
+                .unwrap_or_default(),
+            Statement::Expression(expr) => vec![expr],
+            Statement::Store(expr, target) => {
+                let mut exprs = vec![expr];
+                exprs.extend(target.indices_mut());
+
+                exprs
+            }
+            Statement::UserInput(input) => input.expressions_mut(),
+            Statement::Fiction(statement) => statement.expressions_mut(),
+
+            Statement::None | Statement::SetUpEditor(_) | Statement::ProgramInvocation(_) => {
+                vec![]
+            }
+        }
+    }
+
+    /// The statement which determines how this line behaves, looking through `DelVar` chains and
+    /// fictional statements. Blank lines and `DelVar` chains with nothing attached have no root.
     pub fn root(&self) -> Option<&Self> {
         match self {
             Statement::None => None,
@@ -56,6 +195,17 @@ impl Statement {
             _ => Some(self),
         }
     }
+
+    /// True if this line is an `If` without a `Then`, an `IS>(`, or a `DS<(`, which skip the
+    /// single line after them when their condition is false.
+    pub fn guards_single_line(&self) -> bool {
+        matches!(
+            self.root(),
+            Some(Statement::ControlFlow(
+                ControlFlow::If(_) | ControlFlow::IsGt(_) | ControlFlow::DsLt(_)
+            ))
+        )
+    }
 }
 
 impl Parse for Statement {
@@ -72,22 +222,35 @@ impl Parse for Statement {
             Ok(Some(cmd))
         } else if let Some(cmd) = SetUpEditor::parse(token, more)?.map(Self::SetUpEditor) {
             Ok(Some(cmd))
+        } else if let Some(cmd) = StatPlot::parse(token, more)?.map(Self::StatPlot) {
+            Ok(Some(cmd))
+        } else if let Some(cmd) = UserInput::parse(token, more)?.map(Self::UserInput) {
+            Ok(Some(cmd))
         } else if let Some(expr) = Expression::parse(token, more)? {
             if more.peek() == Some(Token::OneByte(0x04)) {
                 let arrow_pos = more.current_position();
                 more.next();
 
-                Ok(Some(Statement::Store(
-                    expr,
-                    expect_some!(
-                        StoreTarget::parse(next_or_err!(more)?, more)?,
-                        more,
-                        1,
-                        "a store target",
-                        "Parsing failed here."
+                let target = expect_some!(
+                    StoreTarget::parse(next_or_err!(more)?, more)?,
+                    more,
+                    1,
+                    "a store target",
+                    "Parsing failed here."
+                )
+                .map_err(|x| x.with_label(arrow_pos, "Store arrow is here."))?;
+
+                if more.peek() == Some(Token::OneByte(0x04)) {
+                    // 5->A->B is a syntax error on-calc
+                    Err(TokenReport::new(
+                        more.current_position(),
+                        "Chained store",
+                        Some("Store arrows cannot be chained; store Ans on the next line instead."),
                     )
-                    .map_err(|x| x.with_label(arrow_pos, "Store arrow is here."))?,
-                )))
+                    .with_label(arrow_pos, "First store arrow is here."))?;
+                }
+
+                Ok(Some(Statement::Store(expr, target)))
             } else {
                 Ok(Some(Statement::Expression(expr)))
             }
@@ -99,17 +262,29 @@ impl Parse for Statement {
 
 impl Reconstruct for Statement {
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        if config.preserve_comment_strings {
+            if let Statement::Expression(Expression::Operand(Operand::StringLiteral(string))) = self
+            {
+                return string.verbatim();
+            }
+        }
+
         let mut line = match self {
             Statement::Fiction(x) => x.reconstruct(config),
-            Statement::ControlFlow(x) => x.reconstruct(config),
+            // strips its own parentheses, but not always; see `ControlFlow::reconstruct`.
+            Statement::ControlFlow(x) => return x.reconstruct(config),
             Statement::Generic(x) => x.reconstruct(config),
             Statement::DelVarChain(x) => x.reconstruct(config),
             Statement::SetUpEditor(x) => x.reconstruct(config),
+            Statement::StatPlot(x) => x.reconstruct(config),
+            Statement::UserInput(x) => x.reconstruct(config),
             Statement::Expression(x) => x.reconstruct(config),
             Statement::ProgramInvocation(x) => x.reconstruct(config),
             Statement::Store(x, target) => {
                 let mut expr = x.reconstruct(config);
-                Expression::strip_closing_parenthesis(&mut expr);
+                if config.strip_trailing_parens {
+                    Expression::strip_closing_parenthesis(&mut expr);
+                }
                 expr.into_iter()
                     .chain(once(Token::OneByte(0x04)))
                     .chain(target.reconstruct(config))
@@ -118,7 +293,9 @@ impl Reconstruct for Statement {
             Statement::None => return vec![],
         };
 
-        Expression::strip_closing_parenthesis(&mut line);
+        if config.strip_trailing_parens {
+            Expression::strip_closing_parenthesis(&mut line);
+        }
 
         line
     }
@@ -127,7 +304,8 @@ impl Reconstruct for Statement {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use test_files::load_test_data;
+    use crate::parse::{components::Operand, Program};
+    use test_files::{load_test_data, test_tokenizer, test_version};
 
     #[test]
     fn store() {
@@ -138,4 +316,181 @@ mod tests {
             .unwrap();
         assert!(matches!(cmd, Statement::Store(_, _)));
     }
+
+    #[test]
+    fn is_control_flow() {
+        let tokenizer = test_tokenizer!();
+        let statement = |text: &str| {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            Statement::parse(tokens.next().unwrap(), &mut tokens)
+                .unwrap()
+                .unwrap()
+        };
+
+        assert!(statement("If A").is_control_flow());
+        assert!(statement("End").is_control_flow());
+        assert!(statement("DelVar AIf B").is_control_flow());
+        assert!(statement("DelVar ADelVar BGoto C").is_control_flow());
+        assert!(Statement::Fiction(Box::new(statement("Stop"))).is_control_flow());
+
+        assert!(!statement("DelVar A").is_control_flow());
+        assert!(!statement("DelVar ADisp B").is_control_flow());
+        assert!(!statement("Disp A").is_control_flow());
+        assert!(!statement("1->A").is_control_flow());
+        assert!(!Statement::None.is_control_flow());
+    }
+
+    #[test]
+    fn safe_noop() {
+        let config = Config::from(test_version!());
+        assert_eq!(
+            Statement::safe_noop().reconstruct(&config),
+            vec![Token::OneByte(0x72)]
+        );
+
+        let mut program = Program::from_text("If A\nAns\nDisp 1", test_version!());
+        assert_eq!(program.lines[1], Statement::safe_noop());
+
+        // the If still guards the no-op
+        program.lines[1] = Statement::None;
+        assert!(program.is_guarded_single_line(2));
+    }
+
+    #[test]
+    fn chained_store() {
+        let (mut tokens, _) = test_tokenizer!().tokenize("5->A->B").unwrap();
+
+        assert!(Statement::parse(tokens.next().unwrap(), &mut tokens).is_err());
+
+        let text = "5->A\nAns->B";
+        let tokenizer = test_tokenizer!();
+        let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+        let (expected, _) = tokenizer.tokenize(text).unwrap();
+
+        assert!(matches!(
+            program.lines[1],
+            Statement::Store(Expression::Operand(Operand::Ans), _)
+        ));
+        assert_eq!(
+            program.reconstruct(&test_version!().into()),
+            expected.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rand_seed() {
+        let tokenizer = test_tokenizer!();
+        let statement = |text: &str| {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let statement = Statement::parse(tokens.next().unwrap(), &mut tokens)
+                .unwrap()
+                .unwrap();
+            assert_eq!(tokens.peek(), None);
+
+            statement
+        };
+
+        assert!(matches!(
+            statement("5->rand"),
+            Statement::Store(_, StoreTarget::RandSeed)
+        ));
+        assert!(matches!(
+            statement("rand->A"),
+            Statement::Store(Expression::Operand(Operand::Rand(rand)), StoreTarget::NumericVarOrListName(_))
+                if !rand.is_list()
+        ));
+        assert!(matches!(
+            statement("rand(3)->L1"),
+            Statement::Store(Expression::Operand(Operand::Rand(rand)), StoreTarget::List(_))
+                if rand.is_list()
+        ));
+        assert!(matches!(
+            statement("rand->rand"),
+            Statement::Store(Expression::Operand(Operand::Rand(_)), StoreTarget::RandSeed)
+        ));
+
+        for text in ["5->rand", "rand->A", "rand->rand"] {
+            let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+            let program = Program::from_tokens(&mut tokens, &tokenizer);
+            let (expected, _) = tokenizer.tokenize(text).unwrap();
+
+            assert_eq!(
+                program.reconstruct(&test_version!().into()),
+                expected.collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn keep_trailing_parens() {
+        let tokenizer = test_tokenizer!();
+        let text = "Disp sin(1)\nround(A,2)->B\nIf max({1,2})\nThen\nEnd";
+        let (mut tokens, _) = tokenizer.tokenize(text).unwrap();
+        let program = Program::from_tokens(&mut tokens, &tokenizer);
+
+        let config = Config {
+            strip_trailing_parens: false,
+            ..test_version!().into()
+        };
+        let (expected, _) = tokenizer.tokenize(text).unwrap();
+        assert_eq!(program.reconstruct(&config), expected.collect::<Vec<_>>());
+
+        let (expected, _) = tokenizer
+            .tokenize("Disp sin(1\nround(A,2->B\nIf max({1,2\nThen\nEnd")
+            .unwrap();
+        assert_eq!(
+            program.reconstruct(&test_version!().into()),
+            expected.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn from_text() {
+        assert!(matches!(
+            Statement::from_text("5->A", test_version!()),
+            Ok(Statement::Store(_, StoreTarget::NumericVarOrListName(_)))
+        ));
+        assert!(matches!(
+            Statement::from_text("Disp A,B", test_version!()),
+            Ok(Statement::Generic(_))
+        ));
+
+        assert!(Statement::from_text("", test_version!()).is_err());
+        assert!(Statement::from_text("Disp A:Disp B", test_version!()).is_err());
+        assert!(Statement::from_text("5->A->B", test_version!()).is_err());
+    }
+
+    #[test]
+    fn comment_strings() {
+        let comment = Statement::from_text("\"1\"", test_version!()).unwrap();
+        assert!(comment.is_comment_string());
+        assert!(!Statement::from_text("Disp \"1\"", test_version!())
+            .unwrap()
+            .is_comment_string());
+        assert!(!Statement::from_text("\"1\"+Str1", test_version!())
+            .unwrap()
+            .is_comment_string());
+
+        let tokenizer = test_tokenizer!();
+        let (verbatim, _) = tokenizer.tokenize("\"1\"").unwrap();
+        let config = Config {
+            preserve_comment_strings: true,
+            ..Config::from(test_version!())
+        };
+        assert_eq!(comment.reconstruct(&config), verbatim.collect::<Vec<_>>());
+
+        // otherwise, the closing quote is stripped
+        let (stripped, _) = tokenizer.tokenize("\"1").unwrap();
+        assert_eq!(
+            comment.reconstruct(&Config::from(test_version!())),
+            stripped.collect::<Vec<_>>()
+        );
+
+        // a comment without its closing quote is written back without one
+        let text = "\"BY J. DOE\nDisp A";
+        let (verbatim, _) = tokenizer.tokenize(text).unwrap();
+        let program = Program::from_text(text, test_version!());
+        assert_eq!(program.reconstruct(&config), verbatim.collect::<Vec<_>>());
+    }
 }
@@ -4,7 +4,7 @@ use crate::Config;
 use std::iter::once;
 use titokens::{Token, Tokens};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ProgramName {
     pub name: Vec<Token>,
 }
@@ -19,20 +19,22 @@ impl Parse for ProgramName {
         let mut name = vec![];
 
         while let Some(token) = more.next() {
-            if (!name.is_empty() && token.is_alpha())
-                || (name.is_empty() && token.is_alphanumeric())
+            if (name.is_empty() && token.is_alpha())
+                || (!name.is_empty() && token.is_alphanumeric())
             {
-                if name.len() > 8 {
+                if name.len() >= 8 {
+                    let excess_position = more.current_position() - 1;
+
                     Err(TokenReport::new(
                         start_position,
                         "Program name has too many characters (max 8)",
                         None,
                     )
                     .with_span_label(
-                        start_position..start_position + 9,
+                        start_position + 1..excess_position,
                         "This part is a valid program name.",
                     )
-                    .with_label(more.current_position(), "The part starting here is not."))?;
+                    .with_label(excess_position, "The part starting here is not."))?;
                 }
 
                 name.push(token);
@@ -65,7 +67,20 @@ impl Reconstruct for ProgramName {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use test_files::load_test_data;
+    use crate::parse::{statements::Statement, Program};
+    use test_files::{load_test_data, test_tokenizer, test_version};
+
+    fn round_trip(text: &str) {
+        let (mut tokens, _) = test_tokenizer!().tokenize(text).unwrap();
+        let expected = tokens.clone().collect::<Vec<_>>();
+
+        let prgm = ProgramName::parse(tokens.next().unwrap(), &mut tokens)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(prgm.reconstruct(&test_version!().into()), expected);
+        assert_eq!(tokens.next(), None);
+    }
 
     #[test]
     fn parse() {
@@ -88,4 +103,42 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn names() {
+        round_trip("prgmABC");
+        round_trip("prgmtheta");
+        round_trip("prgmA1B2C3D4");
+    }
+
+    #[test]
+    fn leading_digit() {
+        let (mut tokens, _) = test_tokenizer!().tokenize("prgm1").unwrap();
+
+        assert!(ProgramName::parse(tokens.next().unwrap(), &mut tokens).is_err());
+    }
+
+    #[test]
+    fn too_long() {
+        let (mut tokens, _) = test_tokenizer!().tokenize("prgmABCDEFGHI").unwrap();
+
+        assert!(ProgramName::parse(tokens.next().unwrap(), &mut tokens).is_err());
+    }
+
+    #[test]
+    fn delimiters() {
+        let program = Program::from_text("prgmABC:prgmDEF\nprgmGHI", test_version!());
+
+        assert_eq!(program.lines.len(), 3);
+        for (line, name) in program.lines.iter().zip(["ABC", "DEF", "GHI"]) {
+            let Statement::ProgramInvocation(prgm) = line else {
+                panic!("Expected a program invocation.");
+            };
+
+            assert_eq!(
+                prgm.name,
+                name.bytes().map(Token::OneByte).collect::<Vec<_>>()
+            );
+        }
+    }
 }
@@ -6,11 +6,28 @@ use titokens::{Token, Tokens};
 use crate::Config;
 use itertools::Itertools;
 
-#[derive(Clone, Debug)]
+/// `SetUpEditor`, which puts lists in the list editor, creating any which don't exist.
+///
+/// Reference: <http://tibasicdev.wikidot.com/setupeditor>
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SetUpEditor {
+    /// The lists named after `SetUpEditor`, in order. Empty if none were named, which restores
+    /// `L1` through `L6`.
     pub lists: Vec<ListName>,
 }
 
+impl SetUpEditor {
+    /// The lists which end up in the editor: the named lists, or `L1` through `L6` if there are
+    /// none.
+    pub fn edited_lists(&self) -> &[ListName] {
+        if self.lists.is_empty() {
+            &DEFAULT_LISTS
+        } else {
+            &self.lists
+        }
+    }
+}
+
 impl Parse for SetUpEditor {
     fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
         if token != Token::TwoByte(0xBB, 0x4A) {
@@ -53,8 +70,6 @@ impl Parse for SetUpEditor {
                     .with_label(more.current_position() - 1, "here"))?,
                 }
             }
-        } else {
-            lists.extend(Vec::from(DEFAULT_LISTS))
         }
 
         Ok(Some(SetUpEditor { lists }))
@@ -64,7 +79,8 @@ impl Parse for SetUpEditor {
 impl Reconstruct for SetUpEditor {
     fn reconstruct(&self, config: &Config) -> Vec<Token> {
         let mut result = vec![Token::TwoByte(0xBB, 0x4A)];
-        if self.lists.len() == 6 && DEFAULT_LISTS.iter().all(|x| self.lists.contains(x)) {
+        // the order of the lists is the order of the columns in the editor
+        if self.edited_lists() == DEFAULT_LISTS {
             return result;
         }
 
@@ -83,6 +99,7 @@ impl Reconstruct for SetUpEditor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse::statements::Statement;
     use titokens::version;
 
     #[test]
@@ -98,4 +115,28 @@ mod tests {
             vec![token]
         );
     }
+
+    #[test]
+    fn default_lists() {
+        let config = version::LATEST.clone().into();
+        let editor = |text: &str| {
+            let Ok(Statement::SetUpEditor(editor)) =
+                Statement::from_text(text, version::LATEST.clone())
+            else {
+                panic!("Expected a SetUpEditor.");
+            };
+
+            editor
+        };
+
+        assert_eq!(editor("SetUpEditor ").edited_lists(), DEFAULT_LISTS);
+        assert_eq!(
+            editor("SetUpEditor L1,L2,L3,L4,L5,L6").reconstruct(&config),
+            vec![Token::TwoByte(0xBB, 0x4A)]
+        );
+
+        let reordered = editor("SetUpEditor L6,L5,L4,L3,L2,L1");
+        assert_eq!(reordered.edited_lists().first(), Some(&DEFAULT_LISTS[5]));
+        assert_eq!(reordered.reconstruct(&config).len(), 12);
+    }
 }
@@ -0,0 +1,236 @@
+use crate::error_reporting::{expect_some, next_or_err, TokenReport};
+use crate::parse::expression::Expression;
+use crate::parse::{Parse, Reconstruct};
+use crate::Config;
+use titokens::{Token, Tokens};
+
+/// `Plot1(`, `Plot2(`, or `Plot3(`, like `Plot1(Scatter,L1,L2,□`.
+///
+/// The first argument is a plot type rather than an expression, and neither the mark (`□`, `+`,
+/// or `·`) nor the axis of a `NormProbPlot` (`X` or `Y`) is an expression either. The rest are
+/// data lists, a frequency list or number, and a color on color calculators.
+///
+/// Reference: <http://tibasicdev.wikidot.com/plotn>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatPlot {
+    pub kind: Token,
+    /// `Scatter`, `xyLine`, `Histogram`, `ModBoxplot`, `Boxplot`, or `NormProbPlot`. `Plot1(` by
+    /// itself turns the plot on without changing its settings, and has no type.
+    pub plot_type: Option<Token>,
+    pub arguments: Vec<PlotArgument>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlotArgument {
+    Expression(Expression),
+    Mark(Token),
+    /// The data axis of a `NormProbPlot`, `X` or `Y`. This names an axis and doesn't read the
+    /// variable.
+    Axis(Token),
+}
+
+impl StatPlot {
+    fn recognize(token: Token) -> bool {
+        matches!(token, Token::OneByte(0xEC..=0xEE))
+    }
+
+    fn is_plot_type(token: Token) -> bool {
+        matches!(
+            token,
+            Token::OneByte(0xFC..=0xFE) // Histogram xyLine Scatter
+            | Token::OneByte(0x05) // Boxplot
+            | Token::TwoByte(0xBB, 0x5A..=0x5B) // ModBoxplot NormProbPlot
+        )
+    }
+
+    fn is_mark(token: Token) -> bool {
+        matches!(token, Token::OneByte(0x7F..=0x81)) // □ + ·
+    }
+
+    /// True if `token`, as argument number `index` (after the plot type), is the axis of a
+    /// `NormProbPlot`, which comes right after the data list.
+    fn is_axis(&self, index: usize, token: Token) -> bool {
+        self.plot_type == Some(Token::TwoByte(0xBB, 0x5B))
+            && index == 1
+            && matches!(token, Token::OneByte(0x58..=0x59)) // X Y
+    }
+
+    /// The expressions among the arguments, in order.
+    pub fn expressions(&self) -> impl Iterator<Item = &Expression> {
+        self.arguments.iter().filter_map(|argument| match argument {
+            PlotArgument::Expression(expr) => Some(expr),
+            PlotArgument::Mark(_) | PlotArgument::Axis(_) => None,
+        })
+    }
+
+    pub fn expressions_mut(&mut self) -> impl Iterator<Item = &mut Expression> {
+        self.arguments
+            .iter_mut()
+            .filter_map(|argument| match argument {
+                PlotArgument::Expression(expr) => Some(expr),
+                PlotArgument::Mark(_) | PlotArgument::Axis(_) => None,
+            })
+    }
+}
+
+impl Parse for StatPlot {
+    fn parse(token: Token, more: &mut Tokens) -> Result<Option<Self>, TokenReport> {
+        if !StatPlot::recognize(token) {
+            return Ok(None);
+        }
+
+        let statement_position = more.current_position() - 1;
+        let mut plot = StatPlot {
+            kind: token,
+            plot_type: None,
+            arguments: vec![],
+        };
+
+        match more.peek() {
+            Some(Token::OneByte(0x11)) => {
+                // `Plot1()` is `Plot1(` with its parenthesis closed
+                more.next();
+
+                return Ok(Some(plot));
+            }
+            Some(Token::OneByte(0x3E | 0x3F)) | None => return Ok(Some(plot)), // :, \n, EOF
+            _ => {}
+        }
+
+        let plot_type = next_or_err!(more)?;
+        if !StatPlot::is_plot_type(plot_type) {
+            Err(TokenReport::new(
+                more.current_position() - 1,
+                "Expected a plot type",
+                Some("Plots start with a type, like Scatter or Histogram."),
+            )
+            .with_label(statement_position, "This plot."))?;
+        }
+        plot.plot_type = Some(plot_type);
+
+        loop {
+            match more.peek() {
+                Some(Token::OneByte(0x2B)) => {
+                    // ,
+                    more.next();
+                }
+                Some(Token::OneByte(0x11)) => {
+                    // )
+                    more.next();
+                    break;
+                }
+                Some(Token::OneByte(0x3E | 0x3F)) | None => break, // :, \n, EOF
+
+                Some(_) => Err(TokenReport::new(
+                    more.current_position(),
+                    "Unexpected character in plot",
+                    None,
+                )
+                .with_label(statement_position, "This plot."))?,
+            }
+
+            let next = next_or_err!(more)?;
+            if StatPlot::is_mark(next) {
+                plot.arguments.push(PlotArgument::Mark(next));
+            } else if plot.is_axis(plot.arguments.len(), next) {
+                plot.arguments.push(PlotArgument::Axis(next));
+            } else {
+                let expr = expect_some!(
+                    Expression::parse(next, more)?,
+                    more,
+                    "an expression or mark",
+                    "Plot arguments are lists, numbers, colors, or marks."
+                )?;
+
+                plot.arguments.push(PlotArgument::Expression(expr));
+            }
+        }
+
+        Ok(Some(plot))
+    }
+}
+
+impl Reconstruct for StatPlot {
+    /// The closing parenthesis is stripped again by the statement unless
+    /// [`Config::strip_trailing_parens`] is off, like for [`Generic`](super::Generic) commands.
+    fn reconstruct(&self, config: &Config) -> Vec<Token> {
+        let mut result = vec![self.kind];
+
+        let Some(plot_type) = self.plot_type else {
+            return result;
+        };
+        result.push(plot_type);
+
+        for argument in &self.arguments {
+            result.push(Token::OneByte(0x2B));
+
+            match argument {
+                PlotArgument::Expression(expr) => result.extend(expr.reconstruct(config)),
+                PlotArgument::Mark(mark) | PlotArgument::Axis(mark) => result.push(*mark),
+            }
+        }
+
+        result.push(Token::OneByte(0x11));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::statements::Statement;
+    use test_files::{test_tokenizer, test_version};
+
+    #[test]
+    fn plots() {
+        let statement =
+            Statement::from_text("Plot1(Scatter,L1,L2,squareplot,BLUE", test_version!()).unwrap();
+        assert_eq!(statement.used_variables().len(), 2);
+
+        let Statement::StatPlot(plot) = statement else {
+            panic!("Expected a plot.");
+        };
+
+        assert_eq!(plot.plot_type, Some(Token::OneByte(0xFE)));
+        assert_eq!(plot.arguments.len(), 4);
+        assert_eq!(plot.arguments[2], PlotArgument::Mark(Token::OneByte(0x7F)));
+        assert_eq!(plot.expressions().count(), 3);
+
+        let statement =
+            Statement::from_text("Plot1(NormProbPlot,L1,X,crossplot", test_version!()).unwrap();
+        assert_eq!(statement.used_variables().len(), 1);
+        let Statement::StatPlot(plot) = &statement else {
+            panic!("Expected a plot.");
+        };
+        assert_eq!(plot.arguments[1], PlotArgument::Axis(Token::OneByte(0x58)));
+        assert_eq!(
+            statement.reconstruct(&Config::from(test_version!())),
+            test_tokenizer!()
+                .tokenize("Plot1(NormProbPlot,L1,X,crossplot")
+                .unwrap()
+                .0
+                .collect::<Vec<_>>()
+        );
+
+        for text in ["Plot2(", "Plot2()", "Plot3(Histogram,L3"] {
+            assert!(
+                matches!(
+                    Statement::from_text(text, test_version!()),
+                    Ok(Statement::StatPlot(_))
+                ),
+                "{text}"
+            );
+        }
+
+        assert!(Statement::from_text("Plot1(L1,L2", test_version!()).is_err());
+
+        let config = Config::from(test_version!());
+        assert_eq!(
+            Statement::from_text("Plot3()", test_version!())
+                .unwrap()
+                .reconstruct(&config),
+            vec![Token::OneByte(0xEE)]
+        );
+    }
+}
@@ -53,6 +53,15 @@ impl Token {
         }
     }
 
+    /// The number of bytes this token takes up in a program: 1 or 2.
+    #[must_use]
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Token::OneByte(_) => 1,
+            Token::TwoByte(_, _) => 2,
+        }
+    }
+
     #[must_use]
     pub fn string_escaped(&self) -> String {
         match self {
@@ -80,6 +89,10 @@ impl Debug for Token {
     }
 }
 
+/// A stream of tokens.
+///
+/// Two `Tokens` are equal if they contain the same tokens; the current position and version are
+/// not considered.
 #[derive(Clone)]
 pub struct Tokens {
     tokens: Vec<Token>,
@@ -87,6 +100,24 @@ pub struct Tokens {
     version: Option<Version>,
 }
 
+impl PartialEq for Tokens {
+    fn eq(&self, other: &Self) -> bool {
+        self.tokens == other.tokens
+    }
+}
+
+impl Eq for Tokens {}
+
+/// Iterates over every token, regardless of the current position, without consuming anything.
+impl<'a> IntoIterator for &'a Tokens {
+    type Item = &'a Token;
+    type IntoIter = std::slice::Iter<'a, Token>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tokens.iter()
+    }
+}
+
 impl Iterator for Tokens {
     type Item = Token;
 
@@ -143,8 +174,21 @@ impl Tokens {
         self.tokens.get(self.pos).copied()
     }
 
+    /// Steps back one token, so that it is returned by the next call to `next` again. Does
+    /// nothing at the start of the stream.
     pub fn backtrack_once(&mut self) {
-        self.pos -= 1;
+        self.pos = self.pos.saturating_sub(1);
+    }
+
+    /// Returns to the start of the stream.
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+
+    /// The number of tokens left to read.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.tokens.len().saturating_sub(self.pos)
     }
 
     #[must_use]
@@ -163,6 +207,13 @@ impl Tokens {
     pub fn version(&self) -> &Version {
         self.version.as_ref().unwrap()
     }
+
+    /// The number of bytes taken up by every token in this stream, regardless of the current
+    /// position. Unlike converting to a `Vec<u8>`, this does not consume the stream.
+    #[must_use]
+    pub fn byte_len(&self) -> usize {
+        self.tokens.iter().map(Token::byte_len).sum()
+    }
 }
 
 impl From<Tokens> for Vec<u8> {
@@ -181,3 +232,47 @@ impl From<Tokens> for Vec<u8> {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_len() {
+        let bytes = [0xDE, 0x2A, 0xBB, 0xB0, 0x2A, 0x3F, 0xEF, 0x97];
+        let mut tokens = Tokens::from_bytes(&bytes, None);
+
+        assert_eq!(Token::OneByte(0xDE).byte_len(), 1);
+        assert_eq!(Token::TwoByte(0xBB, 0xB0).byte_len(), 2);
+        assert_eq!(tokens.byte_len(), bytes.len());
+
+        tokens.next();
+        assert_eq!(tokens.byte_len(), bytes.len());
+        assert_eq!(Vec::<u8>::from(tokens).len(), bytes.len() - 1);
+    }
+
+    #[test]
+    fn position() {
+        let mut tokens = Tokens::from_vec(vec![Token::OneByte(0xDE), Token::OneByte(0x31)], None);
+
+        tokens.backtrack_once();
+        assert_eq!(tokens.current_position(), 0);
+        assert_eq!(tokens.remaining(), 2);
+
+        assert_eq!(tokens.next(), Some(Token::OneByte(0xDE)));
+        assert_eq!(tokens.remaining(), 1);
+        assert_eq!(tokens.next(), Some(Token::OneByte(0x31)));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.remaining(), 0);
+
+        // reading past the end can be undone like any other read
+        tokens.backtrack_once();
+        assert_eq!(tokens.remaining(), 0);
+        tokens.backtrack_once();
+        assert_eq!(tokens.peek(), Some(Token::OneByte(0x31)));
+
+        tokens.reset();
+        assert_eq!(tokens.remaining(), 2);
+        assert_eq!(tokens.next(), Some(Token::OneByte(0xDE)));
+    }
+}
@@ -1,6 +1,7 @@
-use crate::Tokens;
+use crate::{Tokens, Version};
 use chrono::prelude::*;
 use deku::prelude::*;
+use std::fmt::{Display, Formatter};
 
 pub const DATA_COMMENT_TOOL_ID: u8 = 0x72;
 
@@ -33,12 +34,131 @@ pub struct DataComment {
     rest: Vec<u8>,
 }
 
+/// Reasons [`DataComment::set_author`] can fail.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AuthorError {
+    TooLong,
+    NotAscii,
+}
+
+impl DataComment {
+    /// The date the program was exported, or `None` if the comment does not hold a valid date (for
+    /// instance, if it is zero-filled).
+    pub fn export_date(&self) -> Option<NaiveDate> {
+        fn from_bcd(byte: u8) -> Option<u32> {
+            let (tens, ones) = (byte >> 4, byte & 0x0F);
+
+            (tens <= 9 && ones <= 9).then_some((tens * 10 + ones) as u32)
+        }
+
+        let day = from_bcd(self.export_day)?;
+        let month = from_bcd(self.export_month)?;
+        let year = from_bcd(self.export_year_top)? * 100 + from_bcd(self.export_year_bottom)?;
+
+        NaiveDate::from_ymd_opt(year as i32, month, day)
+    }
+
+    /// The author recorded in the comment.
+    pub fn author_string(&self) -> String {
+        String::from_utf8_lossy(&self.author).into_owned()
+    }
+
+    /// Replace the author recorded in the comment.
+    ///
+    /// The author and the padding after it share 24 bytes, so the author must be ASCII and at most
+    /// 24 characters long. Fails without changing anything otherwise.
+    pub fn set_author(&mut self, author: &str) -> Result<(), AuthorError> {
+        if !author.is_ascii() {
+            return Err(AuthorError::NotAscii);
+        }
+
+        if author.len() > 24 {
+            return Err(AuthorError::TooLong);
+        }
+
+        self.author = author.as_bytes().to_vec();
+        self.author_len = self.author.len() as u8;
+        self.rest.resize(24 - self.author.len(), 0);
+
+        Ok(())
+    }
+}
+
+/// A length field in the header of an 8xp file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LengthField {
+    /// The length of everything between it and the checksum.
+    DataLength,
+    /// The length of the variable data, which is the token data and its length.
+    VarDataLength,
+    /// The second copy of [`LengthField::VarDataLength`].
+    VarDataLength2,
+    /// The length of the token data.
+    TokenDataLength,
+}
+
+impl Display for LengthField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LengthField::DataLength => "data length",
+            LengthField::VarDataLength => "variable data length",
+            LengthField::VarDataLength2 => "second variable data length",
+            LengthField::TokenDataLength => "token data length",
+        })
+    }
+}
+
+/// A length field which disagrees with the rest of the file. See [`TIProgram::validate`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LengthMismatch {
+    pub field: LengthField,
+    /// The length stored in the field.
+    pub claimed: usize,
+    /// The length the field should have, given the rest of the file.
+    pub expected: usize,
+}
+
+impl Display for LengthMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the {} is {} bytes, but should be {} bytes",
+            self.field, self.claimed, self.expected
+        )
+    }
+}
+
+impl std::error::Error for LengthMismatch {}
+
+/// Reasons [`TIProgram::read`] can fail.
+#[derive(Debug)]
+pub enum ReadError {
+    /// A length field disagrees with the file; this is typical of hand-edited or truncated files.
+    Length(LengthMismatch),
+    /// The file is not an 8xp program.
+    Deku(DekuError),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Length(mismatch) => write!(f, "Malformed 8xp header: {mismatch}"),
+            ReadError::Deku(err) => write!(f, "Malformed 8xp file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+/// Offset of the token data length in an 8xp file; the token data follows it.
+const TOKEN_DATA_LENGTH_OFFSET: usize = 72;
+
 #[derive(Debug, DekuRead, DekuWrite)]
 #[deku(endian = "little", magic = b"**TI83")]
 pub struct TIProgram {
     further_magic: [u8; 5],
     comment: Comment,
-    #[deku(update = "self.data.len() + 17")]
+    #[deku(update = "self.data.len() + 19")]
     data_length: u16,
     #[deku(assert = "*flash_indicator == 0x0b || *flash_indicator == 0x0d")]
     flash_indicator: u16,
@@ -60,15 +180,95 @@ pub struct TIProgram {
 }
 
 impl TIProgram {
+    /// An empty, unprotected, unarchived program called `name`, which is padded with zeros. Use
+    /// [`TIProgram::update_tokens`] to fill it in.
+    pub fn new(name: [u8; 8]) -> Self {
+        let mut comment = [0; 41];
+        let text = b"Created by tibo";
+        comment[..text.len() - 1].copy_from_slice(&text[1..]);
+
+        let mut program = TIProgram {
+            further_magic: *b"F*\x1A\x0A\x00",
+            comment: Comment::Bytes(text[0], comment),
+            data_length: 0,
+            flash_indicator: 0x0d,
+            var_data_length: 0,
+            file_type: 0x05,
+            name,
+            version: 0,
+            flags: 0,
+            var_data_length_2: 0,
+            token_data_length: 0,
+            data: vec![],
+            checksum: 0,
+        };
+        program.update().unwrap();
+
+        program
+    }
+
+    /// Read an 8xp file, checking that the lengths in its header agree with each other and with
+    /// the size of the file.
+    pub fn read(bytes: &[u8]) -> Result<Self, ReadError> {
+        // deku gives an unhelpful error when the token data runs off the end of the file.
+        if let Some(&[low, high]) =
+            bytes.get(TOKEN_DATA_LENGTH_OFFSET..TOKEN_DATA_LENGTH_OFFSET + 2)
+        {
+            let claimed = u16::from_le_bytes([low, high]) as usize;
+            // the token data is followed by a two-byte checksum.
+            let available = bytes.len().saturating_sub(TOKEN_DATA_LENGTH_OFFSET + 4);
+
+            if claimed > available {
+                return Err(ReadError::Length(LengthMismatch {
+                    field: LengthField::TokenDataLength,
+                    claimed,
+                    expected: available,
+                }));
+            }
+        }
+
+        let (_, program) = TIProgram::from_bytes((bytes, 0)).map_err(ReadError::Deku)?;
+        program.validate().map_err(ReadError::Length)?;
+
+        Ok(program)
+    }
+
+    /// Check that the length fields in the header agree with the length of the token data.
+    /// Returns the first field which disagrees.
+    pub fn validate(&self) -> Result<(), LengthMismatch> {
+        let len = self.data.len();
+
+        [
+            (LengthField::TokenDataLength, self.token_data_length, len),
+            (LengthField::VarDataLength, self.var_data_length, len + 2),
+            (LengthField::VarDataLength2, self.var_data_length_2, len + 2),
+            (LengthField::DataLength, self.data_length, len + 19),
+        ]
+        .into_iter()
+        .find(|&(_, claimed, expected)| claimed as usize != expected)
+        .map_or(Ok(()), |(field, claimed, expected)| {
+            Err(LengthMismatch {
+                field,
+                claimed: claimed as usize,
+                expected,
+            })
+        })
+    }
+
     pub fn force_data_comment(&mut self, tool_id: Option<u8>) {
+        self.force_data_comment_dated(tool_id, Local::now().date_naive());
+    }
+
+    /// Like [`TIProgram::force_data_comment`], but records `date` as the export date instead of
+    /// today.
+    pub fn force_data_comment_dated(&mut self, tool_id: Option<u8>, date: NaiveDate) {
         match &self.comment {
             Comment::DataComment(x) => {}
 
             Comment::Bytes(first, data) => {
-                let local: DateTime<Local> = Local::now();
-                let day = local.day() as u8;
-                let month = local.month() as u8;
-                let year = local.year() as u16; // if this crashes... are you trying to put an 8xp on the antikythera mechanism?
+                let day = date.day() as u8;
+                let month = date.month() as u8;
+                let year = date.year() as u16; // if this crashes... are you trying to put an 8xp on the antikythera mechanism?
 
                 self.comment = Comment::DataComment(DataComment {
                     magic: 0xB8,
@@ -80,21 +280,52 @@ impl TIProgram {
                     export_year_bottom: ((((year % 100) / 10) << 4) + (year % 10)) as u8,
                     author_len: 0,
                     author: vec![],
-                    rest: vec![],
+                    rest: vec![0; 24],
                 })
             }
         }
     }
 
+    /// The program's data comment, if it has one. See [`TIProgram::force_data_comment`].
+    pub fn data_comment(&self) -> Option<&DataComment> {
+        match &self.comment {
+            Comment::DataComment(comment) => Some(comment),
+            Comment::Bytes(..) => None,
+        }
+    }
+
+    /// Like [`TIProgram::data_comment`], but allows editing it, e.g. with
+    /// [`DataComment::set_author`].
+    ///
+    /// The comment is a fixed 42 bytes and the checksum only covers the token data, so changing
+    /// it leaves the lengths and checksum as they were. The export date isn't touched either.
+    pub fn data_comment_mut(&mut self) -> Option<&mut DataComment> {
+        match &mut self.comment {
+            Comment::DataComment(comment) => Some(comment),
+            Comment::Bytes(..) => None,
+        }
+    }
+
     pub fn read_tokens(&self) -> Tokens {
         Tokens::from_bytes(&self.data, None)
     }
 
+    /// Like [`TIProgram::read_tokens`], but records which version the tokens are meant for.
+    pub fn read_tokens_for(&self, version: Version) -> Tokens {
+        Tokens::from_bytes(&self.data, Some(version))
+    }
+
     pub fn update_tokens(&mut self, tokens: Tokens) {
         self.data = tokens.into();
         self.update().unwrap()
     }
 
+    /// The bytes of this 8xp file.
+    pub fn write(&self) -> Vec<u8> {
+        self.to_bytes()
+            .expect("8xp lengths are updated whenever the tokens are.")
+    }
+
     fn checksum(&self) -> u16 {
         self.token_data_length.wrapping_add(
             self.data
@@ -119,4 +350,134 @@ mod tests {
 
         assert_eq!(x.checksum, 101);
     }
+
+    #[test]
+    fn new() {
+        let mut program = TIProgram::new(*b"HELLO\0\0\0");
+        program.update_tokens(Tokens::from_bytes(&[0xDE, 0x31], None));
+
+        let bytes = program.write();
+        assert_eq!(&bytes[..11], b"**TI83F*\x1A\x0A\x00");
+        assert_eq!(&bytes[11..26], b"Created by tibo");
+
+        let read = TIProgram::read(&bytes).unwrap();
+        assert_eq!(read.name, *b"HELLO\0\0\0");
+        assert_eq!(read.data, vec![0xDE, 0x31]);
+        assert_eq!(read.checksum, program.checksum());
+    }
+
+    #[test]
+    fn lengths() {
+        let data = include_bytes!("./test/TEST.8xp");
+        assert!(TIProgram::read(data).is_ok());
+
+        let mut truncated = data.to_vec();
+        truncated.truncate(data.len() - 10);
+        assert!(matches!(
+            TIProgram::read(&truncated),
+            Err(ReadError::Length(LengthMismatch {
+                field: LengthField::TokenDataLength,
+                claimed: 340,
+                expected: 330,
+            }))
+        ));
+
+        let mut edited = data.to_vec();
+        edited[57] += 1; // var_data_length
+        assert!(matches!(
+            TIProgram::read(&edited),
+            Err(ReadError::Length(LengthMismatch {
+                field: LengthField::VarDataLength,
+                claimed: 343,
+                expected: 342,
+            }))
+        ));
+
+        let mut edited = data.to_vec();
+        edited[53] -= 1; // data_length
+        let mismatch = TIProgram::read(&edited).unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            "Malformed 8xp header: the data length is 358 bytes, but should be 359 bytes"
+        );
+    }
+
+    fn data_comment(day: u8, month: u8, year_top: u8, year_bottom: u8) -> DataComment {
+        DataComment {
+            magic: 0xB8,
+            tool_id: DATA_COMMENT_TOOL_ID,
+            version: [0; 9],
+            export_day: day,
+            export_month: month,
+            export_year_top: year_top,
+            export_year_bottom: year_bottom,
+            author_len: 0,
+            author: vec![],
+            rest: vec![0; 24],
+        }
+    }
+
+    #[test]
+    fn export_date() {
+        assert_eq!(
+            data_comment(0x09, 0x12, 0x20, 0x24).export_date(),
+            NaiveDate::from_ymd_opt(2024, 12, 9)
+        );
+        assert_eq!(data_comment(0, 0, 0, 0).export_date(), None);
+        assert_eq!(data_comment(0x31, 0x02, 0x20, 0x24).export_date(), None);
+        assert_eq!(data_comment(0x0A, 0x01, 0x20, 0x24).export_date(), None);
+    }
+
+    #[test]
+    fn author() {
+        let mut comment = data_comment(0, 0, 0, 0);
+        let check = |comment: &DataComment| {
+            assert_eq!(comment.author_len as usize, comment.author.len());
+            assert_eq!(comment.author.len() + comment.rest.len(), 24);
+        };
+
+        comment.set_author("").unwrap();
+        assert_eq!(comment.author_string(), "");
+        check(&comment);
+
+        let longest = "ABCDEFGHIJKLMNOPQRSTUVWX";
+        comment.set_author(longest).unwrap();
+        assert_eq!(comment.author_string(), longest);
+        check(&comment);
+
+        assert_eq!(
+            comment.set_author("ABCDEFGHIJKLMNOPQRSTUVWXY"),
+            Err(AuthorError::TooLong)
+        );
+        assert_eq!(comment.set_author("\u{3b8}"), Err(AuthorError::NotAscii));
+        assert_eq!(comment.author_string(), longest);
+        check(&comment);
+
+        comment.set_author("tibo").unwrap();
+        assert_eq!(comment.author_string(), "tibo");
+        check(&comment);
+    }
+
+    #[test]
+    fn forced_export_date() {
+        let data = include_bytes!("./test/TEST.8xp");
+        let mut x = TIProgram::from_bytes((data.as_ref(), 0)).unwrap().1;
+        let date = NaiveDate::from_ymd_opt(2024, 12, 9).unwrap();
+
+        x.comment = Comment::Bytes(0, [0; 41]);
+        x.force_data_comment_dated(None, date);
+
+        let comment = x.data_comment().unwrap();
+        assert_eq!(
+            (
+                comment.export_day,
+                comment.export_month,
+                comment.export_year_top,
+                comment.export_year_bottom
+            ),
+            (0x09, 0x12, 0x20, 0x24)
+        );
+        assert_eq!(comment.export_date(), Some(date));
+        assert_eq!(comment.author.len() + comment.rest.len(), 24);
+    }
 }
@@ -1,9 +1,10 @@
 use radix_trie::{Trie, TrieCommon};
-use std::collections::{BTreeMap, Bound};
+use std::collections::{BTreeMap, BTreeSet, Bound};
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Range, RangeBounds};
 
-use crate::{Token, Tokens, Version};
+use crate::{xmlparse, Token, Tokens, Version};
+pub use xmlparse::SheetError;
 
 /// Immutable container for text with an extra layer of information stating where tokens start and
 /// end.
@@ -103,35 +104,149 @@ impl Debug for TokenBoundaries {
     }
 }
 
+/// Which of a token's names to use when converting it to text.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NameStyle {
+    /// ASCII names which can be typed and tokenized again, like `->` and `theta`.
+    Accessible,
+    /// Unicode names as displayed on the calculator, like `→` and `θ`.
+    Display,
+}
+
+impl Default for NameStyle {
+    /// [`NameStyle::Display`] if the `unicode` feature is enabled, otherwise
+    /// [`NameStyle::Accessible`].
+    fn default() -> Self {
+        if cfg!(feature = "unicode") {
+            NameStyle::Display
+        } else {
+            NameStyle::Accessible
+        }
+    }
+}
+
+/// How to write newline tokens.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// Options for [`Tokenizer::stringify_with`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct StringifyOptions {
+    pub names: NameStyle,
+    pub newline: Newline,
+}
+
 pub struct Tokenizer {
-    names: BTreeMap<crate::Token, String>,
+    accessible_names: BTreeMap<crate::Token, String>,
+    display_names: BTreeMap<crate::Token, String>,
     trie: Trie<String, crate::Token>,
+    available: BTreeSet<crate::Token>,
     version: Version,
 }
 
 impl Tokenizer {
     pub fn new(version: Version, lang_code: &str) -> Self {
-        let mut names = BTreeMap::new();
+        Self::from_data(
+            xmlparse::DATA.iter().map(|(key, value)| (*key, *value)),
+            version,
+            lang_code,
+        )
+    }
+
+    /// Like [`Tokenizer::new`], but reads the tokens from `sheet` instead of the built-in token
+    /// sheet. `sheet` must be in the same XML format as the
+    /// [TI-Toolkit token sheet](https://github.com/TI-Toolkit/tokens).
+    ///
+    /// Every token must have an English translation in its first version, which is used when
+    /// there is no translation in `lang_code`.
+    pub fn from_sheet(
+        mut sheet: impl std::io::Read,
+        version: Version,
+        lang_code: &str,
+    ) -> Result<Self, SheetError> {
+        let mut xml = String::new();
+        sheet.read_to_string(&mut xml).map_err(SheetError::Io)?;
+
+        let data = xmlparse::parse_sheet(&xml)?;
+
+        Ok(Self::from_data(
+            data.iter().map(|(key, value)| (*key, value)),
+            version,
+            lang_code,
+        ))
+    }
+
+    fn from_data<'a>(
+        data: impl Iterator<Item = (Token, &'a xmlparse::Token)>,
+        version: Version,
+        lang_code: &str,
+    ) -> Self {
+        let mut accessible_names = BTreeMap::new();
+        let mut display_names = BTreeMap::new();
         let mut trie = Trie::new();
+        let mut available = BTreeSet::new();
 
-        crate::xmlparse::DATA.iter().for_each(|(key, value)| {
-            if cfg!(feature = "unicode") {
-                names.insert(*key, value.at(&version, lang_code).display.clone());
-            } else {
-                names.insert(*key, value.at(&version, lang_code).accessible.clone());
+        data.for_each(|(key, value)| {
+            if value.exists_in(&version) {
+                available.insert(key);
             }
-            trie.insert(value.at(&version, lang_code).accessible.clone(), *key);
+
+            let translation = value.at(&version, lang_code);
+
+            accessible_names.insert(key, translation.accessible.clone());
+            display_names.insert(key, translation.display.clone());
+            trie.insert(translation.accessible.clone(), key);
         });
 
         trie.insert("\r\n".to_string(), Token::OneByte(0x3F));
 
         Tokenizer {
-            names,
+            accessible_names,
+            display_names,
             trie,
+            available,
             version,
         }
     }
 
+    /// True if `token` exists in this tokenizer's version. Every token in the sheet can be
+    /// tokenized and stringified regardless, so that programs for other models can be read.
+    pub fn is_available(&self, token: Token) -> bool {
+        self.available.contains(&token)
+    }
+
+    /// Every token name starting with `prefix`, with its token, sorted by name. Like
+    /// [`Tokenizer::tokenize`], this includes tokens which aren't
+    /// [available](Tokenizer::is_available) in this tokenizer's version.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use titokens::{Model, Token, Tokenizer, Version};
+    /// # let tokenizer = Tokenizer::new(Version { model: Model::TI84PCE, os_version: "5.3.0".to_string()},"en");
+    /// let names = tokenizer
+    ///     .completions("ClrL")
+    ///     .into_iter()
+    ///     .map(|(name, _)| name)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(names, vec!["ClrList "]);
+    /// ```
+    pub fn completions(&self, prefix: &str) -> Vec<(String, Token)> {
+        let Some(subtrie) = self.trie.get_raw_descendant(prefix) else {
+            return vec![];
+        };
+
+        // the closest descendant can also hold keys which only share part of `prefix`
+        subtrie
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(name, token)| (name.clone(), *token))
+            .collect()
+    }
+
     pub fn tokenize(&self, text: &str) -> Result<(Tokens, TokenBoundaries), ()> {
         let mut pos = 0;
         let mut boundaries = vec![];
@@ -159,10 +274,52 @@ impl Tokenizer {
         ))
     }
 
+    /// Convert tokens to text using the default [`StringifyOptions`].
     pub fn stringify(&self, tokens: &[Token]) -> TokenBoundaries {
+        self.stringify_with(tokens, &StringifyOptions::default())
+    }
+
+    /// Convert tokens to text.
+    pub fn stringify_with(&self, tokens: &[Token], options: &StringifyOptions) -> TokenBoundaries {
+        self.stringify_with_policy(tokens, options.newline, |_| options.names)
+    }
+
+    /// Convert tokens to text, choosing the [`NameStyle`] for each token with `policy`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use titokens::{Model, Token, Tokenizer, Version};
+    /// # use titokens::tokenizer::{NameStyle, Newline};
+    /// # let tokenizer = Tokenizer::new(Version { model: Model::TI84PCE, os_version: "5.3.0".to_string()},"en");
+    /// let (tokens, _) = tokenizer.tokenize("If A!=theta:1->B").unwrap();
+    /// let tokens = tokens.collect::<Vec<_>>();
+    ///
+    /// // Unicode for the store arrow and relational operators, accessible names for the rest.
+    /// let text = tokenizer.stringify_with_policy(&tokens, Newline::Lf, |token| match token {
+    ///     Token::OneByte(0x04 | 0x6A..=0x6F) => NameStyle::Display,
+    ///     _ => NameStyle::Accessible,
+    /// });
+    /// assert_eq!(text.to_string(), "If A≠theta:1→B");
+    /// ```
+    pub fn stringify_with_policy(
+        &self,
+        tokens: &[Token],
+        newline: Newline,
+        policy: impl Fn(Token) -> NameStyle,
+    ) -> TokenBoundaries {
         let strings = tokens
             .iter()
-            .map(|tok| self.names.get(tok).unwrap_or(&tok.string_escaped()).clone())
+            .map(|tok| match (tok, newline) {
+                (Token::OneByte(0x3F), Newline::CrLf) => "\r\n".to_string(),
+                _ => {
+                    let names = match policy(*tok) {
+                        NameStyle::Accessible => &self.accessible_names,
+                        NameStyle::Display => &self.display_names,
+                    };
+
+                    names.get(tok).unwrap_or(&tok.string_escaped()).clone()
+                }
+            })
             .collect::<Vec<String>>();
 
         let boundaries = strings
@@ -185,6 +342,81 @@ impl Tokenizer {
 mod tests {
     use super::*;
 
+    fn sheet_token(value: &str, lang: &str) -> String {
+        format!(
+            "<token value=\"{value}\"><version><since><model>TI-82</model><os-version>1.0</os-version></since>{lang}</version></token>"
+        )
+    }
+
+    #[test]
+    fn from_sheet() {
+        let version = Version {
+            model: crate::Model::TI84PCE,
+            os_version: "5.3.0".to_string(),
+        };
+        let lang = |code: &str, name: &str| {
+            format!("<lang code=\"{code}\" ti-ascii=\"00\" display=\"{name}\"><accessible>{name}</accessible></lang>")
+        };
+
+        let sheet = format!(
+            "<tokens>{}<two-byte value=\"$EF\">{}</two-byte></tokens>",
+            sheet_token("$41", &(lang("en", "A") + &lang("fr", "Ah"))),
+            sheet_token("$FF", &lang("en", "Widget(")),
+        );
+
+        let tokenizer = Tokenizer::from_sheet(sheet.as_bytes(), version.clone(), "en").unwrap();
+        let (tokens, _) = tokenizer.tokenize("Widget(A").unwrap();
+        assert_eq!(
+            tokens.collect::<Vec<_>>(),
+            vec![Token::TwoByte(0xEF, 0xFF), Token::OneByte(0x41)]
+        );
+
+        let tokenizer = Tokenizer::from_sheet(sheet.as_bytes(), version.clone(), "fr").unwrap();
+        assert_eq!(
+            tokenizer
+                .stringify(&[Token::TwoByte(0xEF, 0xFF), Token::OneByte(0x41)])
+                .to_string(),
+            "Widget(Ah"
+        );
+
+        let bad_value = format!("<tokens>{}</tokens>", sheet_token("$4G", &lang("en", "A")));
+        assert!(matches!(
+            Tokenizer::from_sheet(bad_value.as_bytes(), version.clone(), "en"),
+            Err(SheetError::InvalidValue(_))
+        ));
+
+        let french_only = format!("<tokens>{}</tokens>", sheet_token("$41", &lang("fr", "Ah")));
+        assert!(matches!(
+            Tokenizer::from_sheet(french_only.as_bytes(), version.clone(), "en"),
+            Err(SheetError::MissingTranslation(_))
+        ));
+
+        assert!(matches!(
+            Tokenizer::from_sheet("<tokens>".as_bytes(), version, "en"),
+            Err(SheetError::Xml(_))
+        ));
+    }
+
+    #[test]
+    fn is_available() {
+        use crate::version::{LATEST, LATEST_MONO};
+
+        let blue = Token::TwoByte(0xEF, 0x41);
+        let disp = Token::OneByte(0xDE);
+
+        let mono = Tokenizer::new(LATEST_MONO.clone(), "en");
+        assert!(mono.is_available(disp));
+        assert!(!mono.is_available(blue));
+        assert_eq!(
+            mono.tokenize("BLUE").unwrap().0.collect::<Vec<_>>(),
+            vec![blue]
+        );
+
+        let latest = Tokenizer::new(LATEST.clone(), "en");
+        assert!(latest.is_available(disp));
+        assert!(latest.is_available(blue));
+    }
+
     #[test]
     fn tokenize() {
         let tokenizer = Tokenizer::new(
@@ -243,4 +475,65 @@ mod tests {
         assert_eq!(boundaries.single(0), 0..1);
         assert_eq!(boundaries.single(2), 9..10);
     }
+
+    #[test]
+    fn stringify_with() {
+        let tokenizer = Tokenizer::new(
+            Version {
+                model: crate::Model::TI84PCE,
+                os_version: "5.3.0".to_string(),
+            },
+            "en",
+        );
+
+        let tokens = [
+            Token::OneByte(0x41),
+            Token::OneByte(0x04),
+            Token::OneByte(0x5B),
+            Token::OneByte(0x3F),
+        ];
+
+        let accessible = StringifyOptions {
+            names: NameStyle::Accessible,
+            newline: Newline::Lf,
+        };
+        let display = StringifyOptions {
+            names: NameStyle::Display,
+            newline: Newline::CrLf,
+        };
+
+        assert_eq!(
+            tokenizer.stringify_with(&tokens, &accessible).to_string(),
+            "A->theta\n"
+        );
+        assert_eq!(
+            tokenizer.stringify_with(&tokens, &display).to_string(),
+            "A→θ\r\n"
+        );
+    }
+
+    #[test]
+    fn completions() {
+        let tokenizer = Tokenizer::new(
+            Version {
+                model: crate::Model::TI84PCE,
+                os_version: "5.3.0".to_string(),
+            },
+            "en",
+        );
+
+        assert_eq!(
+            tokenizer.completions("Disp"),
+            vec![
+                ("Disp ".to_string(), Token::OneByte(0xDE)),
+                ("DispGraph".to_string(), Token::OneByte(0xDF)),
+                ("DispTable".to_string(), Token::OneByte(0xE5)),
+            ]
+        );
+        assert_eq!(
+            tokenizer.completions("getKey"),
+            vec![("getKey".to_string(), Token::OneByte(0xAD))]
+        );
+        assert!(tokenizer.completions("Dispx").is_empty());
+    }
 }
@@ -1,6 +1,8 @@
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Model {
@@ -64,6 +66,44 @@ impl Model {
     }
 }
 
+/// A model or OS version which isn't on the token sheet's timeline. See [`Model::from_str`] and
+/// [`Version::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownVersion(pub String);
+
+impl Display for UnknownVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown model or OS version \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for UnknownVersion {}
+
+impl FromStr for Model {
+    type Err = UnknownVersion;
+
+    /// Accepts the names used by the token sheets (eg. `TI-84+CE`), ignoring case, and `latest`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "TI-82" => Model::TI82,
+            "TI-83" => Model::TI83,
+            "TI-83+" => Model::TI83P,
+            "TI-84+" => Model::TI84P,
+            "TI-84+T" => Model::TI84PT,
+            "TI-82A" => Model::TI82A,
+            "TI-84+CSE" => Model::TI84PCSE,
+            "TI-84+CE" => Model::TI84PCE,
+            "TI-84+CE-T" => Model::TI84PCET,
+            "TI-83PCE" => Model::TI83PCE,
+            "TI-83PCEEP" => Model::TI83PCEEP,
+            "TI-84+CEPY" => Model::TI84PCEPY,
+            "TI-84+CE-TPE" => Model::TI84PCETPE,
+            "LATEST" => Model::LATEST,
+            _ => Err(UnknownVersion(s.to_string()))?,
+        })
+    }
+}
+
 impl PartialEq for Model {
     fn eq(&self, other: &Self) -> bool {
         self.value() == other.value()
@@ -127,6 +167,23 @@ lazy_static! {
     };
 }
 
+impl Version {
+    /// `os_version` must be dot-separated numbers, like `5.3.0`.
+    pub fn new(model: Model, os_version: &str) -> Result<Self, UnknownVersion> {
+        if os_version
+            .split('.')
+            .any(|n| n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()))
+        {
+            Err(UnknownVersion(os_version.to_string()))?
+        }
+
+        Ok(Version {
+            model,
+            os_version: os_version.to_string(),
+        })
+    }
+}
+
 fn cmp_os_version(a: &str, b: &str) -> Ordering {
     a.split('.')
         .map(|n| str::parse::<u64>(n).unwrap())
@@ -146,3 +203,21 @@ impl Ord for Version {
             .then_with(|| cmp_os_version(&self.os_version, &other.os_version))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("TI-84+CE".parse(), Ok(Model::TI84PCE));
+        assert_eq!("ti-84+".parse(), Ok(Model::TI84P));
+        assert_eq!("latest".parse(), Ok(Model::LATEST));
+        assert!("TI-89".parse::<Model>().is_err());
+
+        let version = Version::new(Model::TI84P, "2.55").unwrap();
+        assert!(version < *EARLIEST_COLOR);
+        assert!(Version::new(Model::TI84P, "2.55MP").is_err());
+        assert!(Version::new(Model::TI84P, "").is_err());
+    }
+}
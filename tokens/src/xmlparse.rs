@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
 use crate::Version;
 
@@ -41,6 +42,14 @@ impl Token {
 
         translation
     }
+
+    /// True if this token exists in `version`, i.e. if `version` falls in one of its
+    /// `[since, until)` ranges.
+    pub(crate) fn exists_in(&self, version: &Version) -> bool {
+        self.version
+            .iter()
+            .any(|v| v.since <= *version && v.until.as_ref().is_none_or(|until| version < until))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,34 +93,85 @@ pub(crate) struct Translation {
     pub(crate) variant: Vec<String>,
 }
 
-lazy_static::lazy_static! {
-    static ref PARSED: Tokens =
-        quick_xml::de::from_str::<Tokens>(include_str!("tokens/8X.xml")).unwrap();
-    pub(crate) static ref DATA: Vec<(crate::Token, &'static Token)> = PARSED.tokens
-        .iter()
-        .flat_map(|token_union: &TokenUnion| match token_union {
+/// Reasons [`Tokenizer::from_sheet`](crate::Tokenizer::from_sheet) can fail.
+#[derive(Debug)]
+pub enum SheetError {
+    Io(std::io::Error),
+    Xml(quick_xml::DeError),
+    /// A token's `value` is not a byte written in hex, like `$3F`.
+    InvalidValue(String),
+    /// A token has no versions, or its first version has no English name to fall back on.
+    MissingTranslation(String),
+}
+
+impl Display for SheetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SheetError::Io(err) => write!(f, "Failed to read token sheet: {err}"),
+            SheetError::Xml(err) => write!(f, "Malformed token sheet: {err}"),
+            SheetError::InvalidValue(value) => write!(f, "Invalid token value {value:?}"),
+            SheetError::MissingTranslation(value) => {
+                write!(
+                    f,
+                    "Token {value} has no English translation in its first version"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SheetError {}
+
+fn parse_byte(value: &str) -> Result<u8, SheetError> {
+    u8::from_str_radix(value.trim_start_matches('$'), 16)
+        .map_err(|_| SheetError::InvalidValue(value.to_string()))
+}
+
+/// Pair every token in the sheet with its byte representation.
+fn flatten(parsed: &Tokens) -> Result<Vec<(crate::Token, &Token)>, SheetError> {
+    let mut result = vec![];
+
+    for token_union in &parsed.tokens {
+        match token_union {
             TokenUnion::Token(tok) => {
-                vec![(
-                    crate::Token::OneByte(
-                        u8::from_str_radix(tok.value.trim_start_matches('$'), 16).unwrap(),
-                    ),
-                    tok,
-                )]
+                result.push((crate::Token::OneByte(parse_byte(&tok.value)?), tok));
+            }
+
+            TokenUnion::TwoByte(two_byte) => {
+                let first = parse_byte(&two_byte.value)?;
+                for tok in &two_byte.token {
+                    result.push((crate::Token::TwoByte(first, parse_byte(&tok.value)?), tok));
+                }
             }
+        }
+    }
+
+    Ok(result)
+}
 
-            TokenUnion::TwoByte(two_byte) => two_byte
-                .token
-                .iter()
-                .map(|tok| {
-                    (
-                        crate::Token::TwoByte(
-                            u8::from_str_radix(two_byte.value.trim_start_matches('$'), 16).unwrap(),
-                            u8::from_str_radix(tok.value.trim_start_matches('$'), 16).unwrap(),
-                        ),
-                        tok,
-                    )
-                })
-                .collect::<Vec<_>>(),
+/// Parse a token sheet in the same format as the built-in one.
+pub(crate) fn parse_sheet(xml: &str) -> Result<Vec<(crate::Token, Token)>, SheetError> {
+    let parsed = quick_xml::de::from_str::<Tokens>(xml).map_err(SheetError::Xml)?;
+
+    flatten(&parsed)?
+        .into_iter()
+        .map(|(key, tok)| {
+            if tok
+                .version
+                .first()
+                .and_then(|first| first.translation("en"))
+                .is_none()
+            {
+                return Err(SheetError::MissingTranslation(tok.value.clone()));
+            }
+
+            Ok((key, tok.clone()))
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    static ref PARSED: Tokens =
+        quick_xml::de::from_str::<Tokens>(include_str!("tokens/8X.xml")).unwrap();
+    pub(crate) static ref DATA: Vec<(crate::Token, &'static Token)> = flatten(&PARSED).unwrap();
 }